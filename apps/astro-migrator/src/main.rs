@@ -31,7 +31,7 @@ pub async fn main() -> Result<()> {
     let dump: Vec<Mod> = data.into();
 
     let user = NewUser {
-        github_id: -1,
+        github_id: Some(-1),
         username: "ModHost Migrator".into(),
     };
 
@@ -4,10 +4,10 @@ use diesel::{SelectableHelper, insert_into};
 use diesel_async::RunQueryDsl;
 use modhost_db::{
     DbConn, NewGalleryImage, NewProject, NewProjectFile, NewProjectVersion, Project, ProjectAuthor,
-    ProjectVersion, ProjectVisibility, gallery_images, project_authors, project_versions, projects,
-    version_files,
+    ProjectVersion, ProjectVisibility, VersionChannel, gallery_images, project_authors,
+    project_versions, projects, version_files,
 };
-use object_store::{ObjectStore, PutPayload, aws::AmazonS3};
+use object_store::{ObjectStore, PutPayload};
 use serde::{Deserialize, Serialize};
 use serde_this_or_that::{as_bool, as_i64};
 use sha1::{Digest, Sha1};
@@ -170,7 +170,7 @@ impl From<DumpMod> for Mod {
 }
 
 impl Version {
-    pub async fn upload(&self, bucket: &AmazonS3) -> Result<(String, i64)> {
+    pub async fn upload(&self, bucket: &dyn ObjectStore) -> Result<(String, i64)> {
         let mods_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
             .join("mods")
             .join("releaseMods");
@@ -198,7 +198,7 @@ impl Version {
         self,
         pkg: &Project,
         db: &mut DbConn,
-        bucket: &AmazonS3,
+        bucket: &dyn ObjectStore,
     ) -> Result<ProjectVersion> {
         let (id, size) = self.upload(bucket).await?;
         let file_name = self.release_file_name.clone();
@@ -216,6 +216,9 @@ impl Version {
             sha1: id,
             version_id: ver.id,
             size,
+            is_primary: true,
+            sha256: None,
+            sha512: None,
         };
 
         insert_into(version_files::table)
@@ -235,6 +238,7 @@ impl Version {
             loaders: vec![Some("AstroModIntegrator".into())],
             game_versions: vec![Some(self.astro_build)],
             downloads: 0,
+            channel: VersionChannel::Release,
         }
     }
 }
@@ -263,8 +267,8 @@ impl Mod {
         self,
         user_id: i32,
         db: &mut DbConn,
-        bucket: &AmazonS3,
-        imgs: &AmazonS3,
+        bucket: &dyn ObjectStore,
+        imgs: &dyn ObjectStore,
     ) -> Result<(Project, Vec<ProjectVersion>)> {
         let pkg = self.clone().into_pkg();
 
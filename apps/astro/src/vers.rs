@@ -109,6 +109,7 @@ pub async fn get_astro_versions() -> Result<Vec<GameVersion>> {
         .map(|v| GameVersion {
             id: v.clone(),
             beta: false,
+            release_date: None,
         })
         .collect())
 }
@@ -12,8 +12,8 @@ use modhost::init_logger;
 use modhost_config::get_config;
 use modhost_db::{
     NewProject, NewProjectFile, NewProjectVersion, NewUser, Project, ProjectAuthor, ProjectVersion,
-    ProjectVisibility, User, create_connection, project_authors, project_versions, projects,
-    run_migrations, users, version_files,
+    ProjectVisibility, User, VersionChannel, create_connection, project_authors, project_versions,
+    projects, run_migrations, users, version_files,
 };
 use object_store::{ObjectStore, PutPayload};
 use octocrab::Octocrab;
@@ -86,7 +86,7 @@ pub async fn run() -> Result<()> {
 
             if let std::collections::hash_map::Entry::Vacant(e) = added_users.entry(author_id) {
                 let user = NewUser {
-                    github_id: author_id as i32,
+                    github_id: Some(author_id as i32),
                     username: author_name,
                 };
 
@@ -156,6 +156,7 @@ pub async fn run() -> Result<()> {
                     .filter_map(get_version_str)
                     .map(Some)
                     .collect(),
+                channel: VersionChannel::Release,
             };
 
             let version: ProjectVersion = insert_into(project_versions::table)
@@ -170,6 +171,9 @@ pub async fn run() -> Result<()> {
                 s3_id: file_id.clone(),
                 sha1: file_id,
                 size: file_size,
+                is_primary: true,
+                sha256: None,
+                sha512: None,
             };
 
             insert_into(version_files::table)
@@ -13,6 +13,7 @@ quickhost::quickhost! {
     versions = [crate::get_minecraft_versions().await?];
     loaders = [modhost::loaders!["Forge", "Fabric", "Quilt", "NeoForge"]];
     verifier = [crate::verify_project];
+    extractor = [crate::extract_manifest];
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -50,37 +51,29 @@ pub async fn get_minecraft_versions() -> Result<Vec<GameVersion>> {
         .map(|v| GameVersion {
             id: v.id.clone(),
             beta: v.kind != "release",
+            release_date: v.release_time.parse().ok(),
         })
         .collect())
 }
 
 pub fn verify_project(bytes: Bytes) -> bool {
-    let mut data = GzDecoder::new(Cursor::new(bytes));
-    let mut gunzip = Vec::new();
-
-    if data.read_to_end(&mut gunzip).is_err() {
-        return false;
-    }
-
-    let mut archive = Archive::new(Cursor::new(gunzip));
+    extract_manifest(bytes).is_some()
+}
 
-    if let Ok(entries) = archive.entries() {
-        for mut entry in entries.flatten() {
-            if entry.path().unwrap_or_default().to_str().unwrap() == "kjspkg.json" {
-                let mut data = String::new();
+pub fn extract_manifest(bytes: Bytes) -> Option<ProjectManifest> {
+    // We may only get the start of the file, so the archive is read
+    // lazily instead of decompressing the whole thing up front.
+    let mut archive = Archive::new(GzDecoder::new(Cursor::new(bytes)));
 
-                if entry.read_to_string(&mut data).is_err() {
-                    return false;
-                }
+    for mut entry in archive.entries().ok()?.flatten() {
+        if entry.path().unwrap_or_default().to_str() == Some("kjspkg.json") {
+            let mut data = String::new();
 
-                if serde_json::from_str::<ProjectManifest>(&data).is_err() {
-                    return false;
-                }
+            entry.read_to_string(&mut data).ok()?;
 
-                return true;
-            }
+            return serde_json::from_str(&data).ok();
         }
     }
 
-    false
+    None
 }
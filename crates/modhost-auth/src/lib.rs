@@ -1,34 +1,108 @@
 #![warn(missing_docs)]
 //! ModHost's utilities for authentication.
 
+#[macro_use]
+extern crate serde;
+
+pub mod local;
+pub mod login;
+pub mod providers;
+
 use axum::http::HeaderMap;
 use axum_extra::extract::CookieJar;
+use chrono::Utc;
 use modhost_core::{AppError, Result};
-use modhost_db::{DbConn, User, get_user_for_token};
+use modhost_db::{
+    DbConn, PersonalAccessToken, TokenScope, User, UserToken, get_personal_token, get_session,
+    get_user, is_personal_token, touch_personal_token, touch_session,
+};
+
+/// Get the raw token from a request's `Authorization` header or `auth-token` cookie.
+pub fn get_token_from_req(jar: &CookieJar, headers: &HeaderMap) -> Result<String> {
+    if let Some(value) = headers.get("Authorization") {
+        let val = value.to_str()?;
+
+        if val.starts_with("Bearer ") {
+            return Ok(val.trim_start_matches("Bearer ").to_string());
+        }
+    }
+
+    jar.get("auth-token")
+        .map(|value| value.value().to_string())
+        .ok_or(AppError::MissingToken)
+}
+
+/// Get the personal access token a request was made with.
+/// This returns [`None`] if the request used a session token instead.
+pub async fn get_personal_token_from_req(
+    jar: &CookieJar,
+    headers: &HeaderMap,
+    conn: &mut DbConn,
+) -> Result<Option<PersonalAccessToken>> {
+    let token = get_token_from_req(jar, headers)?;
+
+    if !is_personal_token(&token) {
+        return Ok(None);
+    }
+
+    let token = get_personal_token(token, conn)
+        .await?
+        .ok_or(AppError::InvalidToken)?;
+
+    if token
+        .expires
+        .is_some_and(|expires| expires <= Utc::now().naive_utc())
+    {
+        return Err(AppError::InvalidToken);
+    }
+
+    Ok(Some(token))
+}
+
+/// Get the session a request was made with.
+/// This returns [`None`] if the request used a personal access token instead.
+pub async fn get_session_from_req(
+    jar: &CookieJar,
+    headers: &HeaderMap,
+    conn: &mut DbConn,
+) -> Result<Option<UserToken>> {
+    let token = get_token_from_req(jar, headers)?;
+
+    if is_personal_token(&token) {
+        return Ok(None);
+    }
+
+    Ok(Some(
+        get_session(token, conn)
+            .await?
+            .ok_or(AppError::InvalidToken)?,
+    ))
+}
 
 /// Get a user from a request if the token is present.
+/// Personal access tokens must have the given `scope`, while session
+/// tokens are allowed to do anything.
 pub async fn get_user_from_req(
     jar: &CookieJar,
     headers: &HeaderMap,
+    scope: TokenScope,
     conn: &mut DbConn,
 ) -> Result<User> {
-    let token = if let Some(value) = headers.get("Authorization") {
-        let val = value.to_str()?;
-
-        if val.starts_with("Bearer ") {
-            value.to_str()?.trim_start_matches("Bearer ").to_string()
-        } else if let Some(value) = jar.get("auth-token") {
-            value.value().to_string()
-        } else {
-            return Err(AppError::MissingToken);
+    if let Some(token) = get_personal_token_from_req(jar, headers, conn).await? {
+        if !token.has_scope(scope) {
+            return Err(AppError::MissingScope(scope.to_string()));
         }
-    } else if let Some(value) = jar.get("auth-token") {
-        value.value().to_string()
-    } else {
-        return Err(AppError::MissingToken);
-    };
 
-    get_user_for_token(token, conn)
+        touch_personal_token(&token, conn).await?;
+
+        return get_user(token.user_id.to_string(), conn).await;
+    }
+
+    let session = get_session_from_req(jar, headers, conn)
         .await?
-        .ok_or(AppError::UnknownUser)
+        .ok_or(AppError::InvalidToken)?;
+
+    touch_session(&session, conn).await?;
+
+    get_user(session.user_id.to_string(), conn).await
 }
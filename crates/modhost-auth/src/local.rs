@@ -0,0 +1,212 @@
+//! Local username and password accounts, for development and offline setups.
+
+use argon2::{
+    Argon2, PasswordHash, PasswordHasher, PasswordVerifier,
+    password_hash::{SaltString, rand_core::OsRng},
+};
+use chrono::Utc;
+use diesel::{
+    ExpressionMethods, OptionalExtension, QueryDsl, SelectableHelper, insert_into, update,
+};
+use diesel_async::RunQueryDsl;
+use modhost_config::{AuthConfigs, LocalAuthConfig};
+use modhost_core::{AppError, Result};
+use modhost_db::{
+    DbConn, LocalCredential, NewLocalCredential, NewUser, NewUserIdentity, User, UserIdentity,
+    find_user_identity, local_credentials, user_identities, username_taken, users,
+};
+use tokio::{sync::OnceCell, task::spawn_blocking};
+
+/// The ID of the local provider, stored with local identities.
+pub const LOCAL_PROVIDER: &str = "local";
+
+/// The longest username that's allowed for local accounts.
+pub const MAX_USERNAME_LENGTH: usize = 32;
+
+/// A hash that's checked against when a username doesn't exist,
+/// so failed logins take the same time either way.
+static DUMMY_HASH: OnceCell<String> = OnceCell::const_new();
+
+/// Get the config for local accounts, if they're enabled.
+/// They're only enabled when `auth.local` is configured.
+pub fn local_auth_config(config: &AuthConfigs) -> Option<LocalAuthConfig> {
+    config.local.clone()
+}
+
+/// Check that a username is allowed, returning it without surrounding whitespace.
+pub fn check_username(username: &str) -> Result<&str> {
+    let username = username.trim();
+
+    if username.is_empty() || username.len() > MAX_USERNAME_LENGTH {
+        return Err(AppError::InvalidUsername(format!(
+            "Usernames must be between 1 and {} characters long!",
+            MAX_USERNAME_LENGTH
+        )));
+    }
+
+    if !username
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    {
+        return Err(AppError::InvalidUsername(
+            "Usernames can only contain letters, numbers, dashes, underscores, and periods!".into(),
+        ));
+    }
+
+    Ok(username)
+}
+
+/// Check that a password is long enough.
+pub fn check_password(password: &str, config: &LocalAuthConfig) -> Result<()> {
+    if password.chars().count() < config.min_password_length {
+        return Err(AppError::PasswordTooShort(config.min_password_length));
+    }
+
+    Ok(())
+}
+
+/// Hash a password with argon2.
+pub async fn hash_password(password: impl Into<String>) -> Result<String> {
+    let password = password.into();
+
+    spawn_blocking(move || {
+        Argon2::default()
+            .hash_password(password.as_bytes(), &SaltString::generate(&mut OsRng))
+            .map(|v| v.to_string())
+            .map_err(|err| AppError::AuthFailed(err.to_string()))
+    })
+    .await?
+}
+
+/// Check a password against an argon2 hash.
+pub async fn verify_password(password: impl Into<String>, hash: impl Into<String>) -> Result<bool> {
+    let password = password.into();
+    let hash = hash.into();
+
+    spawn_blocking(move || {
+        let hash = PasswordHash::new(&hash).map_err(|err| AppError::AuthFailed(err.to_string()))?;
+
+        Ok(Argon2::default()
+            .verify_password(password.as_bytes(), &hash)
+            .is_ok())
+    })
+    .await?
+}
+
+/// Get the local identity for a username.
+async fn get_local_identity(username: &str, conn: &mut DbConn) -> Result<Option<UserIdentity>> {
+    find_user_identity(LOCAL_PROVIDER, username.to_lowercase(), conn).await
+}
+
+/// Set the password of a local identity.
+async fn set_password(identity: &UserIdentity, password: &str, conn: &mut DbConn) -> Result<()> {
+    let credential = NewLocalCredential {
+        identity_id: identity.id,
+        password_hash: hash_password(password).await?,
+    };
+
+    insert_into(local_credentials::table)
+        .values(&credential)
+        .on_conflict(local_credentials::identity_id)
+        .do_update()
+        .set((
+            &credential,
+            local_credentials::updated_at.eq(Utc::now().naive_utc()),
+        ))
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+/// Create a local account.
+/// The username must not be used by any other user, local or not.
+pub async fn create_local_user(username: &str, password: &str, conn: &mut DbConn) -> Result<User> {
+    let username = check_username(username)?;
+
+    if username_taken(username, None, conn).await?
+        || get_local_identity(username, conn).await?.is_some()
+    {
+        return Err(AppError::UsernameTaken);
+    }
+
+    let user = insert_into(users::table)
+        .values(&NewUser {
+            username: username.into(),
+            github_id: None,
+        })
+        .returning(User::as_returning())
+        .get_result(conn)
+        .await?;
+
+    let identity = insert_into(user_identities::table)
+        .values(&NewUserIdentity {
+            user_id: user.id,
+            provider: LOCAL_PROVIDER.into(),
+            subject: username.to_lowercase(),
+            username: username.into(),
+            email: None,
+        })
+        .returning(UserIdentity::as_returning())
+        .get_result(conn)
+        .await?;
+
+    set_password(&identity, password, conn).await?;
+
+    Ok(user)
+}
+
+/// Create a local admin account, or make an existing local account an admin
+/// and reset its password. This is used to set up the first admin.
+pub async fn create_local_admin(username: &str, password: &str, conn: &mut DbConn) -> Result<User> {
+    let user_id = match get_local_identity(username.trim(), conn).await? {
+        Some(identity) => {
+            set_password(&identity, password, conn).await?;
+            identity.user_id
+        }
+
+        None => create_local_user(username, password, conn).await?.id,
+    };
+
+    Ok(update(users::table)
+        .filter(users::id.eq(user_id))
+        .set(users::admin.eq(true))
+        .returning(User::as_returning())
+        .get_result(conn)
+        .await?)
+}
+
+/// Log in to a local account, returning its user if the password is right.
+pub async fn authenticate(username: &str, password: &str, conn: &mut DbConn) -> Result<User> {
+    let credential = match get_local_identity(username.trim(), conn).await? {
+        Some(identity) => local_credentials::table
+            .find(identity.id)
+            .select(LocalCredential::as_select())
+            .first(conn)
+            .await
+            .optional()?
+            .map(|v| (identity, v)),
+
+        None => None,
+    };
+
+    let Some((identity, credential)) = credential else {
+        let hash = DUMMY_HASH
+            .get_or_try_init(|| hash_password(LOCAL_PROVIDER))
+            .await?;
+
+        verify_password(password, hash).await?;
+
+        return Err(AppError::InvalidCredentials);
+    };
+
+    if !verify_password(password, credential.password_hash).await? {
+        return Err(AppError::InvalidCredentials);
+    }
+
+    Ok(users::table
+        .find(identity.user_id)
+        .select(User::as_select())
+        .first(conn)
+        .await?)
+}
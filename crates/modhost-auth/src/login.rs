@@ -0,0 +1,98 @@
+//! Signed login request states.
+
+use base64::{Engine, prelude::BASE64_URL_SAFE_NO_PAD};
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use modhost_core::{AppError, Result};
+use random_string::{charsets::ALPHANUMERIC, generate};
+use sha2::Sha256;
+
+/// How long a login request is valid for, in seconds.
+/// Calculation: 10 * SECS_PER_MIN
+pub const LOGIN_STATE_EXPIRE_TIME: i64 = 10 * 60;
+
+/// The length of a login request's nonce.
+pub const LOGIN_NONCE_LENGTH: usize = 32;
+
+/// The name of the cookie that ties a login request to the browser that started it.
+pub const LOGIN_COOKIE: &str = "oauth-login";
+
+/// A login request, sent to the provider as the OAuth2 `state` and checked in the callback.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LoginState {
+    /// The ID of the provider the login was started with.
+    pub provider: String,
+
+    /// A random value that must match the one in the browser's login cookie.
+    pub nonce: String,
+
+    /// Where to redirect to after logging in.
+    pub to: Option<String>,
+
+    /// When this request expires, as a UNIX timestamp in seconds.
+    pub expires: i64,
+}
+
+impl LoginState {
+    /// Create a login request for a provider.
+    pub fn new(provider: impl AsRef<str>, to: Option<String>) -> Self {
+        Self {
+            provider: provider.as_ref().to_string(),
+            nonce: generate(LOGIN_NONCE_LENGTH, ALPHANUMERIC),
+            to,
+            expires: Utc::now().timestamp() + LOGIN_STATE_EXPIRE_TIME,
+        }
+    }
+
+    /// Encode and sign this request with a secret.
+    pub fn sign(&self, secret: impl AsRef<[u8]>) -> Result<String> {
+        let payload = BASE64_URL_SAFE_NO_PAD.encode(serde_json::to_vec(self)?);
+        let signature =
+            BASE64_URL_SAFE_NO_PAD.encode(mac(secret, &payload)?.finalize().into_bytes());
+
+        Ok(format!("{}.{}", payload, signature))
+    }
+
+    /// Check the signature and expiry of a signed request, and decode it.
+    pub fn verify(value: impl AsRef<str>, secret: impl AsRef<[u8]>) -> Result<Self> {
+        let (payload, signature) = value
+            .as_ref()
+            .split_once('.')
+            .ok_or(AppError::InvalidLoginState)?;
+
+        let signature = BASE64_URL_SAFE_NO_PAD
+            .decode(signature)
+            .map_err(|_| AppError::InvalidLoginState)?;
+
+        mac(secret, payload)?
+            .verify_slice(&signature)
+            .map_err(|_| AppError::InvalidLoginState)?;
+
+        let state: Self = BASE64_URL_SAFE_NO_PAD
+            .decode(payload)
+            .ok()
+            .and_then(|v| serde_json::from_slice(&v).ok())
+            .ok_or(AppError::InvalidLoginState)?;
+
+        if state.expires <= Utc::now().timestamp() {
+            return Err(AppError::InvalidLoginState);
+        }
+
+        Ok(state)
+    }
+}
+
+/// Create an HMAC over a payload.
+fn mac(secret: impl AsRef<[u8]>, payload: &str) -> Result<Hmac<Sha256>> {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_ref()).map_err(|_| AppError::InvalidLoginState)?;
+
+    mac.update(payload.as_bytes());
+
+    Ok(mac)
+}
+
+/// Generate a random secret for signing login requests.
+pub fn generate_state_secret() -> String {
+    generate(64, ALPHANUMERIC)
+}
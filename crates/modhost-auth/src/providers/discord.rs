@@ -0,0 +1,63 @@
+//! The Discord login provider.
+
+use super::{AuthProvider, OAuthClient, ProviderIdentity, get_json, oauth_client};
+use async_trait::async_trait;
+use modhost_config::AuthConfig;
+use modhost_core::Result;
+
+/// A Discord user, from the Discord API.
+#[derive(Deserialize)]
+struct DiscordUser {
+    id: String,
+    username: String,
+    email: Option<String>,
+}
+
+/// The Discord login provider.
+pub struct DiscordProvider {
+    /// The provider's configuration.
+    config: AuthConfig,
+}
+
+impl DiscordProvider {
+    /// Create a new Discord provider.
+    pub fn new(config: AuthConfig) -> Self {
+        Self { config }
+    }
+}
+
+#[async_trait]
+impl AuthProvider for DiscordProvider {
+    fn id(&self) -> &str {
+        "discord"
+    }
+
+    fn name(&self) -> &str {
+        "Discord"
+    }
+
+    fn scopes(&self) -> Vec<String> {
+        vec!["identify".into(), "email".into()]
+    }
+
+    async fn client(&self) -> Result<OAuthClient> {
+        oauth_client(
+            &self.config.client_id,
+            &self.config.client_secret,
+            "https://discord.com/oauth2/authorize",
+            "https://discord.com/api/oauth2/token",
+        )
+    }
+
+    async fn identity(&self, access_token: &str) -> Result<ProviderIdentity> {
+        let user: DiscordUser =
+            get_json("https://discord.com/api/users/@me", Some(access_token)).await?;
+
+        Ok(ProviderIdentity {
+            subject: user.id,
+            username: user.username,
+            email: user.email,
+            github_id: None,
+        })
+    }
+}
@@ -0,0 +1,66 @@
+//! The GitHub login provider.
+
+use super::{AuthProvider, OAuthClient, ProviderIdentity, get_json, oauth_client};
+use async_trait::async_trait;
+use modhost_config::AuthConfig;
+use modhost_core::Result;
+
+/// A GitHub user, from the GitHub API.
+#[derive(Deserialize)]
+struct GitHubUser {
+    id: i32,
+    login: String,
+    email: Option<String>,
+}
+
+/// The GitHub login provider.
+pub struct GitHubProvider {
+    /// The provider's configuration.
+    config: AuthConfig,
+}
+
+impl GitHubProvider {
+    /// Create a new GitHub provider.
+    pub fn new(config: AuthConfig) -> Self {
+        Self { config }
+    }
+}
+
+#[async_trait]
+impl AuthProvider for GitHubProvider {
+    fn id(&self) -> &str {
+        "github"
+    }
+
+    fn name(&self) -> &str {
+        "GitHub"
+    }
+
+    fn scopes(&self) -> Vec<String> {
+        vec![
+            "user:email".into(),
+            "read:user".into(),
+            "public_repo".into(),
+        ]
+    }
+
+    async fn client(&self) -> Result<OAuthClient> {
+        oauth_client(
+            &self.config.client_id,
+            &self.config.client_secret,
+            "https://github.com/login/oauth/authorize",
+            "https://github.com/login/oauth/access_token",
+        )
+    }
+
+    async fn identity(&self, access_token: &str) -> Result<ProviderIdentity> {
+        let user: GitHubUser = get_json("https://api.github.com/user", Some(access_token)).await?;
+
+        Ok(ProviderIdentity {
+            subject: user.id.to_string(),
+            username: user.login,
+            email: user.email,
+            github_id: Some(user.id),
+        })
+    }
+}
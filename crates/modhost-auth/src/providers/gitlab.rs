@@ -0,0 +1,67 @@
+//! The GitLab login provider.
+
+use super::{AuthProvider, OAuthClient, ProviderIdentity, get_json, oauth_client};
+use async_trait::async_trait;
+use modhost_config::GitLabAuthConfig;
+use modhost_core::Result;
+
+/// A GitLab user, from the GitLab API.
+#[derive(Deserialize)]
+struct GitLabUser {
+    id: i64,
+    username: String,
+    email: Option<String>,
+}
+
+/// The GitLab login provider. This works with gitlab.com and self-hosted instances.
+pub struct GitLabProvider {
+    /// The provider's configuration.
+    config: GitLabAuthConfig,
+}
+
+impl GitLabProvider {
+    /// Create a new GitLab provider.
+    pub fn new(config: GitLabAuthConfig) -> Self {
+        Self { config }
+    }
+
+    /// Get the URL of a path on the GitLab instance.
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.config.url.trim_end_matches('/'), path)
+    }
+}
+
+#[async_trait]
+impl AuthProvider for GitLabProvider {
+    fn id(&self) -> &str {
+        "gitlab"
+    }
+
+    fn name(&self) -> &str {
+        "GitLab"
+    }
+
+    fn scopes(&self) -> Vec<String> {
+        vec!["read_user".into()]
+    }
+
+    async fn client(&self) -> Result<OAuthClient> {
+        oauth_client(
+            &self.config.client_id,
+            &self.config.client_secret,
+            self.url("/oauth/authorize"),
+            self.url("/oauth/token"),
+        )
+    }
+
+    async fn identity(&self, access_token: &str) -> Result<ProviderIdentity> {
+        let user: GitLabUser = get_json(self.url("/api/v4/user"), Some(access_token)).await?;
+
+        Ok(ProviderIdentity {
+            subject: user.id.to_string(),
+            username: user.username,
+            email: user.email,
+            github_id: None,
+        })
+    }
+}
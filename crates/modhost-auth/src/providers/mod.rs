@@ -0,0 +1,188 @@
+//! Login providers.
+
+mod discord;
+mod github;
+mod gitlab;
+mod oidc;
+
+pub use discord::*;
+pub use github::*;
+pub use gitlab::*;
+pub use oidc::*;
+
+use crate::{local::local_auth_config, login::generate_state_secret};
+use async_trait::async_trait;
+use modhost_config::{AuthConfigs, LocalAuthConfig};
+use modhost_core::{AppError, Result};
+use oauth2::{
+    AuthUrl, ClientId, ClientSecret, EndpointNotSet, EndpointSet, TokenUrl, basic::BasicClient,
+};
+use reqwest::header::{ACCEPT, USER_AGENT};
+use serde::de::DeserializeOwned;
+use std::sync::Arc;
+
+/// An OAuth2 client with its authorization and token URLs set.
+pub type OAuthClient =
+    BasicClient<EndpointSet, EndpointNotSet, EndpointNotSet, EndpointNotSet, EndpointSet>;
+
+/// The `User-Agent` sent with requests to providers' APIs (GitHub requires one).
+const PROVIDER_USER_AGENT: &str = concat!("ModHost/", env!("CARGO_PKG_VERSION"));
+
+/// An account's information, fetched from an auth provider.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProviderIdentity {
+    /// The account's unique ID with the provider.
+    pub subject: String,
+
+    /// The account's username.
+    pub username: String,
+
+    /// The account's email address, if the provider shared it.
+    pub email: Option<String>,
+
+    /// The account's GitHub ID. This is only set by the GitHub provider,
+    /// and is kept on the user for compatibility.
+    pub github_id: Option<i32>,
+}
+
+/// A provider users can log in with, using the OAuth2 authorization code flow.
+#[async_trait]
+pub trait AuthProvider: Send + Sync {
+    /// The provider's ID. This is used in the login URLs, and stored with identities.
+    fn id(&self) -> &str;
+
+    /// The provider's display name.
+    fn name(&self) -> &str;
+
+    /// The scopes to request.
+    fn scopes(&self) -> Vec<String>;
+
+    /// Get the OAuth2 client for this provider.
+    async fn client(&self) -> Result<OAuthClient>;
+
+    /// Get the information of the account an access token belongs to.
+    async fn identity(&self, access_token: &str) -> Result<ProviderIdentity>;
+}
+
+/// The login providers enabled on the server.
+#[derive(Clone)]
+pub struct AuthProviders {
+    /// The providers, in the order they're shown to users.
+    providers: Vec<Arc<dyn AuthProvider>>,
+
+    /// The secret used to sign login requests.
+    state_secret: Arc<str>,
+
+    /// The config for local accounts, if they're enabled.
+    local: Option<LocalAuthConfig>,
+}
+
+impl Default for AuthProviders {
+    fn default() -> Self {
+        Self {
+            providers: Vec::new(),
+            state_secret: generate_state_secret().into(),
+            local: None,
+        }
+    }
+}
+
+impl AuthProviders {
+    /// Create the providers that are configured.
+    pub fn new(config: &AuthConfigs) -> Self {
+        let mut providers = Self::default();
+
+        if let Some(secret) = &config.state_secret {
+            providers.state_secret = secret.as_str().into();
+        }
+
+        providers.local = local_auth_config(config);
+
+        if let Some(github) = &config.github {
+            providers.push(GitHubProvider::new(github.clone()));
+        }
+
+        if let Some(gitlab) = &config.gitlab {
+            providers.push(GitLabProvider::new(gitlab.clone()));
+        }
+
+        if let Some(discord) = &config.discord {
+            providers.push(DiscordProvider::new(discord.clone()));
+        }
+
+        if let Some(oidc) = &config.oidc {
+            providers.push(OidcProvider::new(oidc.clone()));
+        }
+
+        providers
+    }
+
+    /// Add a provider. This replaces any provider with the same ID.
+    pub fn push(&mut self, provider: impl AuthProvider + 'static) {
+        self.providers.retain(|v| v.id() != provider.id());
+        self.providers.push(Arc::new(provider));
+    }
+
+    /// Get a provider by its ID.
+    pub fn get(&self, id: impl AsRef<str>) -> Option<Arc<dyn AuthProvider>> {
+        self.providers
+            .iter()
+            .find(|v| v.id() == id.as_ref())
+            .cloned()
+    }
+
+    /// Get the secret used to sign login requests.
+    pub fn state_secret(&self) -> &str {
+        &self.state_secret
+    }
+
+    /// Get the config for local accounts, if they're enabled.
+    pub fn local(&self) -> Option<&LocalAuthConfig> {
+        self.local.as_ref()
+    }
+
+    /// Iterate over the providers.
+    pub fn iter(&self) -> impl Iterator<Item = &Arc<dyn AuthProvider>> {
+        self.providers.iter()
+    }
+}
+
+/// Create an OAuth2 client.
+pub(crate) fn oauth_client(
+    client_id: &str,
+    client_secret: &str,
+    auth_url: impl Into<String>,
+    token_url: impl Into<String>,
+) -> Result<OAuthClient> {
+    Ok(BasicClient::new(ClientId::new(client_id.into()))
+        .set_client_secret(ClientSecret::new(client_secret.into()))
+        .set_auth_uri(AuthUrl::new(auth_url.into())?)
+        .set_token_uri(TokenUrl::new(token_url.into())?))
+}
+
+/// Get JSON from a provider's API, optionally authenticating with an access token.
+pub(crate) async fn get_json<T: DeserializeOwned>(
+    url: impl AsRef<str>,
+    access_token: Option<&str>,
+) -> Result<T> {
+    let mut req = reqwest::Client::new()
+        .get(url.as_ref())
+        .header(ACCEPT, "application/json")
+        .header(USER_AGENT, PROVIDER_USER_AGENT);
+
+    if let Some(token) = access_token {
+        req = req.bearer_auth(token);
+    }
+
+    let res = req.send().await?;
+
+    if !res.status().is_success() {
+        return Err(AppError::AuthFailed(format!(
+            "{} responded with {}",
+            url.as_ref(),
+            res.status()
+        )));
+    }
+
+    Ok(res.json().await?)
+}
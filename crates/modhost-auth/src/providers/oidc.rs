@@ -0,0 +1,107 @@
+//! The generic OpenID Connect login provider.
+
+use super::{AuthProvider, OAuthClient, ProviderIdentity, get_json, oauth_client};
+use async_trait::async_trait;
+use modhost_config::OidcAuthConfig;
+use modhost_core::{AppError, Result};
+use serde_json::Value;
+use tokio::sync::OnceCell;
+
+/// The parts of an OpenID Connect discovery document that are used.
+#[derive(Debug, Clone, Deserialize)]
+struct OidcDiscovery {
+    authorization_endpoint: String,
+    token_endpoint: String,
+    userinfo_endpoint: String,
+}
+
+/// A generic OpenID Connect login provider.
+/// Its endpoints are discovered from the issuer the first time they're needed,
+/// so the server can start while the issuer is unreachable.
+pub struct OidcProvider {
+    /// The provider's configuration.
+    config: OidcAuthConfig,
+
+    /// The issuer's discovery document.
+    discovery: OnceCell<OidcDiscovery>,
+}
+
+impl OidcProvider {
+    /// Create a new OpenID Connect provider.
+    pub fn new(config: OidcAuthConfig) -> Self {
+        Self {
+            config,
+            discovery: OnceCell::new(),
+        }
+    }
+
+    /// Get the issuer's discovery document, fetching it if needed.
+    async fn discovery(&self) -> Result<&OidcDiscovery> {
+        self.discovery
+            .get_or_try_init(|| {
+                get_json(
+                    format!(
+                        "{}/.well-known/openid-configuration",
+                        self.config.issuer.trim_end_matches('/')
+                    ),
+                    None,
+                )
+            })
+            .await
+    }
+}
+
+/// Get a claim as a string. Numbers are converted to strings.
+fn claim(info: &Value, name: &str) -> Option<String> {
+    match info.get(name)? {
+        Value::String(value) => Some(value.clone()),
+        Value::Number(value) => Some(value.to_string()),
+        _ => None,
+    }
+}
+
+#[async_trait]
+impl AuthProvider for OidcProvider {
+    fn id(&self) -> &str {
+        "oidc"
+    }
+
+    fn name(&self) -> &str {
+        &self.config.name
+    }
+
+    fn scopes(&self) -> Vec<String> {
+        self.config.scopes.clone()
+    }
+
+    async fn client(&self) -> Result<OAuthClient> {
+        let discovery = self.discovery().await?;
+
+        oauth_client(
+            &self.config.client_id,
+            &self.config.client_secret,
+            &discovery.authorization_endpoint,
+            &discovery.token_endpoint,
+        )
+    }
+
+    async fn identity(&self, access_token: &str) -> Result<ProviderIdentity> {
+        let discovery = self.discovery().await?;
+        let info: Value = get_json(&discovery.userinfo_endpoint, Some(access_token)).await?;
+        let claims = &self.config.claims;
+
+        let subject = claim(&info, &claims.subject).ok_or_else(|| {
+            AppError::AuthFailed(format!(
+                "The user info is missing the `{}` claim",
+                claims.subject
+            ))
+        })?;
+
+        Ok(ProviderIdentity {
+            username: claim(&info, &claims.username).unwrap_or_else(|| subject.clone()),
+            email: claim(&info, &claims.email),
+            subject,
+            github_id: None,
+        })
+    }
+}
@@ -1,7 +1,7 @@
 //! Admin panel configuration.
 
 use duration_str::{HumanFormat, deserialize_duration};
-use serde::Serializer;
+use serde::{Deserializer, Serializer, de::Error};
 use std::time::Duration;
 
 /// The ModHost admin panel configuration.
@@ -25,3 +25,14 @@ impl Default for AdminConfig {
 pub fn serialize_duration<S: Serializer>(val: &Duration, ser: S) -> Result<S::Ok, S::Error> {
     ser.serialize_str(&val.human_format())
 }
+
+/// Deserialize a duration that's used as an interval, which can't be zero.
+pub fn deserialize_interval<'de, D: Deserializer<'de>>(de: D) -> Result<Duration, D::Error> {
+    let val = deserialize_duration(de)?;
+
+    if val.is_zero() {
+        return Err(D::Error::custom("intervals must be longer than zero"));
+    }
+
+    Ok(val)
+}
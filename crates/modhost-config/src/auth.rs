@@ -1,15 +1,42 @@
 //! ModHost's auth configuration.
 
-use modhost_core::Result;
-use oauth2::{
-    AuthUrl, ClientId, ClientSecret, EndpointNotSet, EndpointSet, TokenUrl, basic::BasicClient,
-};
-
 /// ModHost's auth methods configuration.
+/// Each provider is only enabled if it's configured.
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct AuthConfigs {
     /// The config for GitHub's OAuth2 system.
-    pub github: AuthConfig,
+    #[serde(default)]
+    pub github: Option<AuthConfig>,
+
+    /// The config for GitLab's OAuth2 system.
+    #[serde(default)]
+    pub gitlab: Option<GitLabAuthConfig>,
+
+    /// The config for Discord's OAuth2 system.
+    #[serde(default)]
+    pub discord: Option<AuthConfig>,
+
+    /// The config for a generic OpenID Connect provider.
+    #[serde(default)]
+    pub oidc: Option<OidcAuthConfig>,
+
+    /// The config for local username and password accounts.
+    /// These are meant for development and offline (air-gapped) setups.
+    /// They're disabled unless this is set.
+    #[serde(default)]
+    pub local: Option<LocalAuthConfig>,
+
+    /// The secret used to sign the `state` of login requests.
+    /// If this isn't set, a random one is generated on startup, so logins that
+    /// are in progress during a restart (or that span multiple instances) will fail.
+    #[serde(default)]
+    pub state_secret: Option<String>,
+
+    /// The origins (like `"https://example.com"`) that logins are allowed to redirect
+    /// back to with the new token in the URL. The server's own origin is always allowed.
+    /// Defaults to `[]`
+    #[serde(default)]
+    pub redirect_allowlist: Vec<String>,
 }
 
 /// The configuration for an OAuth2 configuration.
@@ -22,21 +49,126 @@ pub struct AuthConfig {
     pub client_secret: String,
 }
 
-impl AuthConfigs {
-    /// Get the OAuth2 client for GitHub.
-    pub fn github(
-        &self,
-    ) -> Result<BasicClient<EndpointSet, EndpointNotSet, EndpointNotSet, EndpointNotSet, EndpointSet>>
-    {
-        Ok(
-            BasicClient::new(ClientId::new(self.github.client_id.clone()))
-                .set_client_secret(ClientSecret::new(self.github.client_secret.clone()))
-                .set_auth_uri(AuthUrl::new(
-                    "https://github.com/login/oauth/authorize".to_string(),
-                )?)
-                .set_token_uri(TokenUrl::new(
-                    "https://github.com/login/oauth/access_token".to_string(),
-                )?),
-        )
+/// The configuration for local username and password accounts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocalAuthConfig {
+    /// Whether anyone can create an account.
+    /// If this is disabled, accounts can only be created with the `create-admin` command.
+    /// Defaults to `false`
+    #[serde(default)]
+    pub allow_registration: bool,
+
+    /// The shortest password that's allowed.
+    /// Defaults to `8`
+    #[serde(default = "default_min_password_length")]
+    pub min_password_length: usize,
+}
+
+/// The configuration for GitLab's OAuth2 system.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitLabAuthConfig {
+    /// The OAuth2 client ID.
+    pub client_id: String,
+
+    /// The OAuth2 client secret.
+    pub client_secret: String,
+
+    /// The base URL of the GitLab instance, for self-hosted ones.
+    /// Defaults to `"https://gitlab.com"`
+    #[serde(default = "default_gitlab_url")]
+    pub url: String,
+}
+
+/// The configuration for a generic OpenID Connect provider.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OidcAuthConfig {
+    /// The OAuth2 client ID.
+    pub client_id: String,
+
+    /// The OAuth2 client secret.
+    pub client_secret: String,
+
+    /// The issuer URL. The provider's endpoints are discovered from
+    /// `{issuer}/.well-known/openid-configuration`.
+    pub issuer: String,
+
+    /// The provider's display name.
+    /// Defaults to `"OpenID Connect"`
+    #[serde(default = "default_oidc_name")]
+    pub name: String,
+
+    /// The scopes to request.
+    /// Defaults to `["openid", "profile", "email"]`
+    #[serde(default = "default_oidc_scopes")]
+    pub scopes: Vec<String>,
+
+    /// Which user info claims hold the user's information.
+    #[serde(default)]
+    pub claims: OidcClaimsConfig,
+}
+
+/// The names of the user info claims that hold a user's information.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OidcClaimsConfig {
+    /// The claim with the user's unique ID.
+    /// Defaults to `"sub"`
+    #[serde(default = "default_subject_claim")]
+    pub subject: String,
+
+    /// The claim with the user's username.
+    /// Defaults to `"preferred_username"`
+    #[serde(default = "default_username_claim")]
+    pub username: String,
+
+    /// The claim with the user's email address.
+    /// Defaults to `"email"`
+    #[serde(default = "default_email_claim")]
+    pub email: String,
+}
+
+fn default_min_password_length() -> usize {
+    8
+}
+
+fn default_gitlab_url() -> String {
+    "https://gitlab.com".into()
+}
+
+fn default_oidc_name() -> String {
+    "OpenID Connect".into()
+}
+
+fn default_oidc_scopes() -> Vec<String> {
+    vec!["openid".into(), "profile".into(), "email".into()]
+}
+
+fn default_subject_claim() -> String {
+    "sub".into()
+}
+
+fn default_username_claim() -> String {
+    "preferred_username".into()
+}
+
+fn default_email_claim() -> String {
+    "email".into()
+}
+
+impl Default for LocalAuthConfig {
+    fn default() -> Self {
+        Self {
+            allow_registration: false,
+            min_password_length: default_min_password_length(),
+        }
+    }
+}
+
+impl Default for OidcClaimsConfig {
+    fn default() -> Self {
+        Self {
+            subject: default_subject_claim(),
+            username: default_username_claim(),
+            email: default_email_claim(),
+        }
     }
 }
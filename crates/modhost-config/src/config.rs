@@ -1,6 +1,10 @@
 //! The main config models.
 
-use crate::{AdminConfig, AuthConfigs, MeilisearchConfig, PostgresConfig, StorageConfig, UIConfig};
+use crate::{
+    AdminConfig, AuthConfigs, MeilisearchConfig, PostgresConfig, ScanConfig, StorageConfig,
+    UIConfig,
+};
+use ipnet::IpNet;
 use modhost_core::Result;
 use std::fs;
 
@@ -16,7 +20,7 @@ pub struct AppConfig {
     /// The authentication configuration.
     pub auth: AuthConfigs,
 
-    /// The S3 storage configuration.
+    /// The storage configuration.
     pub storage: StorageConfig,
 
     /// UI configuration.
@@ -27,6 +31,10 @@ pub struct AppConfig {
 
     /// Admin panel configuration.
     pub admin: AdminConfig,
+
+    /// Upload scanning configuration.
+    #[serde(default)]
+    pub scan: ScanConfig,
 }
 
 /// The server configuration.
@@ -39,6 +47,40 @@ pub struct ServerConfig {
     /// The port to listen on.
     /// Defaults to `4000`
     pub port: u16,
+
+    /// The scheme version numbers must follow. This also decides how versions are ordered.
+    /// Defaults to [`VersionScheme::Semver`]
+    #[serde(default)]
+    pub version_scheme: VersionScheme,
+
+    /// The addresses (or CIDR ranges) of the reverse proxies in front of the server.
+    /// The `X-Forwarded-For` and `X-Real-IP` headers are only trusted on requests from them.
+    /// Defaults to none
+    #[serde(default)]
+    pub trusted_proxies: Vec<IpNet>,
+}
+
+/// A scheme for version numbers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+pub enum VersionScheme {
+    /// Version numbers must be valid [SemVer](https://semver.org/), and are ordered by it.
+    #[serde(rename = "semver")]
+    #[default]
+    Semver,
+
+    /// Version numbers are parsed with [`lenient_semver`](https://docs.rs/lenient_semver),
+    /// which accepts things like `1.2`, `v1.2.3`, or `1.2.3.4`.
+    #[serde(rename = "lenient_semver")]
+    LenientSemver,
+
+    /// Any version number is accepted, and they're ordered like Maven orders them
+    /// (so `1.0-alpha` < `1.0-rc1` < `1.0` < `1.0-sp1` < `1.0.1`).
+    #[serde(rename = "maven")]
+    Maven,
+
+    /// Any version number is accepted, and versions are ordered by when they were created.
+    #[serde(rename = "opaque")]
+    Opaque,
 }
 
 impl Default for ServerConfig {
@@ -46,6 +88,8 @@ impl Default for ServerConfig {
         Self {
             host: "127.0.0.1".into(),
             port: 4000,
+            version_scheme: VersionScheme::default(),
+            trusted_proxies: Vec::new(),
         }
     }
 }
@@ -13,6 +13,8 @@ mod auth;
 mod config;
 mod db;
 mod meili;
+mod scan;
+mod signer;
 mod storage;
 mod ui;
 mod util;
@@ -22,6 +24,8 @@ pub use auth::*;
 pub use config::*;
 pub use db::*;
 pub use meili::*;
+pub use scan::*;
+pub use signer::*;
 pub use storage::*;
 pub use ui::*;
 pub use util::*;
@@ -0,0 +1,87 @@
+//! Upload scanning configuration.
+
+/// The configuration for scanning uploaded archives (`.zip`, `.jar`, and `.tar.gz` files).
+/// Problems found by the scanner put the project into the moderation queue.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanConfig {
+    /// Whether uploaded files are scanned.
+    /// Defaults to `true`
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+
+    /// The largest allowed ratio between an entry's unpacked and packed size.
+    /// This is only checked for entries that unpack to at least 1 MiB.
+    /// Defaults to `100`
+    #[serde(default = "default_max_compression_ratio")]
+    pub max_compression_ratio: u64,
+
+    /// The maximum total unpacked size of an archive (including nested archives), in bytes.
+    /// Defaults to `1073741824` (1 GiB)
+    #[serde(default = "default_max_unpacked_size")]
+    pub max_unpacked_size: u64,
+
+    /// The maximum number of entries in an archive (including nested archives).
+    /// Defaults to `65536`
+    #[serde(default = "default_max_entries")]
+    pub max_entries: usize,
+
+    /// How deeply archives can be nested inside each other.
+    /// `0` forbids archives inside of the uploaded archive.
+    /// Defaults to `2`
+    #[serde(default = "default_max_nesting_depth")]
+    pub max_nesting_depth: usize,
+
+    /// File extensions (without the leading dot) that aren't allowed inside archives.
+    /// These are matched case-insensitively.
+    /// Defaults to common native executable and script extensions.
+    #[serde(default = "default_denied_extensions")]
+    pub denied_extensions: Vec<String>,
+
+    /// Whether native executables (ELF, PE, and Mach-O files) are forbidden,
+    /// regardless of their file extension.
+    /// Defaults to `true`
+    #[serde(default = "default_enabled")]
+    pub deny_native_executables: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_max_compression_ratio() -> u64 {
+    100
+}
+
+fn default_max_unpacked_size() -> u64 {
+    1024 * 1024 * 1024
+}
+
+fn default_max_entries() -> usize {
+    65536
+}
+
+fn default_max_nesting_depth() -> usize {
+    2
+}
+
+fn default_denied_extensions() -> Vec<String> {
+    [
+        "exe", "dll", "so", "dylib", "sys", "scr", "com", "msi", "bat", "cmd", "ps1", "vbs",
+    ]
+    .map(String::from)
+    .to_vec()
+}
+
+impl Default for ScanConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_enabled(),
+            max_compression_ratio: default_max_compression_ratio(),
+            max_unpacked_size: default_max_unpacked_size(),
+            max_entries: default_max_entries(),
+            max_nesting_depth: default_max_nesting_depth(),
+            denied_extensions: default_denied_extensions(),
+            deny_native_executables: default_enabled(),
+        }
+    }
+}
@@ -1,14 +1,45 @@
-//! The S3 storage configuration.
+//! The storage configuration.
 
+use crate::{BucketSigner, deserialize_interval, serialize_duration};
+use duration_str::deserialize_duration;
 use modhost_core::Result;
 use object_store::{
-    ClientOptions,
+    ClientOptions, ObjectStore,
     aws::{AmazonS3, AmazonS3Builder},
+    local::LocalFileSystem,
+    memory::InMemory,
 };
+use std::{fs, path::PathBuf, sync::Arc, time::Duration};
 
-/// The S3 storage configuration.
+/// A reference to a storage bucket, backed by any [`ObjectStore`].
+pub type Bucket = Arc<dyn ObjectStore>;
+
+/// The kind of storage backend to use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+pub enum StorageBackend {
+    /// Store files in S3 (or an S3-compatible service, like MinIO).
+    #[serde(rename = "s3")]
+    #[default]
+    S3,
+
+    /// Store files in a directory on the local filesystem.
+    #[serde(rename = "local")]
+    Local,
+
+    /// Store files in memory. Everything is lost when the server stops,
+    /// so this is only really useful for development and testing.
+    #[serde(rename = "memory")]
+    Memory,
+}
+
+/// The storage configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StorageConfig {
+    /// The storage backend to use.
+    /// Defaults to [`StorageBackend::S3`]
+    #[serde(default)]
+    pub backend: StorageBackend,
+
     /// The S3 region to connect with.
     pub s3_region: String,
 
@@ -21,6 +52,23 @@ pub struct StorageConfig {
     /// The S3 secret key.
     pub s3_secret_key: String,
 
+    /// Whether to redirect downloads to presigned S3 URLs instead of
+    /// streaming them through the server. Only works with [`StorageBackend::S3`].
+    /// Defaults to `false`
+    #[serde(default)]
+    pub s3_presigned_downloads: bool,
+
+    /// How long presigned download URLs are valid for, in seconds.
+    /// Defaults to `300`
+    #[serde(default = "default_presigned_expiry")]
+    pub s3_presigned_expiry: u64,
+
+    /// The directory to store buckets in when using [`StorageBackend::Local`].
+    /// Each bucket will be a subdirectory of this one.
+    /// Defaults to `"storage"`
+    #[serde(default = "default_local_path")]
+    pub local_path: PathBuf,
+
     /// The bucket name for projects.
     /// Defaults to `"projects"`
     pub projects_bucket: String,
@@ -28,43 +76,173 @@ pub struct StorageConfig {
     /// The bucket name for project galleries.
     /// Defaults to `"gallery"`
     pub gallery_bucket: String,
+
+    /// The maximum size of an uploaded version file, in bytes.
+    /// Defaults to `268435456` (256 MiB)
+    #[serde(default = "default_max_upload_size")]
+    pub max_upload_size: u64,
+
+    /// How many bytes from the start of an uploaded file are buffered and
+    /// handed to the upload validators.
+    /// Defaults to `16777216` (16 MiB)
+    #[serde(default = "default_verify_buffer_size")]
+    pub verify_buffer_size: usize,
+
+    /// The maximum number of bytes of a single archive entry that can be
+    /// previewed through the API. Larger entries are truncated.
+    /// Defaults to `1048576` (1 MiB)
+    #[serde(default = "default_max_preview_size")]
+    pub max_preview_size: usize,
+
+    /// The maximum number of archive previews and version diffs that can be
+    /// worked on at once. Requests past this are turned away until one finishes.
+    /// Defaults to `4`
+    #[serde(default = "default_max_archive_reads")]
+    pub max_archive_reads: usize,
+
+    /// How often the server will look for (and delete) objects in the buckets
+    /// that aren't referenced by any version file or gallery image.
+    /// This can't be zero.
+    /// Defaults to `6h`
+    #[serde(default = "default_gc_interval")]
+    #[serde(deserialize_with = "deserialize_interval")]
+    #[serde(serialize_with = "serialize_duration")]
+    pub gc_interval: Duration,
+
+    /// How old an unreferenced object has to be before it gets deleted.
+    /// This keeps uploads that are still in progress from being collected.
+    /// Defaults to `1d`
+    #[serde(default = "default_gc_grace_period")]
+    #[serde(deserialize_with = "deserialize_duration")]
+    #[serde(serialize_with = "serialize_duration")]
+    pub gc_grace_period: Duration,
+}
+
+fn default_local_path() -> PathBuf {
+    "storage".into()
+}
+
+fn default_max_preview_size() -> usize {
+    1024 * 1024
+}
+
+fn default_max_archive_reads() -> usize {
+    4
+}
+
+fn default_gc_interval() -> Duration {
+    Duration::from_secs(6 * 60 * 60)
+}
+
+fn default_gc_grace_period() -> Duration {
+    Duration::from_secs(24 * 60 * 60)
+}
+
+fn default_presigned_expiry() -> u64 {
+    300
+}
+
+fn default_max_upload_size() -> u64 {
+    256 * 1024 * 1024
+}
+
+fn default_verify_buffer_size() -> usize {
+    16 * 1024 * 1024
 }
 
 impl Default for StorageConfig {
     fn default() -> Self {
         Self {
+            backend: StorageBackend::default(),
             s3_region: String::new(),
             s3_endpoint: String::new(),
             s3_access_key: String::new(),
             s3_secret_key: String::new(),
+            s3_presigned_downloads: false,
+            s3_presigned_expiry: default_presigned_expiry(),
+            local_path: default_local_path(),
             projects_bucket: "projects".into(),
             gallery_bucket: "gallery".into(),
+            max_upload_size: default_max_upload_size(),
+            verify_buffer_size: default_verify_buffer_size(),
+            max_preview_size: default_max_preview_size(),
+            max_archive_reads: default_max_archive_reads(),
+            gc_interval: default_gc_interval(),
+            gc_grace_period: default_gc_grace_period(),
         }
     }
 }
 
 impl StorageConfig {
-    /// Get the [`AmazonS3`] bucket for projects.
-    pub fn projects(&self) -> Result<AmazonS3> {
-        Ok(AmazonS3Builder::new()
-            .with_region(&self.s3_region)
-            .with_endpoint(&self.s3_endpoint)
-            .with_bucket_name(&self.projects_bucket)
-            .with_access_key_id(&self.s3_access_key)
-            .with_secret_access_key(&self.s3_secret_key)
-            .with_client_options(ClientOptions::new().with_allow_http(true))
-            .build()?)
+    /// Get a [`Bucket`] by its name, using the configured backend.
+    /// Note that with [`StorageBackend::Memory`], every call creates a new, empty store.
+    pub fn bucket(&self, name: impl AsRef<str>) -> Result<Bucket> {
+        let name = name.as_ref();
+
+        Ok(match self.backend {
+            StorageBackend::S3 => Arc::new(self.s3(name)?),
+
+            StorageBackend::Local => {
+                let dir = self.local_path.join(name);
+
+                fs::create_dir_all(&dir)?;
+
+                Arc::new(LocalFileSystem::new_with_prefix(dir)?)
+            }
+
+            StorageBackend::Memory => Arc::new(InMemory::new()),
+        })
     }
 
-    /// Get the [`AmazonS3`] bucket for project galleries.
-    pub fn gallery(&self) -> Result<AmazonS3> {
+    /// Get a [`BucketSigner`] for a bucket by its name.
+    /// This is only present if presigned downloads are enabled and the
+    /// backend is [`StorageBackend::S3`].
+    pub fn signer(&self, name: impl AsRef<str>) -> Result<Option<BucketSigner>> {
+        if self.backend != StorageBackend::S3 || !self.s3_presigned_downloads {
+            return Ok(None);
+        }
+
+        let endpoint = match self.s3_endpoint.trim_end_matches('/') {
+            "" => format!("https://s3.{}.amazonaws.com", self.s3_region),
+            endpoint => endpoint.to_string(),
+        };
+
+        Ok(Some(BucketSigner {
+            endpoint: format!("{}/{}", endpoint, name.as_ref()),
+            region: self.s3_region.clone(),
+            access_key: self.s3_access_key.clone(),
+            secret_key: self.s3_secret_key.clone(),
+        }))
+    }
+
+    fn s3(&self, name: &str) -> Result<AmazonS3> {
         Ok(AmazonS3Builder::new()
             .with_region(&self.s3_region)
             .with_endpoint(&self.s3_endpoint)
-            .with_bucket_name(&self.gallery_bucket)
+            .with_bucket_name(name)
             .with_access_key_id(&self.s3_access_key)
             .with_secret_access_key(&self.s3_secret_key)
             .with_client_options(ClientOptions::new().with_allow_http(true))
             .build()?)
     }
+
+    /// Get the [`Bucket`] for projects.
+    pub fn projects(&self) -> Result<Bucket> {
+        self.bucket(&self.projects_bucket)
+    }
+
+    /// Get the [`Bucket`] for project galleries.
+    pub fn gallery(&self) -> Result<Bucket> {
+        self.bucket(&self.gallery_bucket)
+    }
+
+    /// Get the [`BucketSigner`] for projects, if presigned downloads are enabled.
+    pub fn projects_signer(&self) -> Result<Option<BucketSigner>> {
+        self.signer(&self.projects_bucket)
+    }
+
+    /// Get the [`BucketSigner`] for project galleries, if presigned downloads are enabled.
+    pub fn gallery_signer(&self) -> Result<Option<BucketSigner>> {
+        self.signer(&self.gallery_bucket)
+    }
 }
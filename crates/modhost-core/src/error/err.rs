@@ -229,6 +229,10 @@ pub enum AppError {
     #[error("Missing field: {0}")]
     MissingField(String),
 
+    /// A field that can only be given once was given more than once.
+    #[error("Duplicate field: {0}")]
+    DuplicateField(String),
+
     /// How did we get here?
     #[error("An unknown error occured.")]
     Unknown,
@@ -240,6 +244,156 @@ pub enum AppError {
     /// Couldn't find the right logo for a badge.
     #[error("Failed to find logo: {0}")]
     NoLogo(String),
+
+    /// An uploaded file was larger than the configured maximum size.
+    #[error("Uploaded file is too large! The maximum size is {0} bytes.")]
+    UploadTooLarge(u64),
+
+    /// A version has more than one file with the same name.
+    #[error("Duplicate file name: {0}")]
+    DuplicateFileName(String),
+
+    /// A file that was referred to doesn't exist.
+    #[error("Unknown file: {0}")]
+    UnknownFile(String),
+
+    /// An unsupported hash algorithm was requested.
+    #[error("Unknown hash algorithm: {0}")]
+    UnknownHashAlgorithm(String),
+
+    /// Too many hashes were sent in one request.
+    #[error("At most {0} hashes can be checked at once!")]
+    TooManyHashes(usize),
+
+    /// Tried to remove the only file of a version.
+    #[error("A version must have at least one file!")]
+    LastVersionFile,
+
+    /// A version number isn't valid under the configured version scheme.
+    #[error("Invalid version number: {0}")]
+    InvalidVersionNumber(String),
+
+    /// An unknown release channel was given.
+    #[error("Unknown version channel: {0}")]
+    UnknownVersionChannel(String),
+
+    /// A relation between projects isn't allowed.
+    #[error("Invalid relation: {0}")]
+    InvalidRelation(String),
+
+    /// Tried to delete a project that other projects depend on.
+    #[error("This project can't be deleted, because {0} other version(s) depend on it!")]
+    HasDependents(usize),
+
+    /// A scan rule's pattern is invalid.
+    #[error("Invalid scan rule: {0}")]
+    InvalidScanRule(String),
+
+    /// Tried to download a version that's blocked by a scan rule.
+    #[error("This version is blocked until a moderator reviews it!")]
+    VersionBlocked,
+
+    /// Logging in with an auth provider failed.
+    #[error("Authentication failed: {0}")]
+    AuthFailed(String),
+
+    /// Tried to link an identity that's already linked to another user.
+    #[error("This account is already linked to another user!")]
+    IdentityInUse,
+
+    /// Tried to unlink a user's only identity.
+    #[error("You can't unlink your only login method!")]
+    LastIdentity,
+
+    /// A personal access token is missing a scope it needs.
+    #[error("This token is missing the `{0}` scope!")]
+    MissingScope(String),
+
+    /// Tried to create a personal access token without any scopes.
+    #[error("A token needs at least one scope!")]
+    NoScopes,
+
+    /// Tried to create a personal access token that has already expired.
+    #[error("The token's expiration date must be in the future!")]
+    InvalidExpiration,
+
+    /// Tried to use a personal access token to create one that expires after it.
+    #[error("Tokens can't create other tokens that expire after they do!")]
+    OutlivesToken,
+
+    /// A login request's `state` was missing, forged, or expired.
+    #[error("The login request was invalid or has expired! Please try logging in again.")]
+    InvalidLoginState,
+
+    /// Tried to log in with a redirect to a URL that isn't allowed.
+    #[error("Logins can't redirect to {0}!")]
+    RedirectNotAllowed(String),
+
+    /// A local account's username or password was wrong.
+    #[error("Invalid username or password!")]
+    InvalidCredentials,
+
+    /// Tried to register a username that's already in use.
+    #[error("That username is already taken!")]
+    UsernameTaken,
+
+    /// Tried to register a local account when registration is disabled.
+    #[error("Registration is disabled on this server!")]
+    RegistrationDisabled,
+
+    /// A username wasn't allowed.
+    #[error("Invalid username: {0}")]
+    InvalidUsername(String),
+
+    /// A password was too short.
+    #[error("Passwords must be at least {0} characters long!")]
+    PasswordTooShort(usize),
+
+    /// Tried to browse the contents of a file that isn't an archive.
+    #[error("This file isn't an archive!")]
+    NotAnArchive,
+
+    /// A version file was replaced while its archive was being listed.
+    #[error("This file was replaced while it was being read! Try again.")]
+    FileReplaced,
+
+    /// Too many archives are being read at once.
+    #[error("The server is busy reading other archives! Try again later.")]
+    Busy,
+
+    /// A stored archive couldn't be read.
+    #[error("Couldn't read the archive: {0}")]
+    UnreadableArchive(String),
+
+    /// An upload was rejected by the server's validators.
+    #[error("The upload was rejected: {}", .0.join(" "))]
+    UploadRejected(Vec<String>),
+
+    /// An uploaded file's manifest disagrees with the submitted data.
+    #[error("The project manifest doesn't match the submitted {0}!")]
+    ManifestMismatch(String),
+
+    /// A game version range or wildcard couldn't be parsed.
+    #[error("Invalid game version range: {0}")]
+    InvalidGameVersionRange(String),
+
+    /// Some loaders, game versions, or tags aren't registered on the server.
+    #[error(
+        "Unknown metadata! Loaders: [{}], game versions: [{}], tags: [{}]",
+        loaders.join(", "),
+        game_versions.join(", "),
+        tags.join(", ")
+    )]
+    UnknownMetadata {
+        /// The unknown loaders.
+        loaders: Vec<String>,
+
+        /// The unknown game versions.
+        game_versions: Vec<String>,
+
+        /// The unknown tags.
+        tags: Vec<String>,
+    },
 }
 
 #[cfg(feature = "axum")]
@@ -249,14 +403,49 @@ impl super::HasCode for AppError {
             Self::Multipart(_)
             | Self::ParseInt(_)
             | Self::MissingField(_)
+            | Self::DuplicateField(_)
             | Self::MissingFieldName
             | Self::InvalidFacetData(_, _)
             | Self::UnknownFacetType(_)
             | Self::InvalidImageFile
-            | Self::NoLogo(_) => 400,
-
-            Self::MissingToken | Self::InvalidToken | Self::NoAccess => 403,
+            | Self::NoLogo(_)
+            | Self::DuplicateFileName(_)
+            | Self::UnknownFile(_)
+            | Self::UnknownHashAlgorithm(_)
+            | Self::TooManyHashes(_)
+            | Self::LastVersionFile
+            | Self::InvalidVersionNumber(_)
+            | Self::UnknownVersionChannel(_)
+            | Self::InvalidGameVersionRange(_)
+            | Self::InvalidRelation(_)
+            | Self::ManifestMismatch(_)
+            | Self::InvalidScanRule(_)
+            | Self::NotAnArchive
+            | Self::LastIdentity
+            | Self::NoScopes
+            | Self::InvalidExpiration
+            | Self::OutlivesToken
+            | Self::InvalidLoginState
+            | Self::RedirectNotAllowed(_)
+            | Self::InvalidUsername(_)
+            | Self::PasswordTooShort(_)
+            | Self::UnknownMetadata { .. } => 400,
+
+            Self::AuthFailed(_) | Self::InvalidCredentials => 401,
+            Self::MissingToken
+            | Self::InvalidToken
+            | Self::NoAccess
+            | Self::VersionBlocked
+            | Self::MissingScope(_)
+            | Self::RegistrationDisabled => 403,
             Self::NotFound | Self::UnknownUser | Self::NoVersions => 404,
+            Self::HasDependents(_)
+            | Self::IdentityInUse
+            | Self::UsernameTaken
+            | Self::FileReplaced => 409,
+            Self::UploadTooLarge(_) => 413,
+            Self::UploadRejected(_) | Self::UnreadableArchive(_) => 422,
+            Self::Busy => 503,
             _ => 500,
         }
     }
@@ -265,6 +454,41 @@ impl super::HasCode for AppError {
 #[cfg(feature = "axum")]
 impl axum::response::IntoResponse for AppError {
     fn into_response(self) -> axum::response::Response {
+        #[cfg(feature = "serde-json")]
+        if let Self::UnknownMetadata {
+            loaders,
+            game_versions,
+            tags,
+        } = &self
+        {
+            let body = serde_json::json!({
+                "error": self.to_string(),
+                "loaders": loaders,
+                "game_versions": game_versions,
+                "tags": tags,
+            });
+
+            return axum::response::Response::builder()
+                .status(super::HasCode::code(&self))
+                .header("Content-Type", "application/json")
+                .body(axum::body::Body::new(body.to_string()))
+                .unwrap_or_else(|_| self.into_axum());
+        }
+
+        #[cfg(feature = "serde-json")]
+        if let Self::UploadRejected(reasons) = &self {
+            let body = serde_json::json!({
+                "error": self.to_string(),
+                "reasons": reasons,
+            });
+
+            return axum::response::Response::builder()
+                .status(super::HasCode::code(&self))
+                .header("Content-Type", "application/json")
+                .body(axum::body::Body::new(body.to_string()))
+                .unwrap_or_else(|_| self.into_axum());
+        }
+
         self.into_axum()
     }
 }
@@ -0,0 +1,264 @@
+//! Utilities for working with version files and their hashes.
+
+use diesel::{
+    BelongingToDsl, BoolExpressionMethods, ExpressionMethods, QueryDsl, SelectableHelper, delete,
+    insert_into, update,
+};
+use diesel_async::{AsyncConnection, RunQueryDsl, scoped_futures::ScopedFutureExt};
+use futures::StreamExt;
+use modhost_core::{AppError, Result};
+use modhost_db::{
+    ArchiveEntry, DbConn, NewArchiveEntry, ProjectFile, archive_entries, version_files,
+};
+use object_store::{ObjectStore, path::Path};
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha512};
+
+/// The hashes of a file, as hex strings.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FileHashes {
+    /// The SHA-1 hash.
+    pub sha1: String,
+
+    /// The SHA-256 hash.
+    pub sha256: String,
+
+    /// The SHA-512 hash.
+    pub sha512: String,
+}
+
+/// Computes all of the [`FileHashes`] of a file at once, as it's read.
+#[derive(Debug, Clone, Default)]
+pub struct FileHasher {
+    sha1: Sha1,
+    sha256: Sha256,
+    sha512: Sha512,
+}
+
+impl FileHasher {
+    /// Create a new [`FileHasher`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed some data into the hasher.
+    pub fn update(&mut self, data: impl AsRef<[u8]>) {
+        let data = data.as_ref();
+
+        self.sha1.update(data);
+        self.sha256.update(data);
+        self.sha512.update(data);
+    }
+
+    /// Finish hashing and get the [`FileHashes`].
+    pub fn finish(self) -> FileHashes {
+        FileHashes {
+            sha1: format!("{:x}", self.sha1.finalize()),
+            sha256: format!("{:x}", self.sha256.finalize()),
+            sha512: format!("{:x}", self.sha512.finalize()),
+        }
+    }
+}
+
+/// Find version files by one of their hashes.
+/// The `algorithm` can be `sha1`, `sha256`, or `sha512`.
+pub async fn find_files_by_hash(
+    algorithm: impl AsRef<str>,
+    hash: impl AsRef<str>,
+    conn: &mut DbConn,
+) -> Result<Vec<ProjectFile>> {
+    find_files_by_hashes(algorithm, &[hash.as_ref()], conn).await
+}
+
+/// Find version files matching any of the given hashes, oldest first.
+/// The `algorithm` can be `sha1`, `sha256`, or `sha512`.
+pub async fn find_files_by_hashes(
+    algorithm: impl AsRef<str>,
+    hashes: &[impl AsRef<str>],
+    conn: &mut DbConn,
+) -> Result<Vec<ProjectFile>> {
+    let hashes = hashes
+        .iter()
+        .map(|v| v.as_ref().to_lowercase())
+        .collect::<Vec<_>>();
+
+    let query = version_files::table
+        .select(ProjectFile::as_select())
+        .order(version_files::id)
+        .into_boxed();
+
+    let query = match algorithm.as_ref() {
+        "sha1" => query.filter(version_files::sha1.eq_any(hashes)),
+        "sha256" => query.filter(version_files::sha256.eq_any(hashes)),
+        "sha512" => query.filter(version_files::sha512.eq_any(hashes)),
+        other => return Err(AppError::UnknownHashAlgorithm(other.into())),
+    };
+
+    Ok(query.load(conn).await?)
+}
+
+/// The number of archive entries inserted at once, to stay well under
+/// PostgreSQL's limit on query parameters.
+const ENTRY_CHUNK_SIZE: usize = 1000;
+
+/// Get the cached archive entries of a version file, in the order they appear
+/// in the archive. This is empty if the file hasn't been indexed yet
+/// (see [`ProjectFile::entries_indexed`]).
+pub async fn get_archive_entries(
+    file: &ProjectFile,
+    conn: &mut DbConn,
+) -> Result<Vec<ArchiveEntry>> {
+    Ok(ArchiveEntry::belonging_to(file)
+        .select(ArchiveEntry::as_select())
+        .order(archive_entries::id)
+        .load(conn)
+        .await?)
+}
+
+/// Cache the archive entries of a version file, replacing any old ones,
+/// and mark the file as indexed.
+/// If another request indexed the file first, its entries are returned instead.
+pub async fn save_archive_entries(
+    file: &ProjectFile,
+    entries: &[NewArchiveEntry],
+    conn: &mut DbConn,
+) -> Result<Vec<ArchiveEntry>> {
+    conn.transaction::<_, AppError, _>(|conn| {
+        async move {
+            // Locking the file makes concurrent requests wait for each other here.
+            let current = version_files::table
+                .find(file.id)
+                .select(ProjectFile::as_select())
+                .for_update()
+                .get_result(conn)
+                .await?;
+
+            if current.s3_id != file.s3_id {
+                return Err(AppError::FileReplaced);
+            }
+
+            if current.entries_indexed {
+                return get_archive_entries(&current, conn).await;
+            }
+
+            delete(archive_entries::table)
+                .filter(archive_entries::file_id.eq(file.id))
+                .execute(conn)
+                .await?;
+
+            let mut saved = Vec::with_capacity(entries.len());
+
+            for chunk in entries.chunks(ENTRY_CHUNK_SIZE) {
+                saved.extend(
+                    insert_into(archive_entries::table)
+                        .values(chunk)
+                        .returning(ArchiveEntry::as_returning())
+                        .get_results(conn)
+                        .await?,
+                );
+            }
+
+            update(version_files::table)
+                .filter(version_files::id.eq(file.id))
+                .set(version_files::entries_indexed.eq(true))
+                .execute(conn)
+                .await?;
+
+            Ok(saved)
+        }
+        .scope_boxed()
+    })
+    .await
+}
+
+/// Remove the cached archive entries of a version file, so it's indexed again
+/// the next time it's browsed. This should be done whenever its contents change.
+pub async fn clear_archive_entries(file_id: i32, conn: &mut DbConn) -> Result<()> {
+    delete(archive_entries::table)
+        .filter(archive_entries::file_id.eq(file_id))
+        .execute(conn)
+        .await?;
+
+    update(version_files::table)
+        .filter(version_files::id.eq(file_id))
+        .set(version_files::entries_indexed.eq(false))
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+/// Compute the missing SHA-256 and SHA-512 hashes of version files
+/// by reading their objects from the bucket.
+/// Files that can't be read are logged and skipped.
+/// Returns the number of files that were updated.
+pub async fn backfill_file_hashes(bucket: &dyn ObjectStore, conn: &mut DbConn) -> Result<usize> {
+    let files = version_files::table
+        .select(ProjectFile::as_select())
+        .filter(
+            version_files::sha256
+                .is_null()
+                .or(version_files::sha512.is_null()),
+        )
+        .load(conn)
+        .await?;
+
+    let mut updated = 0;
+
+    for file in files {
+        let mut stream = match bucket.get(&Path::from(format!("/{}", file.s3_id))).await {
+            Ok(data) => data.into_stream(),
+
+            Err(err) => {
+                tracing::warn!(
+                    "Could not read version file {} (id: {}) to backfill its hashes: {}",
+                    file.s3_id,
+                    file.id,
+                    err
+                );
+
+                continue;
+            }
+        };
+
+        let mut hasher = FileHasher::new();
+        let mut failed = None;
+
+        while let Some(chunk) = stream.next().await {
+            match chunk {
+                Ok(chunk) => hasher.update(chunk),
+
+                Err(err) => {
+                    failed = Some(err);
+                    break;
+                }
+            }
+        }
+
+        if let Some(err) = failed {
+            tracing::warn!(
+                "Could not finish reading version file {} (id: {}) to backfill its hashes: {}",
+                file.s3_id,
+                file.id,
+                err
+            );
+
+            continue;
+        }
+
+        let hashes = hasher.finish();
+
+        update(version_files::table)
+            .filter(version_files::id.eq(file.id))
+            .set((
+                version_files::sha256.eq(hashes.sha256),
+                version_files::sha512.eq(hashes.sha512),
+            ))
+            .execute(conn)
+            .await?;
+
+        updated += 1;
+    }
+
+    Ok(updated)
+}
@@ -2,10 +2,10 @@
 
 use modhost_core::Result;
 use modhost_db::{GalleryImage, PublicGalleryImage};
-use object_store::{ObjectStore, aws::AmazonS3, path::Path};
+use object_store::{ObjectStore, path::Path};
 
-/// Get a gallery image's bytes from S3.
-pub async fn get_image(id: impl AsRef<str>, bucket: &AmazonS3) -> Result<Vec<u8>> {
+/// Get a gallery image's bytes from its bucket.
+pub async fn get_image(id: impl AsRef<str>, bucket: &dyn ObjectStore) -> Result<Vec<u8>> {
     Ok(bucket
         .get(&Path::from(format!("/{}", id.as_ref())))
         .await?
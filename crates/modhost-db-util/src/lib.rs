@@ -2,8 +2,11 @@
 #![allow(async_fn_in_trait)]
 //! ModHost's database utilities.
 
+pub mod files;
 pub mod gallery;
 pub mod moderation;
 pub mod projects;
+pub mod relations;
+pub mod scheme;
 pub mod users;
 pub mod vers;
@@ -5,9 +5,13 @@ use diesel_async::RunQueryDsl;
 use modhost_core::Result;
 use modhost_db::{
     DbConn, ModerationComment, ModerationQueueItem, ModerationQueueStatus, NewModerationComment,
-    NewModerationQueueItem, Project, User, moderation_comment, moderation_queue,
+    NewModerationQueueItem, Project, ProjectFile, ScanFinding, ScanRule, User, moderation_comment,
+    moderation_queue, scan_rules,
 };
 
+/// The ID of the user that system moderation comments are attributed to.
+pub const SYSTEM_USER_ID: i32 = -1;
+
 /// Get the entire queue of pending moderation items.
 pub async fn get_pending_moderation_queue(conn: &mut DbConn) -> Result<Vec<ModerationQueueItem>> {
     Ok(moderation_queue::table
@@ -144,10 +148,56 @@ pub async fn create_moderation_comment(
             user_id: user.id,
             project_id: project.id,
             is_moderator: user.moderator || user.admin,
-            is_system: user.id == -1,
+            is_system: user.id == SYSTEM_USER_ID,
             comment,
         })
         .returning(ModerationComment::as_returning())
         .get_result(conn)
         .await?)
 }
+
+/// Create a new moderation comment on a project from the system.
+pub async fn create_system_comment(
+    project: &Project,
+    comment: String,
+    conn: &mut DbConn,
+) -> Result<ModerationComment> {
+    Ok(insert_into(moderation_comment::table)
+        .values(NewModerationComment {
+            user_id: SYSTEM_USER_ID,
+            project_id: project.id,
+            is_moderator: false,
+            is_system: true,
+            comment,
+        })
+        .returning(ModerationComment::as_returning())
+        .get_result(conn)
+        .await?)
+}
+
+/// Get all scan rules.
+pub async fn get_scan_rules(conn: &mut DbConn) -> Result<Vec<ScanRule>> {
+    Ok(scan_rules::table
+        .select(ScanRule::as_select())
+        .order(scan_rules::id.asc())
+        .load(conn)
+        .await?)
+}
+
+/// Get the scan rules that uploads are checked against.
+pub async fn get_enabled_scan_rules(conn: &mut DbConn) -> Result<Vec<ScanRule>> {
+    Ok(scan_rules::table
+        .select(ScanRule::as_select())
+        .filter(scan_rules::enabled.eq(true))
+        .order(scan_rules::id.asc())
+        .load(conn)
+        .await?)
+}
+
+/// Get the archive scan findings for a file.
+pub async fn get_scan_findings(file: &ProjectFile, conn: &mut DbConn) -> Result<Vec<ScanFinding>> {
+    Ok(ScanFinding::belonging_to(file)
+        .select(ScanFinding::as_select())
+        .load(conn)
+        .await?)
+}
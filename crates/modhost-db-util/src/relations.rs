@@ -0,0 +1,53 @@
+//! Utilities for working with relations between projects.
+
+use diesel::{ExpressionMethods, JoinOnDsl, QueryDsl, SelectableHelper};
+use diesel_async::RunQueryDsl;
+use itertools::Itertools;
+use modhost_core::Result;
+use modhost_db::{
+    DbConn, ProjectRelation, ProjectVersionData, RelationKind, project_relations, project_versions,
+};
+
+/// Get the relations of a list of versions.
+pub async fn get_relations(versions: &[i32], conn: &mut DbConn) -> Result<Vec<ProjectRelation>> {
+    Ok(project_relations::table
+        .filter(project_relations::version_id.eq_any(versions))
+        .select(ProjectRelation::as_select())
+        .order(project_relations::id)
+        .load(conn)
+        .await?)
+}
+
+/// Fill in the relations of a list of versions.
+pub async fn attach_relations(
+    versions: &mut [ProjectVersionData],
+    conn: &mut DbConn,
+) -> Result<()> {
+    let ids = versions.iter().map(|v| v.id).collect_vec();
+
+    let mut relations = get_relations(&ids, conn)
+        .await?
+        .into_iter()
+        .into_group_map_by(|v| v.version_id);
+
+    for ver in versions {
+        ver.relations = relations.remove(&ver.id).unwrap_or_default();
+    }
+
+    Ok(())
+}
+
+/// Get the dependency relations that other projects' versions have on a project.
+pub async fn get_dependents(project: i32, conn: &mut DbConn) -> Result<Vec<ProjectRelation>> {
+    Ok(project_relations::table
+        .inner_join(
+            project_versions::table.on(project_versions::id.eq(project_relations::version_id)),
+        )
+        .filter(project_relations::target_project.eq(project))
+        .filter(project_relations::kind.eq(RelationKind::Dependency))
+        .filter(project_versions::project.ne(project))
+        .select(ProjectRelation::as_select())
+        .order(project_relations::id)
+        .load(conn)
+        .await?)
+}
@@ -0,0 +1,153 @@
+//! Utilities for validating and ordering version numbers with a [`VersionScheme`].
+
+use modhost_config::VersionScheme;
+use modhost_core::{AppError, Result};
+use semver::Version;
+use std::cmp::Ordering;
+
+/// Check that a version number is valid under a [`VersionScheme`].
+pub fn validate_version_number(scheme: VersionScheme, version: impl AsRef<str>) -> Result<()> {
+    let version = version.as_ref();
+
+    let valid = match scheme {
+        VersionScheme::Semver => Version::parse(version).is_ok(),
+        VersionScheme::LenientSemver => lenient_semver::parse(version).is_ok(),
+        VersionScheme::Maven | VersionScheme::Opaque => !version.trim().is_empty(),
+    };
+
+    if valid {
+        Ok(())
+    } else {
+        Err(AppError::InvalidVersionNumber(version.into()))
+    }
+}
+
+/// Compare two version numbers under a [`VersionScheme`].
+/// Version numbers that aren't valid under the scheme are ordered before valid ones,
+/// and [`VersionScheme::Opaque`] considers all version numbers equal, so callers
+/// should fall back to comparing creation dates.
+pub fn compare_version_numbers(scheme: VersionScheme, a: &str, b: &str) -> Ordering {
+    match scheme {
+        VersionScheme::Semver => Version::parse(a).ok().cmp(&Version::parse(b).ok()),
+
+        VersionScheme::LenientSemver => lenient_semver::parse(a)
+            .ok()
+            .cmp(&lenient_semver::parse(b).ok()),
+
+        VersionScheme::Maven => compare_maven(a, b),
+        VersionScheme::Opaque => Ordering::Equal,
+    }
+}
+
+/// An item in a Maven version number.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum MavenItem {
+    /// A number, without leading zeroes.
+    Int(String),
+
+    /// A qualifier, like `alpha` or `rc`.
+    Str(String),
+}
+
+/// Split a version number into [`MavenItem`]s. Items are separated by `.` and `-`,
+/// and by any transition between digits and letters.
+fn maven_items(version: &str) -> Vec<MavenItem> {
+    let mut items = Vec::new();
+    let mut current = String::new();
+
+    let mut push = |current: &mut String| {
+        if current.is_empty() {
+            return;
+        }
+
+        if current.chars().all(|c| c.is_ascii_digit()) {
+            let trimmed = current.trim_start_matches('0');
+
+            items.push(MavenItem::Int(if trimmed.is_empty() {
+                "0".into()
+            } else {
+                trimmed.into()
+            }));
+        } else {
+            items.push(MavenItem::Str(current.clone()));
+        }
+
+        current.clear();
+    };
+
+    for c in version.to_lowercase().chars() {
+        if c == '.' || c == '-' || c == '_' || c == '+' {
+            push(&mut current);
+            continue;
+        }
+
+        if current
+            .chars()
+            .last()
+            .is_some_and(|last| last.is_ascii_digit() != c.is_ascii_digit())
+        {
+            push(&mut current);
+        }
+
+        current.push(c);
+    }
+
+    push(&mut current);
+
+    items
+}
+
+/// Get the rank of a Maven qualifier. Unknown qualifiers come after all known ones.
+fn qualifier_rank(qualifier: &str) -> u8 {
+    match qualifier {
+        "alpha" | "a" => 0,
+        "beta" | "b" => 1,
+        "milestone" | "m" => 2,
+        "rc" | "cr" => 3,
+        "snapshot" => 4,
+        "" | "ga" | "final" | "release" => 5,
+        "sp" => 6,
+        _ => 7,
+    }
+}
+
+/// Compare two qualifiers.
+fn compare_qualifiers(a: &str, b: &str) -> Ordering {
+    let (rank_a, rank_b) = (qualifier_rank(a), qualifier_rank(b));
+
+    if rank_a == 7 && rank_b == 7 {
+        a.cmp(b)
+    } else {
+        rank_a.cmp(&rank_b)
+    }
+}
+
+/// Compare two numbers (as strings without leading zeroes), which may not fit in a [`u64`].
+fn compare_ints(a: &str, b: &str) -> Ordering {
+    a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+}
+
+/// Compare two items, where [`None`] means the version has no more items.
+fn compare_items(a: Option<&MavenItem>, b: Option<&MavenItem>) -> Ordering {
+    match (a, b) {
+        (None, None) => Ordering::Equal,
+        (Some(MavenItem::Int(a)), Some(MavenItem::Int(b))) => compare_ints(a, b),
+        (Some(MavenItem::Str(a)), Some(MavenItem::Str(b))) => compare_qualifiers(a, b),
+        (Some(MavenItem::Int(_)), Some(MavenItem::Str(_))) => Ordering::Greater,
+        (Some(MavenItem::Str(_)), Some(MavenItem::Int(_))) => Ordering::Less,
+        (Some(MavenItem::Int(a)), None) => compare_ints(a, "0"),
+        (None, Some(MavenItem::Int(b))) => compare_ints("0", b),
+        (Some(MavenItem::Str(a)), None) => compare_qualifiers(a, ""),
+        (None, Some(MavenItem::Str(b))) => compare_qualifiers("", b),
+    }
+}
+
+/// Compare two version numbers like Maven does.
+fn compare_maven(a: &str, b: &str) -> Ordering {
+    let (a, b) = (maven_items(a), maven_items(b));
+
+    (0..a.len().max(b.len()))
+        .map(|i| compare_items(a.get(i), b.get(i)))
+        .find(|v| v.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
@@ -1,27 +1,43 @@
 //! Utilities for working with project versions.
 
+use crate::{relations::attach_relations, scheme::compare_version_numbers};
 use diesel::{BoolExpressionMethods, ExpressionMethods, QueryDsl, SelectableHelper};
 use diesel_async::RunQueryDsl;
 use itertools::Itertools;
+use modhost_config::VersionScheme;
 use modhost_core::{AppError, Result};
 use modhost_db::{
-    DbConn, ProjectFile, ProjectVersion, ProjectVersionData, project_versions, version_files,
+    DbConn, ProjectFile, ProjectVersion, ProjectVersionData, VersionChannel, project_versions,
+    version_files,
 };
-use semver::Version;
 
-/// Get a list of versions for a project.
-pub async fn get_versions(project: i32, conn: &mut DbConn) -> Result<Vec<ProjectVersionData>> {
-    Ok((project_versions::table
+/// Get a list of versions for a project, newest first.
+pub async fn get_versions(
+    project: i32,
+    scheme: VersionScheme,
+    conn: &mut DbConn,
+) -> Result<Vec<ProjectVersionData>> {
+    let mut versions = (project_versions::table
         .inner_join(version_files::table)
         .select((ProjectVersion::as_select(), ProjectFile::as_select()))
         .filter(project_versions::project.eq(project))
+        .order((version_files::is_primary.desc(), version_files::id))
         .load::<(ProjectVersion, ProjectFile)>(conn)
         .await? as Vec<(ProjectVersion, ProjectFile)>)
         .into_iter()
         .into_group_map()
         .into_iter()
         .map(|v| v.0.with_files(v.1))
-        .collect_vec())
+        .collect_vec();
+
+    sort_versions(scheme, &mut versions, |v| {
+        (&v.version_number, (v.created_at, v.id))
+    });
+
+    versions.reverse();
+    attach_relations(&mut versions, conn).await?;
+
+    Ok(versions)
 }
 
 /// Get the full version data for a project.
@@ -36,6 +52,7 @@ pub async fn get_full_version(
         .inner_join(version_files::table)
         .select((ProjectVersion::as_select(), ProjectFile::as_select()))
         .filter(project_versions::project.eq(project))
+        .order((version_files::is_primary.desc(), version_files::id))
         .into_boxed();
 
     if let Ok(ver_num) = ver.parse::<i32>() {
@@ -52,13 +69,18 @@ pub async fn get_full_version(
         );
     }
 
-    (query.load::<(ProjectVersion, ProjectFile)>(conn).await? as Vec<(ProjectVersion, ProjectFile)>)
+    let mut ver = (query.load::<(ProjectVersion, ProjectFile)>(conn).await?
+        as Vec<(ProjectVersion, ProjectFile)>)
         .into_iter()
         .into_group_map()
         .into_iter()
         .map(|v| v.0.with_files(v.1))
         .next()
-        .ok_or(AppError::NotFound)
+        .ok_or(AppError::NotFound)?;
+
+    attach_relations(std::slice::from_mut(&mut ver), conn).await?;
+
+    Ok(ver)
 }
 
 /// Get a version file.
@@ -88,17 +110,19 @@ pub async fn get_version_file(
 }
 
 /// Get a project's latest version.
-pub async fn get_latest_version(project: i32, conn: &mut DbConn) -> Result<ProjectVersion> {
+pub async fn get_latest_version(
+    project: i32,
+    scheme: VersionScheme,
+    conn: &mut DbConn,
+) -> Result<ProjectVersion> {
     let mut versions = project_versions::table
         .filter(project_versions::project.eq(project))
         .select(ProjectVersion::as_select())
         .load(conn)
         .await?;
 
-    versions.sort_by(|a, b| {
-        Version::parse(&a.version_number)
-            .unwrap()
-            .cmp(&Version::parse(&b.version_number).unwrap())
+    sort_versions(scheme, &mut versions, |v| {
+        (&v.version_number, (v.created_at, v.id))
     });
 
     versions.last().cloned().ok_or(AppError::NoVersions)
@@ -107,15 +131,54 @@ pub async fn get_latest_version(project: i32, conn: &mut DbConn) -> Result<Proje
 /// Get a project's latest version.
 pub async fn get_latest_full_version(
     project: i32,
+    scheme: VersionScheme,
     conn: &mut DbConn,
 ) -> Result<ProjectVersionData> {
-    let mut versions = get_versions(project, conn).await?;
+    get_versions(project, scheme, conn)
+        .await?
+        .into_iter()
+        .next()
+        .ok_or(AppError::NoVersions)
+}
 
+/// Get a project's latest version that supports the given loader and game version,
+/// and is in one of the given release channels.
+/// If either of the loader or game version is [`None`], or there are no channels,
+/// versions aren't filtered by it. Blocked versions are skipped unless `include_blocked` is set.
+pub async fn get_latest_compatible_version(
+    project: i32,
+    loader: Option<&str>,
+    game_version: Option<&str>,
+    channels: &[VersionChannel],
+    include_blocked: bool,
+    scheme: VersionScheme,
+    conn: &mut DbConn,
+) -> Result<ProjectVersionData> {
+    get_versions(project, scheme, conn)
+        .await?
+        .into_iter()
+        .find(|v| {
+            loader.is_none_or(|loader| v.loaders.iter().flatten().any(|v| v == loader))
+                && game_version
+                    .is_none_or(|game| v.game_versions.iter().flatten().any(|v| v == game))
+                && (channels.is_empty() || channels.contains(&v.channel))
+                && (include_blocked || !v.blocked)
+        })
+        .ok_or(AppError::NoVersions)
+}
+
+/// Sort versions from oldest to newest by their version numbers.
+/// Versions with equal version numbers are sorted by the tiebreaker,
+/// which is usually their creation date and ID.
+pub fn sort_versions<T, K: Ord>(
+    scheme: VersionScheme,
+    versions: &mut [T],
+    key: impl Fn(&T) -> (&String, K),
+) {
     versions.sort_by(|a, b| {
-        Version::parse(&a.version_number)
-            .unwrap()
-            .cmp(&Version::parse(&b.version_number).unwrap())
-    });
+        let (a_num, a_tie) = key(a);
+        let (b_num, b_tie) = key(b);
 
-    versions.last().cloned().ok_or(AppError::NoVersions)
+        compare_version_numbers(scheme, a_num, b_num).then_with(|| a_tie.cmp(&b_tie))
+    });
 }
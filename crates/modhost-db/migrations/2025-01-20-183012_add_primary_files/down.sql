@@ -0,0 +1 @@
+ALTER TABLE version_files DROP COLUMN IF EXISTS is_primary;
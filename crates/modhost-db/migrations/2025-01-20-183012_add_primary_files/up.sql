@@ -0,0 +1,4 @@
+ALTER TABLE version_files ADD is_primary BOOLEAN NOT NULL DEFAULT FALSE;
+
+UPDATE version_files SET is_primary = TRUE
+WHERE id IN (SELECT MIN(id) FROM version_files GROUP BY version_id);
@@ -0,0 +1,6 @@
+DROP INDEX IF EXISTS version_files_sha1_idx;
+DROP INDEX IF EXISTS version_files_sha256_idx;
+DROP INDEX IF EXISTS version_files_sha512_idx;
+
+ALTER TABLE version_files DROP COLUMN IF EXISTS sha256;
+ALTER TABLE version_files DROP COLUMN IF EXISTS sha512;
@@ -0,0 +1,7 @@
+-- Existing rows are backfilled by the server on startup, since that needs the files themselves.
+ALTER TABLE version_files ADD sha256 TEXT;
+ALTER TABLE version_files ADD sha512 TEXT;
+
+CREATE INDEX IF NOT EXISTS version_files_sha1_idx ON version_files (sha1);
+CREATE INDEX IF NOT EXISTS version_files_sha256_idx ON version_files (sha256);
+CREATE INDEX IF NOT EXISTS version_files_sha512_idx ON version_files (sha512);
@@ -0,0 +1,3 @@
+ALTER TABLE project_versions DROP COLUMN IF EXISTS channel;
+
+DROP TYPE IF EXISTS version_channel;
@@ -0,0 +1,3 @@
+CREATE TYPE version_channel AS ENUM ('release', 'beta', 'alpha');
+
+ALTER TABLE project_versions ADD channel version_channel NOT NULL DEFAULT 'release';
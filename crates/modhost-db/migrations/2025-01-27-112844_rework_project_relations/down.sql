@@ -0,0 +1,9 @@
+DROP TABLE IF EXISTS project_relations;
+
+CREATE TABLE IF NOT EXISTS project_relations (
+    project INTEGER NOT NULL REFERENCES project_versions(id) ON DELETE CASCADE,
+    dependency INTEGER NOT NULL REFERENCES project_version_refs(value) ON DELETE CASCADE,
+    -- The relation kind. 0 = dependency, 1 = incompatibility
+    kind INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY(project, dependency, kind)
+);
@@ -0,0 +1,17 @@
+-- Nothing used the old table, and it could only point at specific versions.
+DROP TABLE IF EXISTS project_relations;
+
+CREATE TABLE IF NOT EXISTS project_relations (
+    id SERIAL PRIMARY KEY,
+    version_id INTEGER NOT NULL REFERENCES project_versions(id) ON DELETE CASCADE,
+    target_project INTEGER NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    -- If this is NULL, the relation applies to any version of the target project.
+    target_version INTEGER REFERENCES project_versions(id) ON DELETE CASCADE,
+    -- The relation kind. 0 = dependency, 1 = incompatibility
+    kind INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS project_relations_unique_idx
+ON project_relations (version_id, target_project, COALESCE(target_version, 0), kind);
+
+CREATE INDEX IF NOT EXISTS project_relations_target_project_idx ON project_relations (target_project);
@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS scan_findings;
+DROP TYPE IF EXISTS scan_finding_kind;
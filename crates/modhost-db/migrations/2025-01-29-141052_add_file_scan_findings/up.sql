@@ -0,0 +1,12 @@
+CREATE TYPE scan_finding_kind AS ENUM ('archive_bomb', 'path_traversal', 'symlink', 'nested_archive', 'forbidden_file', 'unreadable');
+
+CREATE TABLE IF NOT EXISTS scan_findings (
+    id SERIAL NOT NULL PRIMARY KEY,
+    file_id INTEGER NOT NULL REFERENCES version_files(id) ON DELETE CASCADE,
+    kind scan_finding_kind NOT NULL,
+    path TEXT NOT NULL,
+    message TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX scan_findings_file_id ON scan_findings (file_id);
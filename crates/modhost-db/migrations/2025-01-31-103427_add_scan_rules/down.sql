@@ -0,0 +1,11 @@
+DELETE FROM moderation_comment WHERE user_id = -1;
+DELETE FROM users WHERE id = -1;
+
+ALTER TABLE project_versions DROP COLUMN IF EXISTS blocked;
+
+-- Postgres can't remove values from an enum, so findings for rules are removed instead.
+ALTER TABLE scan_findings DROP COLUMN IF EXISTS rule_id;
+DELETE FROM scan_findings WHERE kind = 'rule_match';
+
+DROP TABLE IF EXISTS scan_rules;
+DROP TYPE IF EXISTS scan_rule_kind;
@@ -0,0 +1,21 @@
+CREATE TYPE scan_rule_kind AS ENUM ('bytes', 'regex', 'hash');
+
+CREATE TABLE IF NOT EXISTS scan_rules (
+    id SERIAL NOT NULL PRIMARY KEY,
+    name TEXT NOT NULL,
+    description TEXT,
+    kind scan_rule_kind NOT NULL,
+    pattern TEXT NOT NULL,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+ALTER TYPE scan_finding_kind ADD VALUE IF NOT EXISTS 'rule_match';
+
+ALTER TABLE scan_findings ADD rule_id INTEGER REFERENCES scan_rules(id) ON DELETE SET NULL;
+
+ALTER TABLE project_versions ADD blocked BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- System moderation comments are attributed to this user.
+INSERT INTO users (id, username, github_id) VALUES (-1, 'System', -1) ON CONFLICT (id) DO NOTHING;
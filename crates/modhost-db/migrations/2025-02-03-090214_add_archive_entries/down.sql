@@ -0,0 +1,2 @@
+ALTER TABLE version_files DROP COLUMN IF EXISTS entries_indexed;
+DROP TABLE IF EXISTS archive_entries;
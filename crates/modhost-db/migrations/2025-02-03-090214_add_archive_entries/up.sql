@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS archive_entries (
+    id SERIAL NOT NULL PRIMARY KEY,
+    file_id INTEGER NOT NULL REFERENCES version_files(id) ON DELETE CASCADE,
+    path TEXT NOT NULL,
+    size BIGINT NOT NULL,
+    compressed_size BIGINT,
+    directory BOOLEAN NOT NULL DEFAULT FALSE
+);
+
+CREATE INDEX archive_entries_file_id ON archive_entries (file_id);
+
+ALTER TABLE version_files ADD COLUMN entries_indexed BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,3 @@
+DELETE FROM users WHERE github_id IS NULL;
+ALTER TABLE users ALTER COLUMN github_id SET NOT NULL;
+DROP TABLE IF EXISTS user_identities;
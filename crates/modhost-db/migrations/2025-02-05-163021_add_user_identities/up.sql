@@ -0,0 +1,18 @@
+CREATE TABLE IF NOT EXISTS user_identities (
+    id SERIAL NOT NULL PRIMARY KEY,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    provider TEXT NOT NULL,
+    subject TEXT NOT NULL,
+    username TEXT NOT NULL,
+    email TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    UNIQUE (provider, subject)
+);
+
+CREATE INDEX user_identities_user_id ON user_identities (user_id);
+
+-- Every existing user signed in with GitHub.
+INSERT INTO user_identities (user_id, provider, subject, username)
+SELECT id, 'github', github_id::TEXT, username FROM users WHERE id > 0;
+
+ALTER TABLE users ALTER COLUMN github_id DROP NOT NULL;
@@ -0,0 +1,13 @@
+CREATE TYPE token_scope AS ENUM ('read', 'project_write', 'version_upload', 'moderation', 'admin', 'account');
+
+CREATE TABLE IF NOT EXISTS personal_access_tokens (
+    id SERIAL NOT NULL PRIMARY KEY,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    token_hash TEXT NOT NULL UNIQUE,
+    prefix TEXT NOT NULL,
+    scopes token_scope[] NOT NULL DEFAULT '{}',
+    expires TIMESTAMP,
+    last_used TIMESTAMP,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,6 @@
+DROP INDEX IF EXISTS user_tokens_value_idx;
+
+ALTER TABLE user_tokens DROP COLUMN IF EXISTS ip;
+ALTER TABLE user_tokens DROP COLUMN IF EXISTS user_agent;
+ALTER TABLE user_tokens DROP COLUMN IF EXISTS last_used;
+ALTER TABLE user_tokens DROP COLUMN IF EXISTS created_at;
@@ -0,0 +1,6 @@
+ALTER TABLE user_tokens ADD created_at TIMESTAMP NOT NULL DEFAULT NOW();
+ALTER TABLE user_tokens ADD last_used TIMESTAMP;
+ALTER TABLE user_tokens ADD user_agent TEXT;
+ALTER TABLE user_tokens ADD ip TEXT;
+
+CREATE INDEX IF NOT EXISTS user_tokens_value_idx ON user_tokens (value);
@@ -0,0 +1,5 @@
+CREATE TABLE IF NOT EXISTS local_credentials (
+    identity_id INTEGER NOT NULL PRIMARY KEY REFERENCES user_identities(id) ON DELETE CASCADE,
+    password_hash TEXT NOT NULL,
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,9 @@
+-- Usernames used to be copied from login providers as-is, so duplicates get a suffix first.
+UPDATE users SET username = username || '-' || id
+WHERE id IN (
+    SELECT id FROM (
+        SELECT id, ROW_NUMBER() OVER (PARTITION BY LOWER(username) ORDER BY id) AS n FROM users
+    ) AS dupes WHERE n > 1
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS users_username_lower_idx ON users (LOWER(username));
@@ -5,11 +5,38 @@ pub mod sql_types {
     #[diesel(postgres_type(name = "moderation_status"))]
     pub struct ModerationStatus;
 
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "scan_finding_kind"))]
+    pub struct ScanFindingKind;
+
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "scan_rule_kind"))]
+    pub struct ScanRuleKind;
+
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "token_scope"))]
+    pub struct TokenScope;
+
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "version_channel"))]
+    pub struct VersionChannel;
+
     #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
     #[diesel(postgres_type(name = "visibility"))]
     pub struct Visibility;
 }
 
+diesel::table! {
+    archive_entries (id) {
+        id -> Int4,
+        file_id -> Int4,
+        path -> Text,
+        size -> Int8,
+        compressed_size -> Nullable<Int8>,
+        directory -> Bool,
+    }
+}
+
 diesel::table! {
     gallery_images (id) {
         id -> Int4,
@@ -23,6 +50,14 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    local_credentials (identity_id) {
+        identity_id -> Int4,
+        password_hash -> Text,
+        updated_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     moderation_comment (id) {
         id -> Int4,
@@ -46,6 +81,23 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::TokenScope;
+
+    personal_access_tokens (id) {
+        id -> Int4,
+        user_id -> Int4,
+        name -> Text,
+        token_hash -> Text,
+        prefix -> Text,
+        scopes -> Array<TokenScope>,
+        expires -> Nullable<Timestamp>,
+        last_used -> Nullable<Timestamp>,
+        created_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     project_authors (project, user_id) {
         project -> Int4,
@@ -54,9 +106,11 @@ diesel::table! {
 }
 
 diesel::table! {
-    project_relations (project, dependency, kind) {
-        project -> Int4,
-        dependency -> Int4,
+    project_relations (id) {
+        id -> Int4,
+        version_id -> Int4,
+        target_project -> Int4,
+        target_version -> Nullable<Int4>,
         kind -> Int4,
     }
 }
@@ -68,6 +122,9 @@ diesel::table! {
 }
 
 diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::VersionChannel;
+
     project_versions (id) {
         id -> Int4,
         project -> Int4,
@@ -79,6 +136,8 @@ diesel::table! {
         downloads -> Int4,
         created_at -> Timestamp,
         updated_at -> Timestamp,
+        channel -> VersionChannel,
+        blocked -> Bool,
     }
 }
 
@@ -104,12 +163,59 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::ScanFindingKind;
+
+    scan_findings (id) {
+        id -> Int4,
+        file_id -> Int4,
+        kind -> ScanFindingKind,
+        path -> Text,
+        message -> Text,
+        created_at -> Timestamp,
+        rule_id -> Nullable<Int4>,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::ScanRuleKind;
+
+    scan_rules (id) {
+        id -> Int4,
+        name -> Text,
+        description -> Nullable<Text>,
+        kind -> ScanRuleKind,
+        pattern -> Text,
+        enabled -> Bool,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    user_identities (id) {
+        id -> Int4,
+        user_id -> Int4,
+        provider -> Text,
+        subject -> Text,
+        username -> Text,
+        email -> Nullable<Text>,
+        created_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     user_tokens (id) {
         id -> Int4,
         user_id -> Int4,
         value -> Text,
         expires -> Timestamp,
+        created_at -> Timestamp,
+        last_used -> Nullable<Timestamp>,
+        user_agent -> Nullable<Text>,
+        ip -> Nullable<Text>,
     }
 }
 
@@ -117,7 +223,7 @@ diesel::table! {
     users (id) {
         id -> Int4,
         username -> Text,
-        github_id -> Int4,
+        github_id -> Nullable<Int4>,
         admin -> Bool,
         moderator -> Bool,
     }
@@ -132,32 +238,47 @@ diesel::table! {
         size -> Int8,
         version_id -> Int4,
         uploaded_at -> Timestamp,
+        is_primary -> Bool,
+        sha256 -> Nullable<Text>,
+        sha512 -> Nullable<Text>,
+        entries_indexed -> Bool,
     }
 }
 
+diesel::joinable!(archive_entries -> version_files (file_id));
 diesel::joinable!(gallery_images -> projects (project));
+diesel::joinable!(local_credentials -> user_identities (identity_id));
 diesel::joinable!(moderation_comment -> projects (project_id));
 diesel::joinable!(moderation_comment -> users (user_id));
 diesel::joinable!(moderation_queue -> projects (project_id));
 diesel::joinable!(moderation_queue -> users (assigned_id));
+diesel::joinable!(personal_access_tokens -> users (user_id));
 diesel::joinable!(project_authors -> projects (project));
 diesel::joinable!(project_authors -> users (user_id));
-diesel::joinable!(project_relations -> project_version_refs (dependency));
-diesel::joinable!(project_relations -> project_versions (project));
+diesel::joinable!(project_relations -> projects (target_project));
 diesel::joinable!(project_version_refs -> project_versions (value));
 diesel::joinable!(project_versions -> projects (project));
+diesel::joinable!(scan_findings -> scan_rules (rule_id));
+diesel::joinable!(scan_findings -> version_files (file_id));
+diesel::joinable!(user_identities -> users (user_id));
 diesel::joinable!(user_tokens -> users (user_id));
 diesel::joinable!(version_files -> project_versions (version_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    archive_entries,
     gallery_images,
+    local_credentials,
     moderation_comment,
     moderation_queue,
+    personal_access_tokens,
     project_authors,
     project_relations,
     project_version_refs,
     project_versions,
     projects,
+    scan_findings,
+    scan_rules,
+    user_identities,
     user_tokens,
     users,
     version_files,
@@ -65,10 +65,16 @@ utoipa_types![
     UserToken,
     NewUser,
     NewUserToken,
+    UserIdentity,
+    NewUserIdentity,
+    PersonalAccessToken,
+    NewPersonalAccessToken,
+    TokenScope,
     ProjectManifest,
     Project,
     ProjectAuthor,
     ProjectRelation,
+    NewProjectRelation,
     ProjectVersion,
     ProjectVersionRef,
     ProjectVersionInit,
@@ -82,8 +88,17 @@ utoipa_types![
     PublicGalleryImage,
     ProjectFile,
     NewProjectFile,
+    ArchiveEntry,
+    NewArchiveEntry,
     ProjectVersionData,
+    VersionChannel,
     ModerationComment,
     ModerationQueueItem,
     ModerationQueueStatus,
+    ScanFinding,
+    NewScanFinding,
+    ScanFindingKind,
+    ScanRule,
+    NewScanRule,
+    ScanRuleKind,
 ];
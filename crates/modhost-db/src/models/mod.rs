@@ -4,10 +4,12 @@ mod gallery;
 mod manifest;
 mod moderation;
 mod project;
+mod token;
 mod user;
 
 pub use gallery::*;
 pub use manifest::*;
 pub use moderation::*;
 pub use project::*;
+pub use token::*;
 pub use user::*;
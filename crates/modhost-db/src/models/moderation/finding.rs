@@ -0,0 +1,127 @@
+//! Archive scan finding models.
+
+use crate::{ProjectFile, schema::scan_findings};
+use chrono::NaiveDateTime;
+use diesel::pg::Pg;
+use diesel_derive_enum::DbEnum;
+
+/// The kind of problem an archive scan found.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    ToSchema,
+    ToResponse,
+    DbEnum,
+)]
+#[ExistingTypePath = "crate::schema::sql_types::ScanFindingKind"]
+#[serde(rename_all = "snake_case")]
+pub enum ScanFindingKind {
+    /// The archive unpacks to too much data, or is compressed suspiciously well.
+    ArchiveBomb,
+
+    /// An entry's path is absolute, or escapes the archive's root.
+    PathTraversal,
+
+    /// An entry is a symbolic (or hard) link.
+    Symlink,
+
+    /// Archives are nested deeper than the configured limit.
+    NestedArchive,
+
+    /// An entry has a forbidden file type, like a native executable.
+    ForbiddenFile,
+
+    /// The archive (or a nested one) couldn't be read.
+    Unreadable,
+
+    /// A file matched one of the moderators' scan rules.
+    RuleMatch,
+}
+
+/// A problem found while scanning an uploaded file.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    Identifiable,
+    Queryable,
+    Selectable,
+    Associations,
+    ToSchema,
+    ToResponse,
+)]
+#[diesel(table_name = scan_findings)]
+#[diesel(belongs_to(ProjectFile, foreign_key = file_id))]
+#[diesel(check_for_backend(Pg))]
+pub struct ScanFinding {
+    /// The finding ID.
+    pub id: i32,
+
+    /// The ID of the file this was found in.
+    pub file_id: i32,
+
+    /// The kind of problem.
+    pub kind: ScanFindingKind,
+
+    /// The path of the offending entry. Entries in nested archives are
+    /// separated by `!/` (like `libs/inner.jar!/evil.exe`).
+    pub path: String,
+
+    /// A description of the problem.
+    pub message: String,
+
+    /// When this was found.
+    pub created_at: NaiveDateTime,
+
+    /// The ID of the scan rule that matched, for [`ScanFindingKind::RuleMatch`] findings.
+    /// This is [`None`] for other findings, or if the rule was deleted.
+    pub rule_id: Option<i32>,
+}
+
+/// The data for creating a new scan finding in the database.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    Insertable,
+    ToSchema,
+    ToResponse,
+)]
+#[diesel(table_name = scan_findings)]
+#[diesel(check_for_backend(Pg))]
+pub struct NewScanFinding {
+    /// The ID of the file this was found in.
+    pub file_id: i32,
+
+    /// The kind of problem.
+    pub kind: ScanFindingKind,
+
+    /// The path of the offending entry.
+    pub path: String,
+
+    /// A description of the problem.
+    pub message: String,
+
+    /// The ID of the scan rule that matched, if any.
+    pub rule_id: Option<i32>,
+}
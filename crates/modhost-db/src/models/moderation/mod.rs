@@ -1,7 +1,11 @@
 //! Moderation models.
 
 mod comment;
+mod finding;
 mod queue;
+mod rule;
 
 pub use comment::*;
+pub use finding::*;
 pub use queue::*;
+pub use rule::*;
@@ -0,0 +1,116 @@
+//! Scan rule models.
+
+use crate::schema::scan_rules;
+use chrono::NaiveDateTime;
+use diesel::pg::Pg;
+use diesel_derive_enum::DbEnum;
+
+/// The kind of pattern a scan rule matches.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    ToSchema,
+    ToResponse,
+    DbEnum,
+)]
+#[ExistingTypePath = "crate::schema::sql_types::ScanRuleKind"]
+#[serde(rename_all = "snake_case")]
+pub enum ScanRuleKind {
+    /// A byte sequence, written as hex (like `de ad be ef`).
+    /// This matches any file that contains it.
+    Bytes,
+
+    /// A regular expression, which matches text files.
+    Regex,
+
+    /// A SHA-1, SHA-256, or SHA-512 hash, written as hex.
+    /// This matches files with that exact hash.
+    Hash,
+}
+
+/// A detection rule, written by moderators, that uploaded files are checked against.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    Identifiable,
+    Queryable,
+    Selectable,
+    ToSchema,
+    ToResponse,
+)]
+#[diesel(table_name = scan_rules)]
+#[diesel(check_for_backend(Pg))]
+pub struct ScanRule {
+    /// The rule ID.
+    pub id: i32,
+
+    /// The rule's name.
+    pub name: String,
+
+    /// A description of what the rule detects.
+    pub description: Option<String>,
+
+    /// The kind of pattern.
+    pub kind: ScanRuleKind,
+
+    /// The pattern itself.
+    pub pattern: String,
+
+    /// Whether uploads are checked against this rule.
+    pub enabled: bool,
+
+    /// When this rule was created.
+    pub created_at: NaiveDateTime,
+
+    /// When this rule was last updated.
+    pub updated_at: NaiveDateTime,
+}
+
+/// The data for creating a new scan rule in the database.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    Insertable,
+    ToSchema,
+    ToResponse,
+)]
+#[diesel(table_name = scan_rules)]
+#[diesel(check_for_backend(Pg))]
+pub struct NewScanRule {
+    /// The rule's name.
+    pub name: String,
+
+    /// A description of what the rule detects.
+    pub description: Option<String>,
+
+    /// The kind of pattern.
+    pub kind: ScanRuleKind,
+
+    /// The pattern itself.
+    pub pattern: String,
+
+    /// Whether uploads are checked against this rule.
+    pub enabled: bool,
+}
@@ -0,0 +1,81 @@
+//! Archive entry models.
+
+use crate::{ProjectFile, schema::archive_entries};
+use diesel::pg::Pg;
+
+/// An entry inside of an archive (a `.zip`, `.jar`, or `.tar.gz` file).
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    Identifiable,
+    Queryable,
+    Selectable,
+    Associations,
+    ToSchema,
+    ToResponse,
+)]
+#[diesel(table_name = archive_entries)]
+#[diesel(belongs_to(ProjectFile, foreign_key = file_id))]
+#[diesel(check_for_backend(Pg))]
+pub struct ArchiveEntry {
+    /// The entry ID.
+    pub id: i32,
+
+    /// The ID of the file this entry is in.
+    pub file_id: i32,
+
+    /// The entry's path inside of the archive.
+    pub path: String,
+
+    /// The entry's unpacked size in bytes.
+    pub size: i64,
+
+    /// The entry's compressed size in bytes.
+    /// This is only known for `.zip` and `.jar` files, since `.tar.gz` files
+    /// are compressed as a whole.
+    pub compressed_size: Option<i64>,
+
+    /// Whether this entry is a directory.
+    pub directory: bool,
+}
+
+/// The data for creating a new archive entry in the database.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    Insertable,
+    ToSchema,
+    ToResponse,
+)]
+#[diesel(table_name = archive_entries)]
+#[diesel(check_for_backend(Pg))]
+pub struct NewArchiveEntry {
+    /// The ID of the file this entry is in.
+    pub file_id: i32,
+
+    /// The entry's path inside of the archive.
+    pub path: String,
+
+    /// The entry's unpacked size in bytes.
+    pub size: i64,
+
+    /// The entry's compressed size in bytes, if it's known.
+    pub compressed_size: Option<i64>,
+
+    /// Whether this entry is a directory.
+    pub directory: bool,
+}
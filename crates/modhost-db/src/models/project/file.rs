@@ -46,6 +46,21 @@ pub struct ProjectFile {
 
     /// The date this file was uploaded.
     pub uploaded_at: NaiveDateTime,
+
+    /// Whether this is the version's primary file.
+    pub is_primary: bool,
+
+    /// The SHA-256 hash of the version file.
+    /// This is only missing for old files that haven't been backfilled yet.
+    pub sha256: Option<String>,
+
+    /// The SHA-512 hash of the version file.
+    /// This is only missing for old files that haven't been backfilled yet.
+    pub sha512: Option<String>,
+
+    /// Whether the entries of this file (if it's an archive) have been listed
+    /// and cached in the database.
+    pub entries_indexed: bool,
 }
 
 /// The initial data for creating a new project file in the database.
@@ -84,4 +99,13 @@ pub struct NewProjectFile {
 
     /// The size of the file in bytes.
     pub size: i64,
+
+    /// Whether this is the version's primary file.
+    pub is_primary: bool,
+
+    /// The SHA-256 hash of the version file.
+    pub sha256: Option<String>,
+
+    /// The SHA-512 hash of the version file.
+    pub sha512: Option<String>,
 }
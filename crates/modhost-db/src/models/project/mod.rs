@@ -1,11 +1,13 @@
 //! Project-related models.
 
+mod archive;
 mod author;
 mod base;
 mod file;
 mod relation;
 mod version;
 
+pub use archive::*;
 pub use author::*;
 pub use base::*;
 pub use file::*;
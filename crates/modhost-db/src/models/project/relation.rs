@@ -1,6 +1,6 @@
 //! Project relation-related models.
 
-use crate::{ProjectVersion, ProjectVersionRef, schema::project_relations};
+use crate::{ProjectVersion, schema::project_relations};
 use diesel::{
     Queryable,
     backend::Backend,
@@ -12,8 +12,7 @@ use diesel::{
     sql_types::Integer,
 };
 
-// TODO: Do something with this xD
-/// A relation between projects.
+/// A relation from a project version to another project or one of its versions.
 #[derive(
     Debug,
     Clone,
@@ -24,24 +23,61 @@ use diesel::{
     Hash,
     Serialize,
     Deserialize,
+    Identifiable,
     Queryable,
     Selectable,
-    Insertable,
     Associations,
     ToSchema,
     ToResponse,
 )]
 #[diesel(table_name = project_relations)]
-#[diesel(belongs_to(ProjectVersion, foreign_key = project))]
-#[diesel(belongs_to(ProjectVersionRef, foreign_key = dependency))]
+#[diesel(belongs_to(ProjectVersion, foreign_key = version_id))]
 #[diesel(check_for_backend(Pg))]
-#[diesel(primary_key(project, dependency, kind))]
 pub struct ProjectRelation {
-    /// The project ID.
-    pub project: i32,
+    /// The relation ID.
+    pub id: i32,
+
+    /// The ID of the version that has this relation.
+    pub version_id: i32,
+
+    /// The target project ID.
+    pub target_project: i32,
+
+    /// The target version ID.
+    /// If this is [`None`], the relation applies to any version of the target project.
+    pub target_version: Option<i32>,
+
+    /// The relation kind.
+    pub kind: RelationKind,
+}
+
+/// The data for creating a new relation in the database.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    Insertable,
+    ToSchema,
+    ToResponse,
+)]
+#[diesel(table_name = project_relations)]
+#[diesel(check_for_backend(Pg))]
+pub struct NewProjectRelation {
+    /// The ID of the version that has this relation.
+    pub version_id: i32,
+
+    /// The target project ID.
+    pub target_project: i32,
 
-    /// The dependency version ID.
-    pub dependency: i32,
+    /// The target version ID.
+    /// If this is [`None`], the relation applies to any version of the target project.
+    pub target_version: Option<i32>,
 
     /// The relation kind.
     pub kind: RelationKind,
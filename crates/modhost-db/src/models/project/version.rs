@@ -6,8 +6,44 @@ use crate::{
 };
 use chrono::NaiveDateTime;
 use diesel::pg::Pg;
+use diesel_derive_enum::DbEnum;
+use modhost_core::AppError;
+use std::str::FromStr;
 
-use super::ProjectFile;
+use super::{ProjectFile, ProjectRelation};
+
+/// The release channel of a project version.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    ToSchema,
+    ToResponse,
+    DbEnum,
+    Default,
+)]
+#[ExistingTypePath = "crate::schema::sql_types::VersionChannel"]
+pub enum VersionChannel {
+    /// A stable release.
+    #[default]
+    #[serde(alias = "release")]
+    Release,
+
+    /// A beta (pre-release) version.
+    #[serde(alias = "beta")]
+    Beta,
+
+    /// An alpha (pre-release) version.
+    #[serde(alias = "alpha")]
+    Alpha,
+}
 
 /// A project version.
 #[derive(
@@ -60,6 +96,13 @@ pub struct ProjectVersion {
 
     /// The number of downloads this version has.
     pub downloads: i32,
+
+    /// The release channel of this version.
+    pub channel: VersionChannel,
+
+    /// Whether this version can't be downloaded, because a scan rule matched one
+    /// of its files. Moderators can clear this.
+    pub blocked: bool,
 }
 
 /// The initial data for creating a new project version in the database.
@@ -104,6 +147,9 @@ pub struct NewProjectVersion {
 
     /// The number of downloads this version has.
     pub downloads: i32,
+
+    /// The release channel of this version.
+    pub channel: VersionChannel,
 }
 
 /// A reference to a project version.
@@ -151,12 +197,24 @@ pub struct ProjectVersionInit {
 
     /// A list of game versions this works on.
     /// This should be a comma-separated list in the request.
+    /// Items can be ranges or wildcards, like `1.20.x`, `>=1.20 <1.21`, or `1.19.2..1.20.1`.
     pub game_versions: String,
 
+    /// The release channel of the version.
+    /// Defaults to [`VersionChannel::Release`].
+    pub channel: Option<VersionChannel>,
+
     /// The file name.
-    pub file_name: String,
+    /// This can be repeated (once for each file), and can be omitted
+    /// if the file parts have their own file names.
+    pub file_name: Option<String>,
+
+    /// The name of the primary file.
+    /// Defaults to the first file.
+    pub primary: Option<String>,
 
     /// The file content.
+    /// This can be repeated to upload multiple files.
     #[schema(content_media_type = "application/octet-stream")]
     pub file: Vec<u8>,
 }
@@ -196,12 +254,23 @@ pub struct ProjectVersionData {
     /// The number of downloads this version has.
     pub downloads: i32,
 
+    /// The release channel of this version.
+    pub channel: VersionChannel,
+
+    /// Whether this version can't be downloaded, because a scan rule matched one
+    /// of its files. Moderators can clear this.
+    pub blocked: bool,
+
     /// This version's files.
     pub files: Vec<ProjectFile>,
+
+    /// This version's dependencies and incompatibilities.
+    pub relations: Vec<ProjectRelation>,
 }
 
 impl ProjectVersion {
     /// Transform this into [`ProjectVersionData`] with a list of [`ProjectFile`]s.
+    /// The relations are left empty.
     pub fn with_files(self, files: Vec<ProjectFile>) -> ProjectVersionData {
         ProjectVersionData {
             id: self.id,
@@ -214,7 +283,39 @@ impl ProjectVersion {
             created_at: self.created_at,
             updated_at: self.updated_at,
             downloads: self.downloads,
+            channel: self.channel,
+            blocked: self.blocked,
             files,
+            relations: Vec::new(),
+        }
+    }
+}
+
+impl VersionChannel {
+    /// Get the string form of this.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Release => "Release",
+            Self::Beta => "Beta",
+            Self::Alpha => "Alpha",
+        }
+    }
+
+    /// Check if this is a pre-release channel.
+    pub fn is_prerelease(&self) -> bool {
+        *self != Self::Release
+    }
+}
+
+impl FromStr for VersionChannel {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "release" => Ok(Self::Release),
+            "beta" => Ok(Self::Beta),
+            "alpha" => Ok(Self::Alpha),
+            _ => Err(AppError::UnknownVersionChannel(s.into())),
         }
     }
 }
@@ -0,0 +1,171 @@
+//! Personal access token models.
+
+use crate::schema::personal_access_tokens;
+use chrono::NaiveDateTime;
+use diesel::pg::Pg;
+use diesel_derive_enum::DbEnum;
+use std::fmt;
+
+/// A permission that a personal access token can be granted.
+/// Session tokens (from logging in) always have every scope.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    ToSchema,
+    ToResponse,
+    DbEnum,
+)]
+#[ExistingTypePath = "crate::schema::sql_types::TokenScope"]
+pub enum TokenScope {
+    /// Read the user's account info and any private projects they can see.
+    #[serde(rename = "read")]
+    Read,
+
+    /// Create, update, and delete projects, along with their authors and gallery images.
+    #[serde(rename = "project:write")]
+    ProjectWrite,
+
+    /// Upload, update, and delete versions and their files.
+    #[serde(rename = "version:upload")]
+    VersionUpload,
+
+    /// Use the moderation endpoints. This only works for moderators.
+    #[serde(rename = "moderation")]
+    Moderation,
+
+    /// Use the admin endpoints. This only works for admins.
+    #[serde(rename = "admin")]
+    Admin,
+
+    /// Manage the user's account, like their linked identities and tokens.
+    #[serde(rename = "account")]
+    Account,
+}
+
+impl TokenScope {
+    /// Get the scope's name, as it's written in the API.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Read => "read",
+            Self::ProjectWrite => "project:write",
+            Self::VersionUpload => "version:upload",
+            Self::Moderation => "moderation",
+            Self::Admin => "admin",
+            Self::Account => "account",
+        }
+    }
+}
+
+impl fmt::Display for TokenScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A personal access token, created by a user for CI or API clients.
+/// Only a hash of the token is stored.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    Identifiable,
+    Queryable,
+    Selectable,
+    Associations,
+    ToSchema,
+    ToResponse,
+)]
+#[diesel(table_name = personal_access_tokens)]
+#[diesel(belongs_to(crate::User))]
+#[diesel(check_for_backend(Pg))]
+pub struct PersonalAccessToken {
+    /// The token's ID.
+    pub id: i32,
+
+    /// The user's ID.
+    pub user_id: i32,
+
+    /// The token's name.
+    pub name: String,
+
+    /// The SHA-256 hash of the token, as hex.
+    #[serde(skip)]
+    pub token_hash: String,
+
+    /// The first few characters of the token, to help tell tokens apart.
+    pub prefix: String,
+
+    /// The scopes the token has.
+    pub scopes: Vec<TokenScope>,
+
+    /// When the token expires, if ever.
+    pub expires: Option<NaiveDateTime>,
+
+    /// When the token was last used.
+    pub last_used: Option<NaiveDateTime>,
+
+    /// When the token was created.
+    pub created_at: NaiveDateTime,
+}
+
+/// A model for creating a new personal access token in the database.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    Queryable,
+    Selectable,
+    Associations,
+    Insertable,
+    ToSchema,
+    ToResponse,
+)]
+#[diesel(table_name = personal_access_tokens)]
+#[diesel(belongs_to(crate::User))]
+#[diesel(check_for_backend(Pg))]
+pub struct NewPersonalAccessToken {
+    /// The user's ID.
+    pub user_id: i32,
+
+    /// The token's name.
+    pub name: String,
+
+    /// The SHA-256 hash of the token, as hex.
+    pub token_hash: String,
+
+    /// The first few characters of the token.
+    pub prefix: String,
+
+    /// The scopes the token has.
+    pub scopes: Vec<TokenScope>,
+
+    /// When the token expires, if ever.
+    pub expires: Option<NaiveDateTime>,
+}
+
+impl PersonalAccessToken {
+    /// Check if the token has a scope.
+    pub fn has_scope(&self, scope: TokenScope) -> bool {
+        self.scopes.contains(&scope)
+    }
+}
@@ -1,6 +1,6 @@
 //! User-related models.
 
-use crate::schema::{user_tokens, users};
+use crate::schema::{local_credentials, user_identities, user_tokens, users};
 use chrono::NaiveDateTime;
 use diesel::pg::Pg;
 
@@ -31,7 +31,8 @@ pub struct User {
     pub username: String,
 
     /// The user's GitHub ID.
-    pub github_id: i32,
+    /// This is only present for users who have signed in with GitHub.
+    pub github_id: Option<i32>,
 
     /// Are they an admin?
     pub admin: bool,
@@ -63,11 +64,11 @@ pub struct NewUser {
     /// The user's username.
     pub username: String,
 
-    /// The user's GitHub ID.
-    pub github_id: i32,
+    /// The user's GitHub ID, if they signed up with GitHub.
+    pub github_id: Option<i32>,
 }
 
-/// A user's token.
+/// A user's session token.
 #[derive(
     Debug,
     Clone,
@@ -96,10 +97,25 @@ pub struct UserToken {
     pub user_id: i32,
 
     /// The token's value.
+    /// This is never sent back in responses.
+    #[serde(skip)]
     pub value: String,
 
     /// The token's expiration date.
     pub expires: NaiveDateTime,
+
+    /// When the session was created.
+    pub created_at: NaiveDateTime,
+
+    /// When the session was last used.
+    /// This is only updated every few minutes.
+    pub last_used: Option<NaiveDateTime>,
+
+    /// The user agent of the client that logged in.
+    pub user_agent: Option<String>,
+
+    /// The IP address of the client that logged in.
+    pub ip: Option<String>,
 }
 
 /// A model for creating a new user token in the database.
@@ -132,4 +148,148 @@ pub struct NewUserToken {
 
     /// The token's expiration date.
     pub expires: NaiveDateTime,
+
+    /// The user agent of the client that logged in.
+    pub user_agent: Option<String>,
+
+    /// The IP address of the client that logged in.
+    pub ip: Option<String>,
+}
+
+/// An account from an auth provider that's linked to a user.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    Identifiable,
+    Queryable,
+    Selectable,
+    Associations,
+    ToSchema,
+    ToResponse,
+)]
+#[diesel(table_name = user_identities)]
+#[diesel(belongs_to(User))]
+#[diesel(check_for_backend(Pg))]
+pub struct UserIdentity {
+    /// The identity's ID.
+    pub id: i32,
+
+    /// The user's ID.
+    pub user_id: i32,
+
+    /// The ID of the auth provider (like `github` or `discord`).
+    pub provider: String,
+
+    /// The account's unique ID with the provider.
+    pub subject: String,
+
+    /// The account's username with the provider.
+    pub username: String,
+
+    /// The account's email address, if the provider shared it.
+    pub email: Option<String>,
+
+    /// When this identity was linked.
+    pub created_at: NaiveDateTime,
+}
+
+/// A model for linking a new identity to a user in the database.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    Queryable,
+    Selectable,
+    Associations,
+    Insertable,
+    ToSchema,
+    ToResponse,
+)]
+#[diesel(table_name = user_identities)]
+#[diesel(belongs_to(User))]
+#[diesel(check_for_backend(Pg))]
+pub struct NewUserIdentity {
+    /// The user's ID.
+    pub user_id: i32,
+
+    /// The ID of the auth provider.
+    pub provider: String,
+
+    /// The account's unique ID with the provider.
+    pub subject: String,
+
+    /// The account's username with the provider.
+    pub username: String,
+
+    /// The account's email address, if the provider shared it.
+    pub email: Option<String>,
+}
+
+/// The password for a user's local identity.
+/// This is never sent in API responses, so it isn't serializable.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Identifiable,
+    Queryable,
+    Selectable,
+    Associations,
+)]
+#[diesel(table_name = local_credentials)]
+#[diesel(primary_key(identity_id))]
+#[diesel(belongs_to(UserIdentity, foreign_key = identity_id))]
+#[diesel(check_for_backend(Pg))]
+pub struct LocalCredential {
+    /// The local identity's ID.
+    pub identity_id: i32,
+
+    /// The argon2 hash of the password, as a PHC string.
+    pub password_hash: String,
+
+    /// When the password was last changed.
+    pub updated_at: NaiveDateTime,
+}
+
+/// A model for setting the password of a local identity in the database.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Queryable,
+    Selectable,
+    Associations,
+    Insertable,
+    AsChangeset,
+)]
+#[diesel(table_name = local_credentials)]
+#[diesel(belongs_to(UserIdentity, foreign_key = identity_id))]
+#[diesel(check_for_backend(Pg))]
+pub struct NewLocalCredential {
+    /// The local identity's ID.
+    pub identity_id: i32,
+
+    /// The argon2 hash of the password, as a PHC string.
+    pub password_hash: String,
 }
@@ -1,11 +1,18 @@
 //! Utilities for tokens.
 
-use crate::{DbConn, DbPool, NewUserToken, User, UserToken, user_tokens, users};
-use chrono::{DateTime, Utc};
-use diesel::{ExpressionMethods, OptionalExtension, QueryDsl, SelectableHelper, insert_into};
+use crate::{
+    DbConn, DbPool, NewPersonalAccessToken, NewUserToken, PersonalAccessToken, TokenScope, User,
+    UserToken, personal_access_tokens, user_tokens, users,
+};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use diesel::{
+    BelongingToDsl, ExpressionMethods, OptionalExtension, PgSortExpressionMethods, QueryDsl,
+    SelectableHelper, delete, insert_into, update,
+};
 use diesel_async::RunQueryDsl;
 use modhost_core::Result;
 use random_string::{charsets::ALPHANUMERIC, generate};
+use sha2::{Digest, Sha256};
 
 /// The time until a token expires in milliseconds.
 /// Calculation: 1 * SECS_PER_WEEK * MILLIS_PER_SEC
@@ -15,34 +22,67 @@ pub const TOKEN_EXPIRE_TIME: i64 = 1 * 604800 * 1000;
 /// The length of a token string.
 pub const TOKEN_LENGTH: usize = 64;
 
+/// How long to wait before recording another use of a token, in milliseconds.
+/// Calculation: 5 * SECS_PER_MIN * MILLIS_PER_SEC
+pub const LAST_USED_INTERVAL: i64 = 5 * 60 * 1000;
+
+/// The prefix of every personal access token.
+/// This is how they're told apart from session tokens.
+pub const PERSONAL_TOKEN_PREFIX: &str = "mhp_";
+
+/// The length of a personal access token string, not including its prefix.
+pub const PERSONAL_TOKEN_LENGTH: usize = 48;
+
+/// How many characters of a personal access token (including its prefix)
+/// are kept in plain text so users can tell their tokens apart.
+pub const PERSONAL_TOKEN_HINT_LENGTH: usize = 8;
+
 /// Generate a token to insert into the database.
-pub fn generate_token(user_id: i32) -> NewUserToken {
+pub fn generate_token(
+    user_id: i32,
+    user_agent: Option<String>,
+    ip: Option<String>,
+) -> NewUserToken {
     NewUserToken {
         user_id,
         value: generate(TOKEN_LENGTH, ALPHANUMERIC),
         expires: DateTime::from_timestamp_millis(Utc::now().timestamp_millis() + TOKEN_EXPIRE_TIME)
             .unwrap()
             .naive_utc(),
+        user_agent,
+        ip,
     }
 }
 
 /// Create a token and insert it into the database.
-pub async fn create_token(user_id: i32, pool: &DbPool) -> Result<UserToken> {
+/// The `user_agent` and `ip` are the client's, and are only used for listing sessions.
+pub async fn create_token(
+    user_id: i32,
+    user_agent: Option<String>,
+    ip: Option<String>,
+    pool: &DbPool,
+) -> Result<UserToken> {
     Ok(insert_into(user_tokens::table)
-        .values(&generate_token(user_id))
+        .values(&generate_token(user_id, user_agent, ip))
         .returning(UserToken::as_returning())
         .get_result(&mut pool.get().await?)
         .await?)
 }
 
-/// Get the user the provided token belongs to.
-pub async fn get_user_for_token(token: impl AsRef<str>, conn: &mut DbConn) -> Result<Option<User>> {
-    let token = user_tokens::table
+/// Get the session matching a token, if it exists and hasn't expired.
+pub async fn get_session(token: impl AsRef<str>, conn: &mut DbConn) -> Result<Option<UserToken>> {
+    Ok(user_tokens::table
         .filter(user_tokens::value.eq(token.as_ref().to_string()))
+        .filter(user_tokens::expires.gt(Utc::now().naive_utc()))
         .select(UserToken::as_select())
         .first(conn)
         .await
-        .optional()?;
+        .optional()?)
+}
+
+/// Get the user the provided token belongs to.
+pub async fn get_user_for_token(token: impl AsRef<str>, conn: &mut DbConn) -> Result<Option<User>> {
+    let token = get_session(token, conn).await?;
 
     if let Some(token) = token {
         Ok(users::table
@@ -55,3 +95,145 @@ pub async fn get_user_for_token(token: impl AsRef<str>, conn: &mut DbConn) -> Re
         Ok(None)
     }
 }
+
+/// Get a user's active sessions, most recently used first.
+pub async fn get_sessions(user: &User, conn: &mut DbConn) -> Result<Vec<UserToken>> {
+    Ok(UserToken::belonging_to(user)
+        .filter(user_tokens::expires.gt(Utc::now().naive_utc()))
+        .select(UserToken::as_select())
+        .order((
+            user_tokens::last_used.desc().nulls_last(),
+            user_tokens::created_at.desc(),
+        ))
+        .load(conn)
+        .await?)
+}
+
+/// Revoke one of a user's sessions.
+/// This returns `false` if the user doesn't have a session with that ID.
+pub async fn revoke_session(user_id: i32, session: i32, conn: &mut DbConn) -> Result<bool> {
+    Ok(delete(user_tokens::table)
+        .filter(user_tokens::id.eq(session))
+        .filter(user_tokens::user_id.eq(user_id))
+        .execute(conn)
+        .await?
+        > 0)
+}
+
+/// Revoke all of a user's sessions, returning how many there were.
+pub async fn revoke_sessions(user_id: i32, conn: &mut DbConn) -> Result<usize> {
+    Ok(delete(user_tokens::table)
+        .filter(user_tokens::user_id.eq(user_id))
+        .execute(conn)
+        .await?)
+}
+
+/// Record that a session was just used.
+/// To avoid a write on every request, this only does anything if the
+/// last recorded use was more than [`LAST_USED_INTERVAL`] ago.
+pub async fn touch_session(token: &UserToken, conn: &mut DbConn) -> Result<()> {
+    let now = Utc::now().naive_utc();
+
+    if token
+        .last_used
+        .is_some_and(|v| (now - v).num_milliseconds() < LAST_USED_INTERVAL)
+    {
+        return Ok(());
+    }
+
+    update(user_tokens::table)
+        .filter(user_tokens::id.eq(token.id))
+        .set(user_tokens::last_used.eq(now))
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+/// Hash a personal access token for storing or looking it up.
+pub fn hash_token(token: impl AsRef<str>) -> String {
+    format!("{:x}", Sha256::digest(token.as_ref().as_bytes()))
+}
+
+/// Check if a token string is a personal access token.
+pub fn is_personal_token(token: impl AsRef<str>) -> bool {
+    token.as_ref().starts_with(PERSONAL_TOKEN_PREFIX)
+}
+
+/// Create a personal access token and insert it into the database.
+/// This returns the token's raw value, which is the only time it's available.
+pub async fn create_personal_token(
+    user_id: i32,
+    name: impl AsRef<str>,
+    scopes: Vec<TokenScope>,
+    expires: Option<NaiveDateTime>,
+    conn: &mut DbConn,
+) -> Result<(PersonalAccessToken, String)> {
+    let value = format!(
+        "{}{}",
+        PERSONAL_TOKEN_PREFIX,
+        generate(PERSONAL_TOKEN_LENGTH, ALPHANUMERIC)
+    );
+
+    let token = insert_into(personal_access_tokens::table)
+        .values(&NewPersonalAccessToken {
+            user_id,
+            name: name.as_ref().to_string(),
+            token_hash: hash_token(&value),
+            prefix: value[..PERSONAL_TOKEN_HINT_LENGTH].to_string(),
+            scopes,
+            expires,
+        })
+        .returning(PersonalAccessToken::as_returning())
+        .get_result(conn)
+        .await?;
+
+    Ok((token, value))
+}
+
+/// Get the personal access token matching a raw token value, if it exists.
+/// This doesn't check if the token has expired.
+pub async fn get_personal_token(
+    token: impl AsRef<str>,
+    conn: &mut DbConn,
+) -> Result<Option<PersonalAccessToken>> {
+    Ok(personal_access_tokens::table
+        .filter(personal_access_tokens::token_hash.eq(hash_token(token)))
+        .select(PersonalAccessToken::as_select())
+        .first(conn)
+        .await
+        .optional()?)
+}
+
+/// Get a user's personal access tokens, newest first.
+pub async fn get_personal_tokens(
+    user: &User,
+    conn: &mut DbConn,
+) -> Result<Vec<PersonalAccessToken>> {
+    Ok(PersonalAccessToken::belonging_to(user)
+        .select(PersonalAccessToken::as_select())
+        .order(personal_access_tokens::created_at.desc())
+        .load(conn)
+        .await?)
+}
+
+/// Record that a personal access token was just used.
+/// Like [`touch_session`], this only writes every [`LAST_USED_INTERVAL`].
+pub async fn touch_personal_token(token: &PersonalAccessToken, conn: &mut DbConn) -> Result<()> {
+    let now = Utc::now().naive_utc();
+
+    if token
+        .last_used
+        .is_some_and(|v| (now - v).num_milliseconds() < LAST_USED_INTERVAL)
+    {
+        return Ok(());
+    }
+
+    update(personal_access_tokens::table)
+        .filter(personal_access_tokens::id.eq(token.id))
+        .set(personal_access_tokens::last_used.eq(now))
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
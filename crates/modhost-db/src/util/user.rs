@@ -1,11 +1,21 @@
 //! Utilities for users.
 
-use crate::{DbConn, User, schema::users};
+use crate::{
+    DbConn, User, UserIdentity,
+    schema::{user_identities, users},
+};
 use diesel::{
-    ExpressionMethods, OptionalExtension, PgTextExpressionMethods, QueryDsl, SelectableHelper,
+    BelongingToDsl, ExpressionMethods, OptionalExtension, PgTextExpressionMethods, QueryDsl,
+    SelectableHelper, TextExpressionMethods, define_sql_function, sql_types::Text,
 };
 use diesel_async::RunQueryDsl;
 use modhost_core::Result;
+use std::collections::HashSet;
+
+define_sql_function! {
+    /// SQL's `LOWER` function.
+    fn lower(x: Text) -> Text;
+}
 
 /// Get a user by their ID, GitHub ID, or their username.
 pub async fn get_user(id: impl AsRef<str>, conn: &mut DbConn) -> Result<User> {
@@ -50,3 +60,92 @@ pub async fn search_users(name: impl AsRef<str>, conn: &mut DbConn) -> Result<Ve
         .load(conn)
         .await?)
 }
+
+/// Check if a username is used by any user other than `except`.
+/// Usernames are compared case-insensitively.
+pub async fn username_taken(
+    username: impl AsRef<str>,
+    except: Option<i32>,
+    conn: &mut DbConn,
+) -> Result<bool> {
+    let mut query = users::table
+        .filter(lower(users::username).eq(username.as_ref().to_lowercase()))
+        .select(users::id)
+        .into_boxed();
+
+    if let Some(except) = except {
+        query = query.filter(users::id.ne(except));
+    }
+
+    Ok(query.first::<i32>(conn).await.optional()?.is_some())
+}
+
+/// Get a username that no user other than `except` has, based on `username`.
+/// If it's taken, a number is added to the end (like `name-2`).
+pub async fn unique_username(
+    username: impl AsRef<str>,
+    except: Option<i32>,
+    conn: &mut DbConn,
+) -> Result<String> {
+    let username = username.as_ref();
+
+    if !username_taken(username, except, conn).await? {
+        return Ok(username.into());
+    }
+
+    let base = username.to_lowercase();
+
+    let mut query = users::table
+        .filter(lower(users::username).like(format!("{}-%", escape_like(&base))))
+        .select(lower(users::username))
+        .into_boxed();
+
+    if let Some(except) = except {
+        query = query.filter(users::id.ne(except));
+    }
+
+    let taken = query
+        .load::<String>(conn)
+        .await?
+        .into_iter()
+        .collect::<HashSet<_>>();
+    let mut n = 2;
+
+    while taken.contains(&format!("{}-{}", base, n)) {
+        n += 1;
+    }
+
+    Ok(format!("{}-{}", username, n))
+}
+
+/// Escape the wildcards in a string for a `LIKE` pattern.
+fn escape_like(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+/// Get the identities linked to a user.
+pub async fn get_user_identities(user: &User, conn: &mut DbConn) -> Result<Vec<UserIdentity>> {
+    Ok(UserIdentity::belonging_to(user)
+        .select(UserIdentity::as_select())
+        .order(user_identities::id)
+        .load(conn)
+        .await?)
+}
+
+/// Find an identity by its provider and the account's ID with that provider.
+pub async fn find_user_identity(
+    provider: impl AsRef<str>,
+    subject: impl AsRef<str>,
+    conn: &mut DbConn,
+) -> Result<Option<UserIdentity>> {
+    Ok(user_identities::table
+        .filter(user_identities::provider.eq(provider.as_ref()))
+        .filter(user_identities::subject.eq(subject.as_ref()))
+        .select(UserIdentity::as_select())
+        .first(conn)
+        .await
+        .optional()?)
+}
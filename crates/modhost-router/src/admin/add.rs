@@ -9,7 +9,7 @@ use diesel::{ExpressionMethods, update};
 use diesel_async::RunQueryDsl;
 use modhost_auth::get_user_from_req;
 use modhost_core::{AppError, Result};
-use modhost_db::{get_user, users};
+use modhost_db::{TokenScope, get_user, users};
 use modhost_server_core::state::AppState;
 
 /// Add Admin
@@ -39,7 +39,7 @@ pub async fn add_handler(
 ) -> Result<()> {
     let mut conn = state.pool.get().await?;
     let to_add = get_user(user, &mut conn).await?;
-    let user = get_user_from_req(&jar, &headers, &mut conn).await?;
+    let user = get_user_from_req(&jar, &headers, TokenScope::Admin, &mut conn).await?;
 
     if !user.admin {
         return Err(AppError::NoAccess);
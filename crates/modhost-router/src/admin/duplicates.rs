@@ -0,0 +1,42 @@
+//! The duplicate files report route.
+
+use crate::util::duplicates::{DuplicateGroup, find_duplicates};
+use axum::{Json, extract::State, http::HeaderMap};
+use axum_extra::extract::CookieJar;
+use modhost_auth::get_user_from_req;
+use modhost_core::{AppError, Result};
+use modhost_db::TokenScope;
+use modhost_server_core::state::AppState;
+
+/// Duplicate Files Report
+///
+/// Find files that were uploaded to more than one project. Each group lists the
+/// first upload of a file, and the later uploads of it to other projects, along
+/// with the authors of each project, so re-uploads by other people stand out.
+#[utoipa::path(
+    get,
+    path = "/duplicates/report",
+    tag = "Admin",
+    responses(
+        (status = 200, description = "Created the report!", body = Vec<DuplicateGroup>),
+        (status = INTERNAL_SERVER_ERROR, description = "An internal error occured!"),
+    ),
+    security(
+        ("api_auth_token" = []),
+    ),
+)]
+#[debug_handler]
+pub async fn report_handler(
+    jar: CookieJar,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<DuplicateGroup>>> {
+    let mut conn = state.pool.get().await?;
+    let user = get_user_from_req(&jar, &headers, TokenScope::Admin, &mut conn).await?;
+
+    if !user.admin {
+        return Err(AppError::NoAccess);
+    }
+
+    Ok(Json(find_duplicates(&mut conn).await?))
+}
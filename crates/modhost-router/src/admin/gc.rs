@@ -0,0 +1,68 @@
+//! The storage garbage collection route.
+
+use crate::util::gc::{GcReport, collect_garbage};
+use axum::{
+    Json,
+    extract::{Query, State},
+    http::HeaderMap,
+};
+use axum_extra::extract::CookieJar;
+use modhost_auth::get_user_from_req;
+use modhost_core::{AppError, Result};
+use modhost_db::TokenScope;
+use modhost_server_core::state::AppState;
+
+/// Query params for the storage garbage collection route.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, ToSchema, ToResponse, Serialize, Deserialize,
+)]
+pub struct GcQueryParams {
+    /// Only report what would be deleted, without deleting anything.
+    /// Defaults to `true`.
+    pub dry_run: Option<bool>,
+}
+
+/// Collect Garbage
+///
+/// Find objects in storage that aren't referenced by any version file or gallery image,
+/// and rows whose objects are missing. Unless this is a dry run, unreferenced objects
+/// older than the configured grace period are deleted.
+#[utoipa::path(
+    post,
+    path = "/storage/gc",
+    tag = "Admin",
+    params(
+        ("dry_run" = Option<bool>, Query, description = "Only report what would be deleted. Defaults to true."),
+    ),
+    responses(
+        (status = 200, description = "Collected garbage!", body = GcReport),
+        (status = INTERNAL_SERVER_ERROR, description = "An internal error occured!"),
+    ),
+    security(
+        ("api_auth_token" = []),
+    ),
+)]
+#[debug_handler]
+pub async fn gc_handler(
+    jar: CookieJar,
+    headers: HeaderMap,
+    Query(GcQueryParams { dry_run }): Query<GcQueryParams>,
+    State(state): State<AppState>,
+) -> Result<Json<GcReport>> {
+    let mut conn = state.pool.get().await?;
+    let user = get_user_from_req(&jar, &headers, TokenScope::Admin, &mut conn).await?;
+
+    if !user.admin {
+        return Err(AppError::NoAccess);
+    }
+
+    Ok(Json(
+        collect_garbage(
+            &state.buckets,
+            state.config.storage.gc_grace_period,
+            dry_run.unwrap_or(true),
+            &mut conn,
+        )
+        .await?,
+    ))
+}
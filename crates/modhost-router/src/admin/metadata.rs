@@ -0,0 +1,137 @@
+//! The unknown metadata report route.
+
+use crate::util::metadata::find_unknown;
+use axum::{Json, extract::State, http::HeaderMap};
+use axum_extra::extract::CookieJar;
+use diesel::{QueryDsl, SelectableHelper};
+use diesel_async::RunQueryDsl;
+use modhost_auth::get_user_from_req;
+use modhost_core::{AppError, Result};
+use modhost_db::{Project, ProjectVersion, TokenScope, project_versions, projects};
+use modhost_server_core::state::AppState;
+
+/// A project version that references unknown loaders or game versions.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema, ToResponse)]
+pub struct UnknownVersionMetadata {
+    /// The project ID.
+    pub project: i32,
+
+    /// The version ID.
+    pub version: i32,
+
+    /// The version number.
+    pub version_number: String,
+
+    /// The unknown loaders.
+    pub loaders: Vec<String>,
+
+    /// The unknown game versions.
+    pub game_versions: Vec<String>,
+}
+
+/// A project that references unknown tags.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema, ToResponse)]
+pub struct UnknownProjectMetadata {
+    /// The project ID.
+    pub project: i32,
+
+    /// The project's slug.
+    pub slug: String,
+
+    /// The unknown tags.
+    pub tags: Vec<String>,
+}
+
+/// A report of existing rows that reference unknown metadata.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema, ToResponse)]
+pub struct UnknownMetadataReport {
+    /// Versions with unknown loaders or game versions.
+    pub versions: Vec<UnknownVersionMetadata>,
+
+    /// Projects with unknown tags.
+    pub projects: Vec<UnknownProjectMetadata>,
+}
+
+/// Unknown Metadata Report
+///
+/// Find projects and versions that reference loaders, game versions,
+/// or tags that aren't registered on the server.
+#[utoipa::path(
+    get,
+    path = "/metadata/report",
+    tag = "Admin",
+    responses(
+        (status = 200, description = "Created the report!", body = UnknownMetadataReport),
+        (status = INTERNAL_SERVER_ERROR, description = "An internal error occured!"),
+    ),
+    security(
+        ("api_auth_token" = []),
+    ),
+)]
+#[debug_handler]
+pub async fn report_handler(
+    jar: CookieJar,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Result<Json<UnknownMetadataReport>> {
+    let mut conn = state.pool.get().await?;
+    let user = get_user_from_req(&jar, &headers, TokenScope::Admin, &mut conn).await?;
+
+    if !user.admin {
+        return Err(AppError::NoAccess);
+    }
+
+    let versions = project_versions::table
+        .select(ProjectVersion::as_select())
+        .order(project_versions::id)
+        .load(&mut conn)
+        .await?
+        .into_iter()
+        .filter_map(|ver| {
+            let loaders = find_unknown(
+                ver.loaders.iter().flatten(),
+                state.loaders.iter().map(|v| &v.id),
+            );
+
+            let game_versions = find_unknown(
+                ver.game_versions.iter().flatten(),
+                state.game_versions.iter().map(|v| &v.id),
+            );
+
+            if loaders.is_empty() && game_versions.is_empty() {
+                None
+            } else {
+                Some(UnknownVersionMetadata {
+                    project: ver.project,
+                    version: ver.id,
+                    version_number: ver.version_number,
+                    loaders,
+                    game_versions,
+                })
+            }
+        })
+        .collect();
+
+    let projects = projects::table
+        .select(Project::as_select())
+        .order(projects::id)
+        .load(&mut conn)
+        .await?
+        .into_iter()
+        .filter_map(|pkg| {
+            let tags = find_unknown(pkg.tags.iter().flatten(), state.tags.iter().map(|v| &v.id));
+
+            if tags.is_empty() {
+                None
+            } else {
+                Some(UnknownProjectMetadata {
+                    project: pkg.id,
+                    slug: pkg.slug,
+                    tags,
+                })
+            }
+        })
+        .collect();
+
+    Ok(Json(UnknownMetadataReport { versions, projects }))
+}
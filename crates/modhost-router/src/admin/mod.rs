@@ -2,12 +2,15 @@
 
 use axum::{
     Router,
-    routing::{delete, get, put},
+    routing::{delete, get, post, put},
 };
 use modhost_server_core::state::AppState;
 
 pub mod add;
+pub mod duplicates;
+pub mod gc;
 pub mod list;
+pub mod metadata;
 pub mod projects;
 pub mod remove;
 pub mod stats;
@@ -25,7 +28,15 @@ pub fn router(state: AppState) -> Router<AppState> {
         .route("/users/list", get(users::list::list_handler))
         .route("/users/{id}", get(users::get::get_handler))
         .route("/users/{id}", delete(users::delete::delete_handler))
+        .route("/users/{id}/sessions", get(users::sessions::list_handler))
+        .route(
+            "/users/{id}/sessions",
+            delete(users::sessions::revoke_handler),
+        )
         .route("/stats/ws", get(stats_ws::stats_socket_handler))
+        .route("/storage/gc", post(gc::gc_handler))
+        .route("/metadata/report", get(metadata::report_handler))
+        .route("/duplicates/report", get(duplicates::report_handler))
         .with_state(state)
 }
 
@@ -40,6 +51,11 @@ pub fn router(state: AppState) -> Router<AppState> {
     users::list::list_handler,
     users::get::get_handler,
     users::delete::delete_handler,
+    users::sessions::list_handler,
+    users::sessions::revoke_handler,
     stats_ws::stats_socket_handler,
+    gc::gc_handler,
+    metadata::report_handler,
+    duplicates::report_handler,
 ))]
 pub struct AdminApi;
@@ -5,6 +5,7 @@ use axum::{Json, extract::State, http::HeaderMap};
 use axum_extra::extract::CookieJar;
 use modhost_auth::get_user_from_req;
 use modhost_core::{AppError, Result};
+use modhost_db::TokenScope;
 use modhost_server_core::state::AppState;
 
 /// Stats
@@ -29,7 +30,7 @@ pub async fn stats_handler(
     State(state): State<AppState>,
 ) -> Result<Json<AdminStats>> {
     let mut conn = state.pool.get().await?;
-    let user = get_user_from_req(&jar, &headers, &mut conn).await?;
+    let user = get_user_from_req(&jar, &headers, TokenScope::Admin, &mut conn).await?;
 
     if !user.admin {
         return Err(AppError::NoAccess);
@@ -37,8 +38,8 @@ pub async fn stats_handler(
 
     Ok(Json(
         fetch_stats(
-            &state.buckets.projects,
-            &state.buckets.gallery,
+            state.buckets.projects.as_ref(),
+            state.buckets.gallery.as_ref(),
             &state.search.projects(),
             &mut conn,
         )
@@ -66,8 +66,8 @@ pub async fn handle_stats_socket(socket: WebSocket, state: AppState) -> Result<(
         tx.send(Message::Text(
             serde_json::to_string(
                 &fetch_stats(
-                    &state.buckets.projects,
-                    &state.buckets.gallery,
+                    state.buckets.projects.as_ref(),
+                    state.buckets.gallery.as_ref(),
                     &state.search.projects(),
                     &mut state.pool.get().await?,
                 )
@@ -10,7 +10,7 @@ use diesel::{ExpressionMethods, SelectableHelper, delete};
 use diesel_async::RunQueryDsl;
 use modhost_auth::get_user_from_req;
 use modhost_core::{AppError, Result};
-use modhost_db::{User, get_user, users};
+use modhost_db::{TokenScope, User, get_user, users};
 use modhost_server_core::state::AppState;
 
 /// Delete User
@@ -36,7 +36,7 @@ pub async fn delete_handler(
     Path(id): Path<String>,
 ) -> Result<Json<User>> {
     let mut conn = state.pool.get().await?;
-    let user = get_user_from_req(&jar, &headers, &mut conn).await?;
+    let user = get_user_from_req(&jar, &headers, TokenScope::Admin, &mut conn).await?;
 
     if !user.admin {
         return Err(AppError::NoAccess);
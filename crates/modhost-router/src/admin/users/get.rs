@@ -8,7 +8,7 @@ use axum::{
 use axum_extra::extract::CookieJar;
 use modhost_auth::get_user_from_req;
 use modhost_core::{AppError, Result};
-use modhost_db::{User, get_user};
+use modhost_db::{TokenScope, User, get_user};
 use modhost_server_core::state::AppState;
 
 /// Get User
@@ -34,7 +34,7 @@ pub async fn get_handler(
     Path(id): Path<String>,
 ) -> Result<Json<User>> {
     let mut conn = state.pool.get().await?;
-    let user = get_user_from_req(&jar, &headers, &mut conn).await?;
+    let user = get_user_from_req(&jar, &headers, TokenScope::Admin, &mut conn).await?;
 
     if !user.admin {
         return Err(AppError::NoAccess);
@@ -6,7 +6,7 @@ use diesel::{QueryDsl, SelectableHelper};
 use diesel_async::RunQueryDsl;
 use modhost_auth::get_user_from_req;
 use modhost_core::{AppError, Result};
-use modhost_db::{User, users};
+use modhost_db::{TokenScope, User, users};
 use modhost_server_core::state::AppState;
 
 /// List Users
@@ -31,7 +31,7 @@ pub async fn list_handler(
     State(state): State<AppState>,
 ) -> Result<Json<Vec<User>>> {
     let mut conn = state.pool.get().await?;
-    let user = get_user_from_req(&jar, &headers, &mut conn).await?;
+    let user = get_user_from_req(&jar, &headers, TokenScope::Admin, &mut conn).await?;
 
     if !user.admin {
         return Err(AppError::NoAccess);
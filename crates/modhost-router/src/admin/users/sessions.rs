@@ -0,0 +1,89 @@
+//! The user session admin routes.
+
+use axum::{
+    Json,
+    body::Body,
+    extract::{Path, State},
+    http::HeaderMap,
+    response::Response,
+};
+use axum_extra::extract::CookieJar;
+use modhost_auth::get_user_from_req;
+use modhost_core::{AppError, Result};
+use modhost_db::{TokenScope, UserToken, get_sessions, get_user, revoke_sessions};
+use modhost_server_core::state::AppState;
+
+/// List User Sessions
+///
+/// List a user's active login sessions.
+#[utoipa::path(
+    get,
+    path = "/users/{id}/sessions",
+    tag = "Admin",
+    params(
+        ("id" = String, Path, description = "The user's ID or username."),
+    ),
+    responses(
+        (status = 200, description = "Got the sessions!", body = Vec<UserToken>),
+        (status = INTERNAL_SERVER_ERROR, description = "An internal error occured!"),
+    ),
+    security(
+        ("api_auth_token" = []),
+    ),
+)]
+#[debug_handler]
+pub async fn list_handler(
+    jar: CookieJar,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<Vec<UserToken>>> {
+    let mut conn = state.pool.get().await?;
+    let user = get_user_from_req(&jar, &headers, TokenScope::Admin, &mut conn).await?;
+
+    if !user.admin {
+        return Err(AppError::NoAccess);
+    }
+
+    let target = get_user(id, &mut conn).await?;
+
+    Ok(Json(get_sessions(&target, &mut conn).await?))
+}
+
+/// Revoke User Sessions
+///
+/// End all of a user's login sessions, logging them out everywhere.
+#[utoipa::path(
+    delete,
+    path = "/users/{id}/sessions",
+    tag = "Admin",
+    params(
+        ("id" = String, Path, description = "The user's ID or username."),
+    ),
+    responses(
+        (status = 200, description = "Revoked the sessions!", body = String),
+        (status = INTERNAL_SERVER_ERROR, description = "An internal error occured!"),
+    ),
+    security(
+        ("api_auth_token" = []),
+    ),
+)]
+#[debug_handler]
+pub async fn revoke_handler(
+    jar: CookieJar,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Response> {
+    let mut conn = state.pool.get().await?;
+    let user = get_user_from_req(&jar, &headers, TokenScope::Admin, &mut conn).await?;
+
+    if !user.admin {
+        return Err(AppError::NoAccess);
+    }
+
+    let target = get_user(id, &mut conn).await?;
+    let count = revoke_sessions(target.id, &mut conn).await?;
+
+    Ok(Response::builder().body(Body::new(format!("Revoked {} session(s)!", count)))?)
+}
@@ -1,139 +1,301 @@
-//! The route for the GitHub authentication callback.
+//! The route for the authentication callback.
 
 use axum::{
     body::Body,
-    extract::State,
+    extract::{ConnectInfo, Path, Query, State},
     http::{
-        HeaderValue, StatusCode, Uri,
+        HeaderMap, HeaderValue, StatusCode,
         header::{LOCATION, SET_COOKIE},
     },
     response::Response,
 };
-use axum_extra::extract::Host;
+use axum_extra::extract::{CookieJar, Host};
 use diesel::{
     ExpressionMethods, OptionalExtension, QueryDsl, SelectableHelper, dsl::insert_into, update,
 };
 use diesel_async::RunQueryDsl;
-use modhost_core::Result;
-use modhost_db::{NewUser, User, create_token, users};
+use modhost_auth::{
+    get_user_from_req,
+    login::{LOGIN_COOKIE, LoginState},
+    providers::ProviderIdentity,
+};
+use modhost_core::{AppError, Result};
+use modhost_db::{
+    DbConn, NewUser, NewUserIdentity, TokenScope, User, create_token, find_user_identity,
+    unique_username, user_identities, users,
+};
 use modhost_middleware::scheme::Scheme;
-use modhost_server_core::{github::create_github_client, state::AppState};
-use oauth2::{RedirectUrl, TokenResponse};
-use std::collections::HashMap;
+use modhost_server_core::state::AppState;
+use oauth2::{AuthorizationCode, PkceCodeVerifier, RedirectUrl, TokenResponse};
+use std::net::SocketAddr;
+
+use super::callback_url;
+use crate::util::{
+    page::error_page,
+    session::{clear_login_cookie, client_ip, resolve_redirect, session_cookie, user_agent},
+};
+
+/// Query params for the auth callback route.
+#[derive(
+    Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, ToSchema, ToResponse, Serialize, Deserialize,
+)]
+pub struct CallbackQueryParams {
+    /// The authorization code from the provider.
+    pub code: Option<String>,
 
-use super::CALLBACK_URL;
+    /// The signed login request, sent back by the provider.
+    pub state: Option<String>,
 
-/// GitHub Auth Callback
+    /// The error from the provider, if the login failed (or was cancelled).
+    pub error: Option<String>,
+
+    /// A description of the provider's error.
+    pub error_description: Option<String>,
+}
+
+/// Auth Callback
 ///
-/// Complete the GitHub login flow.
+/// Complete the login flow with a provider.
+/// If the request is already authenticated, the provider's account is linked to
+/// the current user instead of logging in as (or creating) another one.
 #[utoipa::path(
     get,
-    path = "/github/callback",
+    path = "/{provider}/callback",
     tag = "Auth",
     responses(
         (status = 307, description = "Success, redirecting to user info."),
+        (status = 400, description = "The login request was invalid or has expired!"),
+        (status = 401, description = "The provider rejected the login!"),
+        (status = 404, description = "The provider isn't enabled!"),
+        (status = 409, description = "The account is already linked to another user!"),
     ),
     params(
-        ("code" = String, Query, description = "Response code from GitHub"),
-        ("state" = String, Query, description = "Response state from GitHub"),
+        ("provider" = String, Path, description = "The provider's ID."),
+        ("code" = Option<String>, Query, description = "Response code from the provider"),
+        ("state" = Option<String>, Query, description = "Response state from the provider"),
+        ("error" = Option<String>, Query, description = "Response error from the provider"),
+        ("error_description" = Option<String>, Query, description = "Response error description from the provider"),
     ),
 )]
+#[allow(clippy::too_many_arguments)]
 pub async fn callback_handler(
+    Path(provider): Path<String>,
     State(state): State<AppState>,
     Host(host): Host,
     Scheme(scheme): Scheme,
-    url: Uri,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    jar: CookieJar,
+    headers: HeaderMap,
+    Query(query): Query<CallbackQueryParams>,
+) -> Response {
+    let mut response = finish_login(provider, state, host, scheme, addr, jar, headers, query)
+        .await
+        .unwrap_or_else(error_page);
+
+    // The login request is used up either way.
+    if let Ok(cookie) = clear_login_cookie() {
+        response.headers_mut().append(SET_COOKIE, cookie);
+    }
+
+    response
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn finish_login(
+    provider: String,
+    state: AppState,
+    host: String,
+    scheme: String,
+    addr: SocketAddr,
+    jar: CookieJar,
+    headers: HeaderMap,
+    query: CallbackQueryParams,
 ) -> Result<Response> {
     let mut conn = state.pool.get().await?;
-    let query = url::form_urlencoded::parse(url.query().unwrap().as_bytes())
-        .into_owned()
-        .collect::<HashMap<String, String>>();
+    let provider = state.auth.get(&provider).ok_or(AppError::NotFound)?;
 
-    let code = query.get("code").unwrap();
-    let to = query.get("to");
+    if let Some(error) = query.error {
+        return Err(AppError::AuthFailed(
+            query.error_description.unwrap_or(error),
+        ));
+    }
+
+    let login = LoginState::verify(
+        query.state.ok_or(AppError::InvalidLoginState)?,
+        state.auth.state_secret(),
+    )?;
 
-    let auth_url = format!("{}://{}{}", scheme, host, CALLBACK_URL);
+    if login.provider != provider.id() {
+        return Err(AppError::InvalidLoginState);
+    }
 
-    let client = state.auth.set_redirect_uri(RedirectUrl::new(auth_url)?);
+    // The login must finish in the same browser that started it.
+    let (nonce, verifier) = jar
+        .get(LOGIN_COOKIE)
+        .and_then(|v| {
+            v.value()
+                .split_once('.')
+                .map(|(a, b)| (a.to_string(), b.to_string()))
+        })
+        .ok_or(AppError::InvalidLoginState)?;
 
-    match client
-        .exchange_code(oauth2::AuthorizationCode::new(code.to_owned()))
+    if nonce != login.nonce {
+        return Err(AppError::InvalidLoginState);
+    }
+
+    let code = query.code.ok_or(AppError::MissingField("code".into()))?;
+    let origin = format!("{}://{}", scheme, host);
+
+    let client = provider
+        .client()
+        .await?
+        .set_redirect_uri(RedirectUrl::new(format!(
+            "{}{}",
+            origin,
+            callback_url(provider.id())
+        ))?);
+
+    let token = client
+        .exchange_code(AuthorizationCode::new(code))
+        .set_pkce_verifier(PkceCodeVerifier::new(verifier))
         .request_async(&oauth2::reqwest::Client::new())
         .await
-    {
-        Ok(token) => {
-            let github_token = token.access_token().secret();
-            let client = create_github_client(github_token)?;
-            let me = client.current().user().await?;
-
-            let existing = users::table
-                .filter(users::github_id.eq(me.id.0 as i32))
-                .select(User::as_select())
-                .first(&mut conn)
-                .await
-                .optional()?;
-
-            let user = if let Some(existing) = existing {
-                update(users::table)
-                    .filter(users::id.eq(existing.id))
-                    .set(users::username.eq(me.login))
-                    .returning(User::as_returning())
-                    .get_result(&mut conn)
-                    .await?
-            } else {
-                let user = NewUser {
-                    username: me.login,
-                    github_id: me.id.0 as i32,
-                };
-
-                insert_into(users::table)
-                    .values(&user)
-                    .returning(User::as_returning())
-                    .get_result(&mut conn)
-                    .await?
-            };
+        .map_err(|err| AppError::AuthFailed(err.to_string()))?;
 
-            let token = create_token(user.id, &state.pool).await?;
+    let identity = provider.identity(token.access_token().secret()).await?;
+    let current = get_user_from_req(&jar, &headers, TokenScope::Account, &mut conn)
+        .await
+        .ok();
+    let user = login_user(provider.id(), identity, current, &mut conn).await?;
+    let token = create_token(
+        user.id,
+        user_agent(&headers),
+        Some(client_ip(
+            &headers,
+            addr,
+            &state.config.server.trusted_proxies,
+        )),
+        &state.pool,
+    )
+    .await?;
 
-            let cookie_value = format!(
-                "auth-token={}; HttpOnly; Path=/; Domain={}",
-                token.value,
-                sanitize_port(&host)
-            );
+    // The redirect was checked when the login started, but the allowlist may have changed since.
+    let location = match login.to {
+        Some(to) => {
+            let mut url = resolve_redirect(to, &origin, &state.config.auth.redirect_allowlist)?;
 
-            let mut response = Response::builder()
-                .status(StatusCode::TEMPORARY_REDIRECT)
-                .body(Body::new(token.value.clone()))?;
+            url.query_pairs_mut().append_pair("token", &token.value);
+            url.to_string()
+        }
 
-            let cookie_header = HeaderValue::from_str(&cookie_value)?;
+        None => "/api/v1/users/me".to_string(),
+    };
 
-            response.headers_mut().insert(SET_COOKIE, cookie_header);
+    Ok(Response::builder()
+        .status(StatusCode::TEMPORARY_REDIRECT)
+        .header(LOCATION, HeaderValue::from_str(&location)?)
+        .header(SET_COOKIE, session_cookie(&token.value, &host)?)
+        .body(Body::empty())?)
+}
 
-            if let Some(to) = to {
-                response.headers_mut().insert(
-                    LOCATION,
-                    HeaderValue::from_str(&format!("{}?token={}", to, token.value)).unwrap(),
-                );
-            } else {
-                response
-                    .headers_mut()
-                    .insert(LOCATION, HeaderValue::from_str("/api/v1/users/me").unwrap());
-            }
+/// Find the user an identity belongs to, creating one if it's new.
+/// If someone is already logged in (`current`), the identity is linked to them instead.
+async fn login_user(
+    provider: &str,
+    identity: ProviderIdentity,
+    current: Option<User>,
+    conn: &mut DbConn,
+) -> Result<User> {
+    let existing = find_user_identity(provider, &identity.subject, conn).await?;
 
-            Ok(response)
+    let (user, returning) = match (existing, current) {
+        (Some(existing), Some(current)) if existing.user_id != current.id => {
+            return Err(AppError::IdentityInUse);
         }
 
-        Err(_) => {
-            let mut resp = Response::new(Body::empty());
-            *resp.status_mut() = StatusCode::TEMPORARY_REDIRECT;
-            Ok(resp)
+        (Some(existing), _) => {
+            update(user_identities::table)
+                .filter(user_identities::id.eq(existing.id))
+                .set((
+                    user_identities::username.eq(&identity.username),
+                    user_identities::email.eq(&identity.email),
+                ))
+                .execute(conn)
+                .await?;
+
+            let user = users::table
+                .find(existing.user_id)
+                .select(User::as_select())
+                .first(conn)
+                .await?;
+
+            (user, true)
         }
-    }
-}
 
-fn sanitize_port(host: &str) -> String {
-    match host.split_once(":") {
-        Some((domain, _port)) => domain.to_string(),
-        None => host.to_string(),
-    }
+        (None, current) => {
+            // Users created before identities existed (or by a migrator) only have a GitHub ID.
+            let legacy = match (&current, identity.github_id) {
+                (None, Some(github_id)) => users::table
+                    .filter(users::github_id.eq(github_id))
+                    .select(User::as_select())
+                    .first(conn)
+                    .await
+                    .optional()?,
+
+                _ => None,
+            };
+
+            let returning = legacy.is_some();
+
+            let user = match current.or(legacy) {
+                Some(user) => user,
+
+                None => {
+                    // Provider usernames aren't unique here, so they might need a suffix.
+                    let username = unique_username(&identity.username, None, conn).await?;
+
+                    insert_into(users::table)
+                        .values(&NewUser {
+                            username,
+                            github_id: identity.github_id,
+                        })
+                        .returning(User::as_returning())
+                        .get_result(conn)
+                        .await?
+                }
+            };
+
+            insert_into(user_identities::table)
+                .values(&NewUserIdentity {
+                    user_id: user.id,
+                    provider: provider.into(),
+                    subject: identity.subject.clone(),
+                    username: identity.username.clone(),
+                    email: identity.email.clone(),
+                })
+                .execute(conn)
+                .await?;
+
+            (user, returning)
+        }
+    };
+
+    let Some(github_id) = identity.github_id else {
+        return Ok(user);
+    };
+
+    // Logging in with GitHub keeps the username in sync with GitHub, like it always has.
+    // Linking GitHub to an existing user doesn't rename them.
+    let username = if returning {
+        unique_username(&identity.username, Some(user.id), conn).await?
+    } else {
+        user.username
+    };
+
+    Ok(update(users::table)
+        .filter(users::id.eq(user.id))
+        .set((users::github_id.eq(github_id), users::username.eq(username)))
+        .returning(User::as_returning())
+        .get_result(conn)
+        .await?)
 }
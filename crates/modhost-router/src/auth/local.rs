@@ -0,0 +1,142 @@
+//! The routes for local username and password accounts.
+
+use crate::util::session::{client_ip, session_cookie, user_agent};
+use axum::{
+    Json,
+    extract::{ConnectInfo, State},
+    http::{HeaderMap, header::SET_COOKIE},
+    response::{IntoResponse, Response},
+};
+use axum_extra::extract::Host;
+use modhost_auth::local::{authenticate, check_password, create_local_user};
+use modhost_core::{AppError, Result};
+use modhost_db::{User, create_token};
+use modhost_server_core::state::AppState;
+use std::net::SocketAddr;
+
+/// The data for logging in to (or registering) a local account.
+#[derive(
+    Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, ToSchema, ToResponse, Serialize, Deserialize,
+)]
+pub struct LocalCredentials {
+    /// The account's username.
+    pub username: String,
+
+    /// The account's password.
+    pub password: String,
+}
+
+/// A successful local login.
+#[derive(
+    Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, ToSchema, ToResponse, Serialize, Deserialize,
+)]
+pub struct LocalLogin {
+    /// The user that logged in.
+    pub user: User,
+
+    /// The new session token. This is also set as the `auth-token` cookie.
+    pub token: String,
+}
+
+/// Local Login
+///
+/// Log in to a local account with its username and password.
+/// Local accounts are only available when they're enabled in the config.
+#[utoipa::path(
+    post,
+    path = "/local/login",
+    tag = "Auth",
+    request_body(content = LocalCredentials, description = "The account's credentials"),
+    responses(
+        (status = 200, description = "Logged in!", body = LocalLogin),
+        (status = 401, description = "The username or password was wrong!"),
+        (status = 404, description = "Local accounts aren't enabled!"),
+    ),
+)]
+#[debug_handler]
+pub async fn login_handler(
+    State(state): State<AppState>,
+    Host(host): Host,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Json(body): Json<LocalCredentials>,
+) -> Result<Response> {
+    state.auth.local().ok_or(AppError::NotFound)?;
+
+    let mut conn = state.pool.get().await?;
+    let user = authenticate(&body.username, &body.password, &mut conn).await?;
+
+    start_session(&state, user, &host, &headers, addr).await
+}
+
+/// Local Register
+///
+/// Create a local account and log in to it.
+/// This only works if registration is enabled in the config.
+#[utoipa::path(
+    post,
+    path = "/local/register",
+    tag = "Auth",
+    request_body(content = LocalCredentials, description = "The new account's credentials"),
+    responses(
+        (status = 200, description = "Registered and logged in!", body = LocalLogin),
+        (status = 400, description = "The username or password wasn't allowed!"),
+        (status = 403, description = "Registration is disabled!"),
+        (status = 404, description = "Local accounts aren't enabled!"),
+        (status = 409, description = "The username is already taken!"),
+    ),
+)]
+#[debug_handler]
+pub async fn register_handler(
+    State(state): State<AppState>,
+    Host(host): Host,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Json(body): Json<LocalCredentials>,
+) -> Result<Response> {
+    let config = state.auth.local().ok_or(AppError::NotFound)?;
+
+    if !config.allow_registration {
+        return Err(AppError::RegistrationDisabled);
+    }
+
+    check_password(&body.password, config)?;
+
+    let mut conn = state.pool.get().await?;
+    let user = create_local_user(&body.username, &body.password, &mut conn).await?;
+
+    start_session(&state, user, &host, &headers, addr).await
+}
+
+/// Create a session for a user that just logged in.
+async fn start_session(
+    state: &AppState,
+    user: User,
+    host: &str,
+    headers: &HeaderMap,
+    addr: SocketAddr,
+) -> Result<Response> {
+    let token = create_token(
+        user.id,
+        user_agent(headers),
+        Some(client_ip(
+            headers,
+            addr,
+            &state.config.server.trusted_proxies,
+        )),
+        &state.pool,
+    )
+    .await?;
+
+    let mut response = Json(LocalLogin {
+        user,
+        token: token.value.clone(),
+    })
+    .into_response();
+
+    response
+        .headers_mut()
+        .insert(SET_COOKIE, session_cookie(&token.value, host)?);
+
+    Ok(response)
+}
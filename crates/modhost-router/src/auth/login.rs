@@ -1,76 +1,112 @@
-//! The route to initiate the GitHub login flow.
+//! The route to initiate the login flow.
 
-use std::collections::HashMap;
-
-use super::CALLBACK_URL;
+use super::callback_url;
+use crate::util::{
+    page::error_page,
+    session::{login_cookie, resolve_redirect},
+};
 use axum::{
     body::Body,
-    extract::State,
-    http::{HeaderValue, StatusCode, Uri, header::LOCATION},
+    extract::{Path, Query, State},
+    http::{
+        HeaderValue, StatusCode,
+        header::{LOCATION, SET_COOKIE},
+    },
     response::Response,
 };
 use axum_extra::extract::Host;
-use modhost_core::Result;
+use modhost_auth::login::LoginState;
+use modhost_core::{AppError, Result};
 use modhost_middleware::scheme::Scheme;
 use modhost_server_core::state::AppState;
-use oauth2::{CsrfToken, RedirectUrl, Scope};
+use oauth2::{CsrfToken, PkceCodeChallenge, RedirectUrl, Scope};
+
+/// Query params for the login route.
+#[derive(
+    Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, ToSchema, ToResponse, Serialize, Deserialize,
+)]
+pub struct LoginQueryParams {
+    /// Where to redirect to after logging in.
+    pub redirect_uri: Option<String>,
+}
 
-/// GitHub Auth Login
+/// Auth Login
 ///
-/// Initiate the GitHub login flow.
+/// Initiate the login flow with a provider (like `github`, `gitlab`, `discord`, or `oidc`).
+/// See the providers route for the ones that are enabled.
+/// The `redirect_uri` must be on this server or one of the origins in `auth.redirect_allowlist`.
 #[utoipa::path(
     get,
-    path = "/github/login",
+    path = "/{provider}/login",
     tag = "Auth",
     params(
+        ("provider" = String, Path, description = "The provider's ID."),
         ("redirect_uri" = Option<String>, Query, description = "An optional custom URL to redirect to."),
     ),
     responses(
-        (status = 307, description = "Redirecting to GitHub for login"),
+        (status = 307, description = "Redirecting to the provider for login"),
+        (status = 400, description = "The redirect URL isn't allowed!"),
+        (status = 404, description = "The provider isn't enabled!"),
     ),
 )]
 #[debug_handler]
 pub async fn login_handler(
+    Path(provider): Path<String>,
     State(state): State<AppState>,
     Host(host): Host,
     Scheme(scheme): Scheme,
-    url: Uri,
+    Query(query): Query<LoginQueryParams>,
+) -> Response {
+    begin_login(provider, state, host, scheme, query)
+        .await
+        .unwrap_or_else(error_page)
+}
+
+async fn begin_login(
+    provider: String,
+    state: AppState,
+    host: String,
+    scheme: String,
+    query: LoginQueryParams,
 ) -> Result<Response> {
-    let query = url::form_urlencoded::parse(url.query().unwrap_or_default().as_bytes())
-        .into_owned()
-        .collect::<HashMap<String, String>>();
+    let provider = state.auth.get(&provider).ok_or(AppError::NotFound)?;
+    let origin = format!("{}://{}", scheme, host);
 
-    let callback_url_base = query
-        .get("redirect_uri")
-        .cloned()
-        .unwrap_or("/".to_string());
+    let to = query
+        .redirect_uri
+        .filter(|v| !v.is_empty())
+        .map(|to| resolve_redirect(to, &origin, &state.config.auth.redirect_allowlist))
+        .transpose()?;
 
-    let callback_url = format!(
-        "{}://{}{}?to={}",
-        scheme, host, CALLBACK_URL, callback_url_base
-    );
+    let login = LoginState::new(provider.id(), to.map(|v| v.to_string()));
+    let signed = login.sign(state.auth.state_secret())?;
+    let (challenge, verifier) = PkceCodeChallenge::new_random_sha256();
 
-    let client = state
-        .auth
-        .set_redirect_uri(RedirectUrl::new(callback_url).unwrap());
+    let client = provider
+        .client()
+        .await?
+        .set_redirect_uri(RedirectUrl::new(format!(
+            "{}{}",
+            origin,
+            callback_url(provider.id())
+        ))?);
 
     let (mut authorize_url, _) = client
-        .authorize_url(CsrfToken::new_random)
-        .add_scope(Scope::new("user:email".to_string()))
-        .add_scope(Scope::new("read:user".to_string()))
-        .add_scope(Scope::new("public_repo".to_string()))
+        .authorize_url(|| CsrfToken::new(signed))
+        .add_scopes(provider.scopes().into_iter().map(Scope::new))
+        .set_pkce_challenge(challenge)
         .url();
 
     authorize_url
         .query_pairs_mut()
         .append_pair("prompt", "consent");
 
-    let mut resp = Response::new(Body::empty());
-
-    resp.headers_mut()
-        .insert(LOCATION, HeaderValue::from_str(authorize_url.as_str())?);
-
-    *resp.status_mut() = StatusCode::TEMPORARY_REDIRECT;
-
-    Ok(resp)
+    Ok(Response::builder()
+        .status(StatusCode::TEMPORARY_REDIRECT)
+        .header(LOCATION, HeaderValue::from_str(authorize_url.as_str())?)
+        .header(
+            SET_COOKIE,
+            login_cookie(&login.nonce, verifier.secret(), scheme == "https")?,
+        )
+        .body(Body::empty())?)
 }
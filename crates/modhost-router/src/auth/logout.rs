@@ -0,0 +1,82 @@
+//! The routes for logging out.
+
+use crate::util::session::clear_session_cookie;
+use axum::{
+    body::Body,
+    extract::State,
+    http::{HeaderMap, header::SET_COOKIE},
+    response::Response,
+};
+use axum_extra::extract::{CookieJar, Host};
+use modhost_auth::{get_session_from_req, get_user_from_req};
+use modhost_core::{AppError, Result};
+use modhost_db::{TokenScope, revoke_session, revoke_sessions};
+use modhost_server_core::state::AppState;
+
+/// Log Out
+///
+/// End the current session. The session's token stops working immediately.
+/// Personal access tokens can't log out, and should be revoked instead.
+#[utoipa::path(
+    post,
+    path = "/logout",
+    tag = "Auth",
+    responses(
+        (status = 200, description = "Logged out!", body = String),
+        (status = 403, description = "The request wasn't made with a session token!"),
+    ),
+    security(
+        ("api_auth_token" = []),
+    ),
+)]
+#[debug_handler]
+pub async fn logout_handler(
+    State(state): State<AppState>,
+    Host(host): Host,
+    jar: CookieJar,
+    headers: HeaderMap,
+) -> Result<Response> {
+    let mut conn = state.pool.get().await?;
+
+    let session = get_session_from_req(&jar, &headers, &mut conn)
+        .await?
+        .ok_or(AppError::InvalidToken)?;
+
+    revoke_session(session.user_id, session.id, &mut conn).await?;
+
+    Ok(Response::builder()
+        .header(SET_COOKIE, clear_session_cookie(&host)?)
+        .body(Body::new("Logged out successfully!".to_string()))?)
+}
+
+/// Log Out Everywhere
+///
+/// End all of the current user's sessions, on every device.
+/// This doesn't revoke personal access tokens.
+#[utoipa::path(
+    post,
+    path = "/logout/all",
+    tag = "Auth",
+    responses(
+        (status = 200, description = "Logged out everywhere!", body = String),
+        (status = INTERNAL_SERVER_ERROR, description = "An internal error occured! Are you authenticated?"),
+    ),
+    security(
+        ("api_auth_token" = []),
+    ),
+)]
+#[debug_handler]
+pub async fn logout_all_handler(
+    State(state): State<AppState>,
+    Host(host): Host,
+    jar: CookieJar,
+    headers: HeaderMap,
+) -> Result<Response> {
+    let mut conn = state.pool.get().await?;
+    let user = get_user_from_req(&jar, &headers, TokenScope::Account, &mut conn).await?;
+    let count = revoke_sessions(user.id, &mut conn).await?;
+
+    Ok(Response::builder()
+        .header(SET_COOKIE, clear_session_cookie(&host)?)
+        .body(Body::new(format!("Logged out of {} session(s)!", count)))?)
+}
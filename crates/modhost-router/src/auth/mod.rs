@@ -4,25 +4,46 @@
 //! (From the incredible post: https://medium.com/@abrar.nitk/rust-authentication-with-github-oauth-3c581fa274a1)
 
 pub mod callback;
+pub mod local;
 pub mod login;
+pub mod logout;
+pub mod providers;
 
-use axum::{Router, routing::get};
+use axum::{
+    Router,
+    routing::{get, post},
+};
 use modhost_server_core::state::AppState;
 
-/// The relative URL for the GitHub auth callback.
-pub const CALLBACK_URL: &str = "/api/v1/auth/github/callback";
+/// Get the relative URL for a provider's auth callback.
+pub fn callback_url(provider: &str) -> String {
+    format!("/api/v1/auth/{}/callback", provider)
+}
 
 /// Register auth-related routes.
 /// Should be nested at `/api/v1/auth`.
 pub fn router(state: AppState) -> Router<AppState> {
     Router::new()
-        .route("/github/login", get(login::login_handler))
-        .route("/github/callback", get(callback::callback_handler))
+        .route("/providers", get(providers::providers_handler))
+        .route("/local/login", post(local::login_handler))
+        .route("/local/register", post(local::register_handler))
+        .route("/logout", post(logout::logout_handler))
+        .route("/logout/all", post(logout::logout_all_handler))
+        .route("/{provider}/login", get(login::login_handler))
+        .route("/{provider}/callback", get(callback::callback_handler))
         .with_state(state)
 }
 
 /// The spec for the auth API.
 /// Should be nested at `/api/v1/auth`.
 #[derive(OpenApi)]
-#[openapi(paths(login::login_handler, callback::callback_handler,))]
+#[openapi(paths(
+    providers::providers_handler,
+    local::login_handler,
+    local::register_handler,
+    logout::logout_handler,
+    logout::logout_all_handler,
+    login::login_handler,
+    callback::callback_handler,
+))]
 pub struct AuthApi;
@@ -0,0 +1,42 @@
+//! The route to list the available login providers.
+
+use axum::{Json, extract::State};
+use modhost_core::Result;
+use modhost_server_core::state::AppState;
+
+/// A provider users can log in with.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema, ToResponse)]
+pub struct AuthProviderInfo {
+    /// The provider's ID, used in its login URL (`/api/v1/auth/{id}/login`).
+    pub id: String,
+
+    /// The provider's display name.
+    pub name: String,
+}
+
+/// List Auth Providers
+///
+/// List the providers users can log in with.
+#[utoipa::path(
+    get,
+    path = "/providers",
+    tag = "Auth",
+    responses(
+        (status = 200, description = "Got the providers!", body = Vec<AuthProviderInfo>),
+    ),
+)]
+#[debug_handler]
+pub async fn providers_handler(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<AuthProviderInfo>>> {
+    Ok(Json(
+        state
+            .auth
+            .iter()
+            .map(|v| AuthProviderInfo {
+                id: v.id().into(),
+                name: v.name().into(),
+            })
+            .collect(),
+    ))
+}
@@ -0,0 +1,89 @@
+//! The file lookup route.
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::HeaderMap,
+};
+use axum_extra::extract::CookieJar;
+use diesel::{QueryDsl, SelectableHelper};
+use diesel_async::RunQueryDsl;
+use modhost_auth::get_user_from_req;
+use modhost_core::{AppError, Result};
+use modhost_db::{
+    ProjectData, ProjectFile, ProjectVersion, ProjectVisibility, TokenScope, project_versions,
+};
+use modhost_db_util::{files::find_files_by_hash, projects::get_full_project};
+use modhost_server_core::state::AppState;
+
+/// A version file, along with the project and version it belongs to.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, ToResponse)]
+pub struct FileLookup {
+    /// The project the file belongs to.
+    pub project: ProjectData,
+
+    /// The version the file belongs to.
+    pub version: ProjectVersion,
+
+    /// The file itself.
+    pub file: ProjectFile,
+}
+
+/// Get File By Hash
+///
+/// Find a version file by its hash, along with its project and version.
+/// If multiple files have the same hash, the oldest one is returned.
+#[utoipa::path(
+    get,
+    path = "/{algorithm}/{hash}",
+    tag = "Versions",
+    responses(
+        (status = 200, description = "Found the file!", body = FileLookup),
+        (status = 400, description = "Unknown hash algorithm!"),
+        (status = 404, description = "No file with that hash exists!"),
+        (status = INTERNAL_SERVER_ERROR, description = "An internal error occured!"),
+    ),
+    params(
+        ("algorithm" = String, Path, description = "The hash algorithm (`sha1`, `sha256`, or `sha512`)."),
+        ("hash" = String, Path, description = "The file's hash, as a hex string."),
+    ),
+)]
+#[debug_handler]
+pub async fn lookup_handler(
+    jar: CookieJar,
+    headers: HeaderMap,
+    Path((algorithm, hash)): Path<(String, String)>,
+    State(state): State<AppState>,
+) -> Result<Json<FileLookup>> {
+    let mut conn = state.pool.get().await?;
+    let files = find_files_by_hash(algorithm, hash, &mut conn).await?;
+    let user = get_user_from_req(&jar, &headers, TokenScope::Read, &mut conn)
+        .await
+        .ok();
+
+    for file in files {
+        let version = project_versions::table
+            .find(file.version_id)
+            .select(ProjectVersion::as_select())
+            .get_result(&mut conn)
+            .await?;
+
+        let project = get_full_project(version.project.to_string(), &mut conn).await?;
+
+        if project.visibility == ProjectVisibility::Private
+            && !user
+                .as_ref()
+                .is_some_and(|user| user.admin || project.authors.iter().any(|v| v.id == user.id))
+        {
+            continue;
+        }
+
+        return Ok(Json(FileLookup {
+            project,
+            version,
+            file,
+        }));
+    }
+
+    Err(AppError::NotFound)
+}
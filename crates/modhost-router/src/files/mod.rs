@@ -0,0 +1,25 @@
+//! Routes concerning version files.
+
+use axum::{
+    Router,
+    routing::{get, post},
+};
+use modhost_server_core::state::AppState;
+
+pub mod lookup;
+pub mod updates;
+
+/// Register version file API routes.
+/// Should be nested at `/api/v1/files`.
+pub fn router(state: AppState) -> Router<AppState> {
+    Router::new()
+        .route("/updates", post(updates::updates_handler))
+        .route("/{algorithm}/{hash}", get(lookup::lookup_handler))
+        .with_state(state)
+}
+
+/// The spec for the version files API.
+/// Should be nested at `/api/v1/files`.
+#[derive(OpenApi)]
+#[openapi(paths(lookup::lookup_handler, updates::updates_handler))]
+pub struct FilesApi;
@@ -0,0 +1,172 @@
+//! The bulk update check route.
+
+use crate::util::access::{can_see_blocked, get_visible_project};
+use axum::{Json, extract::State, http::HeaderMap};
+use axum_extra::extract::CookieJar;
+use diesel::{ExpressionMethods, QueryDsl, SelectableHelper};
+use diesel_async::RunQueryDsl;
+use modhost_auth::get_user_from_req;
+use modhost_core::{AppError, Result};
+use modhost_db::{
+    ProjectVersion, ProjectVersionData, TokenScope, VersionChannel, project_versions,
+};
+use modhost_db_util::{files::find_files_by_hashes, vers::get_latest_compatible_version};
+use modhost_server_core::state::AppState;
+use std::collections::{HashMap, hash_map::Entry};
+
+/// The maximum number of hashes that can be checked in one request.
+pub const MAX_UPDATE_HASHES: usize = 1000;
+
+/// A request to check for updates to a set of files.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema, ToResponse)]
+pub struct UpdateCheckRequest {
+    /// The hash algorithm used for the hashes (`sha1`, `sha256`, or `sha512`).
+    pub algorithm: String,
+
+    /// The hashes of the installed files, in hex. Case doesn't matter.
+    /// At most 1000 hashes can be checked at once.
+    pub hashes: Vec<String>,
+
+    /// The loader that updates must support.
+    pub loader: Option<String>,
+
+    /// The game version that updates must support.
+    pub game_version: Option<String>,
+
+    /// The release channels that updates can be from.
+    /// Defaults to the installed version's channel and any more stable ones,
+    /// so a file from a release is only offered releases.
+    pub channels: Option<Vec<VersionChannel>>,
+}
+
+/// Check For Updates
+///
+/// Find the newest compatible version of the project each file belongs to.
+/// The response maps each known hash to that version, using the hash exactly as it was sent.
+/// Hashes that don't match any file, or that have no compatible version, are left out.
+/// Unless channels are given, updates are only offered from the installed version's
+/// channel or more stable ones.
+/// Versions blocked by a scan rule are skipped, except for moderators.
+#[utoipa::path(
+    post,
+    path = "/updates",
+    tag = "Versions",
+    request_body(content = UpdateCheckRequest, description = "The files to check"),
+    responses(
+        (status = 200, description = "The latest versions, keyed by hash.", body = HashMap<String, ProjectVersionData>),
+        (status = 400, description = "Unknown hash algorithm, or too many hashes!"),
+        (status = INTERNAL_SERVER_ERROR, description = "An internal error occured!"),
+    ),
+)]
+#[debug_handler]
+pub async fn updates_handler(
+    jar: CookieJar,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Json(req): Json<UpdateCheckRequest>,
+) -> Result<Json<HashMap<String, ProjectVersionData>>> {
+    let mut conn = state.pool.get().await?;
+    let user = get_user_from_req(&jar, &headers, TokenScope::Read, &mut conn)
+        .await
+        .ok();
+    if req.hashes.len() > MAX_UPDATE_HASHES {
+        return Err(AppError::TooManyHashes(MAX_UPDATE_HASHES));
+    }
+
+    // Hashes are stored in lowercase, but the response uses the hashes that were sent.
+    let mut submitted = HashMap::<String, Vec<&String>>::new();
+
+    for hash in &req.hashes {
+        submitted.entry(hash.to_lowercase()).or_default().push(hash);
+    }
+
+    let files = find_files_by_hashes(&req.algorithm, &req.hashes, &mut conn).await?;
+
+    let versions = project_versions::table
+        .filter(project_versions::id.eq_any(files.iter().map(|v| v.version_id)))
+        .select(ProjectVersion::as_select())
+        .load(&mut conn)
+        .await?
+        .into_iter()
+        .map(|v| (v.id, (v.project, v.channel)))
+        .collect::<HashMap<_, _>>();
+
+    let mut latest = HashMap::<(i32, Vec<VersionChannel>), Option<ProjectVersionData>>::new();
+    let mut output = HashMap::new();
+
+    for file in files {
+        let hash = match req.algorithm.as_str() {
+            "sha256" => file.sha256.clone(),
+            "sha512" => file.sha512.clone(),
+            _ => Some(file.sha1.clone()),
+        };
+
+        let (Some(hash), Some((project, installed))) =
+            (hash, versions.get(&file.version_id).copied())
+        else {
+            continue;
+        };
+
+        // Hashes are only used up once a version is found for them, since a later
+        // file with the same hash may be in a project that the user can see.
+        if !submitted.contains_key(&hash) {
+            continue;
+        }
+
+        let channels = match &req.channels {
+            Some(channels) => channels.clone(),
+
+            None => [
+                VersionChannel::Release,
+                VersionChannel::Beta,
+                VersionChannel::Alpha,
+            ]
+            .into_iter()
+            .filter(|v| *v <= installed)
+            .collect(),
+        };
+
+        let key = (project, channels);
+
+        if let Entry::Vacant(entry) = latest.entry(key.clone()) {
+            let visible =
+                match get_visible_project(project.to_string(), user.as_ref(), &mut conn).await {
+                    Ok(_) => true,
+                    Err(AppError::NotFound) => false,
+                    Err(err) => return Err(err),
+                };
+
+            let version = if visible {
+                match get_latest_compatible_version(
+                    project,
+                    req.loader.as_deref(),
+                    req.game_version.as_deref(),
+                    &key.1,
+                    can_see_blocked(user.as_ref()),
+                    state.config.server.version_scheme,
+                    &mut conn,
+                )
+                .await
+                {
+                    Ok(version) => Some(version),
+                    Err(AppError::NoVersions) => None,
+                    Err(err) => return Err(err),
+                }
+            } else {
+                None
+            };
+
+            entry.insert(version);
+        }
+
+        if let Some(Some(version)) = latest.get(&key)
+            && let Some(keys) = submitted.remove(&hash)
+        {
+            for sent in keys {
+                output.insert(sent.clone(), version.clone());
+            }
+        }
+    }
+
+    Ok(Json(output))
+}
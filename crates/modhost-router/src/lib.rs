@@ -16,10 +16,12 @@ extern crate utoipa;
 pub mod admin;
 pub mod api;
 pub mod auth;
+pub mod files;
 pub mod meta;
 pub mod moderation;
 pub mod openapi;
 pub mod projects;
+pub mod resolve;
 pub mod users;
 pub mod util;
 
@@ -42,6 +44,8 @@ pub fn create_router(spec: &OpenApi, state: AppState, glue: Glue) -> Router {
         .nest("/api/v1/auth", auth::router(state.clone()))
         .nest("/api/v1/users", users::router(state.clone()))
         .nest("/api/v1/projects", projects::router(state.clone()))
+        .nest("/api/v1/files", files::router(state.clone()))
+        .nest("/api/v1/resolve", resolve::router(state.clone()))
         .nest("/api/v1/meta", meta::router(state.clone()))
         .nest("/api/v1/moderation", moderation::router(state.clone()))
         .nest("/api/v1/admin", admin::router(state.clone()))
@@ -52,12 +56,48 @@ pub fn create_router(spec: &OpenApi, state: AppState, glue: Glue) -> Router {
 }
 
 modhost_core::utoipa_types![
+    auth::providers::AuthProviderInfo,
+    auth::login::LoginQueryParams,
+    auth::callback::CallbackQueryParams,
+    auth::local::LocalCredentials,
+    auth::local::LocalLogin,
     api::JsonQueryParams,
     projects::search::SearchQuery,
     projects::update::PartialProject,
+    projects::delete::DeleteProjectQueryParams,
     projects::versions::update::PartialProjectVersion,
+    projects::versions::files::upload::VersionFileUpload,
+    projects::versions::relations::create::VersionRelationInit,
+    projects::versions::files::tree::ArchiveEntryPreview,
     projects::gallery::create::GalleryImageUpload,
     projects::gallery::update::PartialGalleryImage,
     util::stats::AdminStats,
     admin::stats_ws::AdminStatsSocketQueryParams,
+    admin::gc::GcQueryParams,
+    admin::metadata::UnknownMetadataReport,
+    admin::metadata::UnknownVersionMetadata,
+    admin::metadata::UnknownProjectMetadata,
+    meta::badge::LatestBadgeQueryParams,
+    projects::versions::latest::LatestVersionQueryParams,
+    projects::versions::diff::VersionDiffQueryParams,
+    projects::versions::diff::VersionDiff,
+    util::diff::EntryChange,
+    util::diff::EntryChangeKind,
+    files::lookup::FileLookup,
+    files::updates::UpdateCheckRequest,
+    resolve::deps::ResolveTarget,
+    resolve::deps::ResolveRequest,
+    resolve::deps::ResolvedVersion,
+    resolve::deps::ResolveConflict,
+    util::duplicates::DuplicateFile,
+    util::duplicates::DuplicateGroup,
+    util::gc::GcReport,
+    util::gc::OrphanedObject,
+    util::gc::MissingObject,
+    util::metadata::MetadataQueryParams,
+    moderation::rules::ScanRuleInit,
+    moderation::rules::PartialScanRule,
+    users::sessions::UserSession,
+    users::tokens::PersonalAccessTokenInit,
+    users::tokens::CreatedPersonalAccessToken,
 ];
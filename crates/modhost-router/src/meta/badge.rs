@@ -1,13 +1,27 @@
 //! Meta endpoints for badges.
 
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     response::Response,
 };
 use modhost_core::Result;
-use modhost_db_util::{projects::get_project, vers::get_latest_version};
+use modhost_db::VersionChannel;
+use modhost_db_util::{projects::get_project, vers::get_latest_compatible_version};
 use modhost_server_core::state::AppState;
 
+/// Query params for the latest version badge route.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, ToSchema, ToResponse, Serialize, Deserialize,
+)]
+pub struct LatestBadgeQueryParams {
+    /// Only consider versions in this release channel.
+    pub channel: Option<VersionChannel>,
+
+    /// Only consider pre-release versions (if `true`) or stable versions (if `false`).
+    /// This is ignored if `channel` is set.
+    pub prerelease: Option<bool>,
+}
+
 /// Version Badge
 ///
 /// Get a badge for a specific version of a project.
@@ -45,12 +59,15 @@ pub async fn version_handler(
 /// Latest Version Badge
 ///
 /// Get a badge for the latest version of a project.
+/// Use the `prerelease` param to show the latest stable or pre-release version.
 #[utoipa::path(
     get,
     path = "/badge/latest/{project}",
     tag = "Meta",
     params(
         ("project" = String, description = "The project."),
+        ("channel" = Option<VersionChannel>, Query, description = "Only consider versions in this release channel."),
+        ("prerelease" = Option<bool>, Query, description = "Only consider pre-release (true) or stable (false) versions."),
     ),
     responses(
         (status = 200, description = "Created a badge!", body = String),
@@ -61,10 +78,31 @@ pub async fn version_handler(
 pub async fn latest_version_badge_handler(
     State(state): State<AppState>,
     Path(project): Path<String>,
+    Query(LatestBadgeQueryParams {
+        channel,
+        prerelease,
+    }): Query<LatestBadgeQueryParams>,
 ) -> Result<Response> {
     let mut conn = state.pool.get().await?;
     let pkg = get_project(project, &mut conn).await?;
-    let ver = get_latest_version(pkg.id, &mut conn).await?;
+
+    let channels = match (channel, prerelease) {
+        (Some(channel), _) => vec![channel],
+        (None, Some(true)) => vec![VersionChannel::Beta, VersionChannel::Alpha],
+        (None, Some(false)) => vec![VersionChannel::Release],
+        (None, None) => Vec::new(),
+    };
+
+    let ver = get_latest_compatible_version(
+        pkg.id,
+        None,
+        None,
+        &channels,
+        false,
+        state.config.server.version_scheme,
+        &mut conn,
+    )
+    .await?;
 
     let data = format!(
         include_str!("../assets/badges/version.svg"),
@@ -1,9 +1,14 @@
 //! ModHost's moderation routes.
 
-use axum::{Router, routing::get};
+use axum::{
+    Router,
+    routing::{get, patch, post},
+};
 use modhost_server_core::state::AppState;
 
 pub mod queue;
+pub mod rules;
+pub mod versions;
 
 /// Register moderation-related routes onto the router.
 /// This should be nested at `/api/v1/moderation`.
@@ -14,6 +19,12 @@ pub fn router(state: AppState) -> Router<AppState> {
         .route("/queue/approved", get(queue::list_queue_approved))
         .route("/queue/under_review", get(queue::list_queue_under_review))
         .route("/queue/denied", get(queue::list_queue_denied))
+        .route("/rules", get(rules::list_rules).put(rules::create_rule))
+        .route(
+            "/rules/{rule}",
+            patch(rules::update_rule).delete(rules::delete_rule),
+        )
+        .route("/versions/{version}/clear", post(versions::clear_version))
         .with_state(state)
 }
 
@@ -26,5 +37,10 @@ pub fn router(state: AppState) -> Router<AppState> {
     queue::list_queue_approved,
     queue::list_queue_under_review,
     queue::list_queue_denied,
+    rules::list_rules,
+    rules::create_rule,
+    rules::update_rule,
+    rules::delete_rule,
+    versions::clear_version,
 ))]
 pub struct ModerationApi;
@@ -4,7 +4,7 @@ use axum::{Json, extract::State, http::HeaderMap};
 use axum_extra::extract::CookieJar;
 use modhost_auth::get_user_from_req;
 use modhost_core::{AppError, Result};
-use modhost_db::ModerationQueueItem;
+use modhost_db::{ModerationQueueItem, TokenScope};
 use modhost_db_util::moderation::{
     get_approved_moderation_queue, get_denied_moderation_queue, get_moderation_queue,
     get_pending_moderation_queue, get_under_review_moderation_queue,
@@ -33,7 +33,7 @@ pub async fn list_queue(
     State(state): State<AppState>,
 ) -> Result<Json<Vec<ModerationQueueItem>>> {
     let mut conn = state.pool.get().await?;
-    let user = get_user_from_req(&jar, &headers, &mut conn).await?;
+    let user = get_user_from_req(&jar, &headers, TokenScope::Moderation, &mut conn).await?;
 
     if !user.admin || !user.moderator {
         return Err(AppError::NoAccess);
@@ -64,7 +64,7 @@ pub async fn list_queue_pending(
     State(state): State<AppState>,
 ) -> Result<Json<Vec<ModerationQueueItem>>> {
     let mut conn = state.pool.get().await?;
-    let user = get_user_from_req(&jar, &headers, &mut conn).await?;
+    let user = get_user_from_req(&jar, &headers, TokenScope::Moderation, &mut conn).await?;
 
     if !user.admin || !user.moderator {
         return Err(AppError::NoAccess);
@@ -95,7 +95,7 @@ pub async fn list_queue_approved(
     State(state): State<AppState>,
 ) -> Result<Json<Vec<ModerationQueueItem>>> {
     let mut conn = state.pool.get().await?;
-    let user = get_user_from_req(&jar, &headers, &mut conn).await?;
+    let user = get_user_from_req(&jar, &headers, TokenScope::Moderation, &mut conn).await?;
 
     if !user.admin || !user.moderator {
         return Err(AppError::NoAccess);
@@ -126,7 +126,7 @@ pub async fn list_queue_under_review(
     State(state): State<AppState>,
 ) -> Result<Json<Vec<ModerationQueueItem>>> {
     let mut conn = state.pool.get().await?;
-    let user = get_user_from_req(&jar, &headers, &mut conn).await?;
+    let user = get_user_from_req(&jar, &headers, TokenScope::Moderation, &mut conn).await?;
 
     if !user.admin || !user.moderator {
         return Err(AppError::NoAccess);
@@ -157,7 +157,7 @@ pub async fn list_queue_denied(
     State(state): State<AppState>,
 ) -> Result<Json<Vec<ModerationQueueItem>>> {
     let mut conn = state.pool.get().await?;
-    let user = get_user_from_req(&jar, &headers, &mut conn).await?;
+    let user = get_user_from_req(&jar, &headers, TokenScope::Moderation, &mut conn).await?;
 
     if !user.admin || !user.moderator {
         return Err(AppError::NoAccess);
@@ -0,0 +1,262 @@
+//! Scan rule routes.
+
+use crate::util::scan::validate_rule;
+use axum::{
+    Json,
+    body::Body,
+    extract::{Path, State},
+    http::HeaderMap,
+    response::Response,
+};
+use axum_extra::extract::CookieJar;
+use chrono::Utc;
+use diesel::{ExpressionMethods, OptionalExtension, QueryDsl, SelectableHelper, insert_into};
+use diesel_async::RunQueryDsl;
+use modhost_auth::get_user_from_req;
+use modhost_core::{AppError, Result};
+use modhost_db::{DbConn, NewScanRule, ScanRule, ScanRuleKind, TokenScope, User, scan_rules};
+use modhost_db_util::moderation::get_scan_rules;
+use modhost_server_core::state::AppState;
+
+/// The data for creating a scan rule.
+#[derive(
+    Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, ToSchema, ToResponse, Serialize, Deserialize,
+)]
+pub struct ScanRuleInit {
+    /// The rule's name.
+    pub name: String,
+
+    /// A description of what the rule detects.
+    #[serde(default)]
+    pub description: Option<String>,
+
+    /// The kind of pattern.
+    pub kind: ScanRuleKind,
+
+    /// The pattern itself.
+    /// Byte sequences and hashes are written as hex.
+    pub pattern: String,
+
+    /// Whether uploads are checked against this rule.
+    /// Defaults to `true`.
+    #[serde(default)]
+    pub enabled: Option<bool>,
+}
+
+/// The data for updating a scan rule.
+#[derive(
+    Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, ToSchema, ToResponse, Serialize, Deserialize,
+)]
+pub struct PartialScanRule {
+    /// The rule's name.
+    #[serde(default)]
+    pub name: Option<String>,
+
+    /// A description of what the rule detects.
+    #[serde(default)]
+    pub description: Option<String>,
+
+    /// The kind of pattern.
+    #[serde(default)]
+    pub kind: Option<ScanRuleKind>,
+
+    /// The pattern itself.
+    #[serde(default)]
+    pub pattern: Option<String>,
+
+    /// Whether uploads are checked against this rule.
+    #[serde(default)]
+    pub enabled: Option<bool>,
+}
+
+/// Make sure a user is a moderator.
+fn check_moderator(user: &User) -> Result<()> {
+    if !user.admin && !user.moderator {
+        return Err(AppError::NoAccess);
+    }
+
+    Ok(())
+}
+
+/// Get a scan rule by its ID.
+async fn get_rule(id: i32, conn: &mut DbConn) -> Result<ScanRule> {
+    scan_rules::table
+        .find(id)
+        .select(ScanRule::as_select())
+        .first(conn)
+        .await
+        .optional()?
+        .ok_or(AppError::NotFound)
+}
+
+/// List Scan Rules
+///
+/// List the rules uploaded files are checked against.
+#[utoipa::path(
+    get,
+    path = "/rules",
+    tag = "Moderation",
+    responses(
+        (status = 200, description = "Fetched the rules!", body = Vec<ScanRule>),
+        (status = INTERNAL_SERVER_ERROR, description = "An internal error occured!"),
+    ),
+    security(
+        ("api_auth_token" = []),
+    ),
+)]
+#[debug_handler]
+pub async fn list_rules(
+    jar: CookieJar,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<ScanRule>>> {
+    let mut conn = state.pool.get().await?;
+    let user = get_user_from_req(&jar, &headers, TokenScope::Moderation, &mut conn).await?;
+
+    check_moderator(&user)?;
+
+    Ok(Json(get_scan_rules(&mut conn).await?))
+}
+
+/// Create Scan Rule
+///
+/// Create a rule that uploaded files (and every file inside uploaded archives)
+/// are checked against. Versions with matching files are blocked from being
+/// downloaded until a moderator clears them.
+#[utoipa::path(
+    put,
+    path = "/rules",
+    tag = "Moderation",
+    responses(
+        (status = 200, description = "Created the rule!", body = ScanRule),
+        (status = 400, description = "The pattern is invalid!"),
+        (status = INTERNAL_SERVER_ERROR, description = "An internal error occured!"),
+    ),
+    request_body(content = ScanRuleInit, description = "The rule to create"),
+    security(
+        ("api_auth_token" = []),
+    ),
+)]
+#[debug_handler]
+pub async fn create_rule(
+    jar: CookieJar,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Json(data): Json<ScanRuleInit>,
+) -> Result<Json<ScanRule>> {
+    let mut conn = state.pool.get().await?;
+    let user = get_user_from_req(&jar, &headers, TokenScope::Moderation, &mut conn).await?;
+
+    check_moderator(&user)?;
+    validate_rule(data.kind, &data.pattern)?;
+
+    Ok(Json(
+        insert_into(scan_rules::table)
+            .values(NewScanRule {
+                name: data.name,
+                description: data.description,
+                kind: data.kind,
+                pattern: data.pattern,
+                enabled: data.enabled.unwrap_or(true),
+            })
+            .returning(ScanRule::as_returning())
+            .get_result(&mut conn)
+            .await?,
+    ))
+}
+
+/// Update Scan Rule
+///
+/// Update a scan rule. This doesn't affect files that were already scanned.
+#[utoipa::path(
+    patch,
+    path = "/rules/{rule}",
+    tag = "Moderation",
+    responses(
+        (status = 200, description = "Updated the rule!", body = ScanRule),
+        (status = 400, description = "The pattern is invalid!"),
+        (status = INTERNAL_SERVER_ERROR, description = "An internal error occured!"),
+    ),
+    params(
+        ("rule" = i32, Path, description = "The rule ID."),
+    ),
+    request_body(content = PartialScanRule, description = "The information to update"),
+    security(
+        ("api_auth_token" = []),
+    ),
+)]
+#[debug_handler]
+pub async fn update_rule(
+    jar: CookieJar,
+    headers: HeaderMap,
+    Path(id): Path<i32>,
+    State(state): State<AppState>,
+    Json(data): Json<PartialScanRule>,
+) -> Result<Json<ScanRule>> {
+    let mut conn = state.pool.get().await?;
+    let user = get_user_from_req(&jar, &headers, TokenScope::Moderation, &mut conn).await?;
+
+    check_moderator(&user)?;
+
+    let rule = get_rule(id, &mut conn).await?;
+    let kind = data.kind.unwrap_or(rule.kind);
+    let pattern = data.pattern.unwrap_or(rule.pattern);
+
+    validate_rule(kind, &pattern)?;
+
+    Ok(Json(
+        diesel::update(scan_rules::table)
+            .filter(scan_rules::id.eq(rule.id))
+            .set((
+                scan_rules::name.eq(data.name.unwrap_or(rule.name)),
+                scan_rules::description.eq(data.description.or(rule.description)),
+                scan_rules::kind.eq(kind),
+                scan_rules::pattern.eq(pattern),
+                scan_rules::enabled.eq(data.enabled.unwrap_or(rule.enabled)),
+                scan_rules::updated_at.eq(Utc::now().naive_utc()),
+            ))
+            .returning(ScanRule::as_returning())
+            .get_result(&mut conn)
+            .await?,
+    ))
+}
+
+/// Delete Scan Rule
+///
+/// Delete a scan rule. Findings from it are kept.
+#[utoipa::path(
+    delete,
+    path = "/rules/{rule}",
+    tag = "Moderation",
+    responses(
+        (status = 200, description = "Deleted the rule!", body = String),
+        (status = INTERNAL_SERVER_ERROR, description = "An internal error occured!"),
+    ),
+    params(
+        ("rule" = i32, Path, description = "The rule ID."),
+    ),
+    security(
+        ("api_auth_token" = []),
+    ),
+)]
+#[debug_handler]
+pub async fn delete_rule(
+    jar: CookieJar,
+    headers: HeaderMap,
+    Path(id): Path<i32>,
+    State(state): State<AppState>,
+) -> Result<Response> {
+    let mut conn = state.pool.get().await?;
+    let user = get_user_from_req(&jar, &headers, TokenScope::Moderation, &mut conn).await?;
+
+    check_moderator(&user)?;
+
+    let rule = get_rule(id, &mut conn).await?;
+
+    diesel::delete(scan_rules::table)
+        .filter(scan_rules::id.eq(rule.id))
+        .execute(&mut conn)
+        .await?;
+
+    Ok(Response::builder().body(Body::new("Deleted scan rule successfully!".to_string()))?)
+}
@@ -0,0 +1,83 @@
+//! Moderation routes for project versions.
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::HeaderMap,
+};
+use axum_extra::extract::CookieJar;
+use diesel::{ExpressionMethods, OptionalExtension, QueryDsl, SelectableHelper};
+use diesel_async::RunQueryDsl;
+use modhost_auth::get_user_from_req;
+use modhost_core::{AppError, Result};
+use modhost_db::{Project, ProjectVersion, TokenScope, project_versions, projects};
+use modhost_db_util::moderation::create_moderation_comment;
+use modhost_server_core::state::AppState;
+
+/// Clear Version
+///
+/// Unblock a version that was blocked because one of its files matched a scan rule,
+/// allowing it to be downloaded again.
+#[utoipa::path(
+    post,
+    path = "/versions/{version}/clear",
+    tag = "Moderation",
+    responses(
+        (status = 200, description = "Cleared the version!", body = ProjectVersion),
+        (status = INTERNAL_SERVER_ERROR, description = "An internal error occured!"),
+    ),
+    params(
+        ("version" = i32, Path, description = "The version ID."),
+    ),
+    security(
+        ("api_auth_token" = []),
+    ),
+)]
+#[debug_handler]
+pub async fn clear_version(
+    jar: CookieJar,
+    headers: HeaderMap,
+    Path(id): Path<i32>,
+    State(state): State<AppState>,
+) -> Result<Json<ProjectVersion>> {
+    let mut conn = state.pool.get().await?;
+    let user = get_user_from_req(&jar, &headers, TokenScope::Moderation, &mut conn).await?;
+
+    if !user.admin && !user.moderator {
+        return Err(AppError::NoAccess);
+    }
+
+    let ver = project_versions::table
+        .find(id)
+        .select(ProjectVersion::as_select())
+        .first(&mut conn)
+        .await
+        .optional()?
+        .ok_or(AppError::NotFound)?;
+
+    let project = projects::table
+        .find(ver.project)
+        .select(Project::as_select())
+        .first(&mut conn)
+        .await?;
+
+    let ver = diesel::update(project_versions::table)
+        .filter(project_versions::id.eq(ver.id))
+        .set(project_versions::blocked.eq(false))
+        .returning(ProjectVersion::as_returning())
+        .get_result(&mut conn)
+        .await?;
+
+    create_moderation_comment(
+        &project,
+        &user,
+        format!(
+            "Cleared version {} ({}) for download.",
+            ver.version_number, ver.id
+        ),
+        &mut conn,
+    )
+    .await?;
+
+    Ok(Json(ver))
+}
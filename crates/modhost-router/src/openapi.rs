@@ -1,8 +1,8 @@
 //! ModHost's OpenAPI system using [`utoipa`].
 
 use crate::{
-    admin::AdminApi, auth::AuthApi, meta::MetadataApi, moderation::ModerationApi,
-    projects::ProjectsApi, users::UsersApi,
+    admin::AdminApi, auth::AuthApi, files::FilesApi, meta::MetadataApi, moderation::ModerationApi,
+    projects::ProjectsApi, resolve::ResolveApi, users::UsersApi,
 };
 use modhost_config::AppConfig;
 use utoipa::{
@@ -28,6 +28,10 @@ impl Modify for TokenAuthAddon {
                 HttpBuilder::new()
                     .scheme(HttpAuthScheme::Bearer)
                     .bearer_format("TOKEN")
+                    .description(Some(
+                        "A session token, or a personal access token (starting with `mhp_`) \
+                         that has the scope the endpoint needs.",
+                    ))
                     .build(),
             ),
         )
@@ -102,6 +106,8 @@ pub fn build_openapi(_config: &AppConfig) -> OpenApi {
     api.nest("/api/v1/auth", AuthApi::openapi())
         .nest("/api/v1/meta", MetadataApi::openapi())
         .nest("/api/v1/projects", ProjectsApi::openapi())
+        .nest("/api/v1/files", FilesApi::openapi())
+        .nest("/api/v1/resolve", ResolveApi::openapi())
         .nest("/api/v1/users", UsersApi::openapi())
         .nest("/api/v1/moderation", ModerationApi::openapi())
         .nest("/api/v1/admin", AdminApi::openapi())
@@ -11,7 +11,7 @@ use diesel::{BoolExpressionMethods, ExpressionMethods, QueryDsl, SelectableHelpe
 use diesel_async::RunQueryDsl;
 use modhost_auth::get_user_from_req;
 use modhost_core::Result;
-use modhost_db::{ProjectAuthor, ProjectData, get_user, project_authors};
+use modhost_db::{ProjectAuthor, ProjectData, TokenScope, get_user, project_authors};
 use modhost_db_util::projects::{get_full_project, get_project};
 use modhost_server_core::state::AppState;
 
@@ -42,7 +42,7 @@ pub async fn remove_handler(
     body: String,
 ) -> Result<Response> {
     let mut conn = state.pool.get().await?;
-    let user = get_user_from_req(&jar, &headers, &mut conn).await?;
+    let user = get_user_from_req(&jar, &headers, TokenScope::ProjectWrite, &mut conn).await?;
     let pkg = get_project(id, &mut conn).await?;
 
     let authors = project_authors::table
@@ -12,7 +12,9 @@ use diesel::{ExpressionMethods, OptionalExtension, QueryDsl, SelectableHelper, i
 use diesel_async::RunQueryDsl;
 use modhost_auth::get_user_from_req;
 use modhost_core::Result;
-use modhost_db::{NewProject, Project, ProjectAuthor, ProjectData, project_authors, projects};
+use modhost_db::{
+    NewProject, Project, ProjectAuthor, ProjectData, TokenScope, project_authors, projects,
+};
 use modhost_db_util::projects::get_full_project;
 use modhost_server_core::state::AppState;
 
@@ -41,7 +43,7 @@ pub async fn create_handler(
     Json(body): Json<NewProject>,
 ) -> Result<Response> {
     let mut conn = state.pool.get().await?;
-    let user = get_user_from_req(&jar, &headers, &mut conn).await?;
+    let user = get_user_from_req(&jar, &headers, TokenScope::ProjectWrite, &mut conn).await?;
 
     if body.slug.is_empty() {
         return Ok(Response::builder()
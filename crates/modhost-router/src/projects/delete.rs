@@ -2,28 +2,44 @@
 
 use axum::{
     body::Body,
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::{HeaderMap, StatusCode},
     response::Response,
 };
 use axum_extra::extract::CookieJar;
 use diesel::{ExpressionMethods, QueryDsl, SelectableHelper, delete};
 use diesel_async::RunQueryDsl;
+use itertools::Itertools;
 use modhost_auth::get_user_from_req;
-use modhost_core::Result;
-use modhost_db::{ProjectAuthor, project_authors, projects};
-use modhost_db_util::projects::get_project;
+use modhost_core::{AppError, Result};
+use modhost_db::{ProjectAuthor, TokenScope, project_authors, projects};
+use modhost_db_util::{projects::get_project, relations::get_dependents};
 use modhost_server_core::state::AppState;
 
+/// Query params for the project delete route.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, ToSchema, ToResponse, Serialize, Deserialize,
+)]
+pub struct DeleteProjectQueryParams {
+    /// Delete the project even if other projects depend on it.
+    /// Their dependencies on this project are removed.
+    pub force: Option<bool>,
+}
+
 /// Delete Project
 ///
-/// Delete a project
+/// Delete a project.
+/// If other projects' versions depend on it, this fails unless `force` is set.
 #[utoipa::path(
     delete,
     path = "/{id}",
     tag = "Projects",
+    params(
+        ("force" = Option<bool>, Query, description = "Delete the project even if other projects depend on it."),
+    ),
     responses(
         (status = 200, description = "Project deleted successfully!", body = String),
+        (status = 409, description = "Other projects depend on this project!"),
         (status = INTERNAL_SERVER_ERROR, description = "Error: project might not exist, or another error occured!"),
     ),
     security(
@@ -35,10 +51,11 @@ pub async fn delete_handler(
     jar: CookieJar,
     headers: HeaderMap,
     Path(id): Path<String>,
+    Query(DeleteProjectQueryParams { force }): Query<DeleteProjectQueryParams>,
     State(state): State<AppState>,
 ) -> Result<Response> {
     let mut conn = state.pool.get().await?;
-    let user = get_user_from_req(&jar, &headers, &mut conn).await?;
+    let user = get_user_from_req(&jar, &headers, TokenScope::ProjectWrite, &mut conn).await?;
     let pkg = get_project(id, &mut conn).await?;
 
     let authors = project_authors::table
@@ -53,6 +70,15 @@ pub async fn delete_handler(
             .body(Body::empty())?);
     }
 
+    let dependents = get_dependents(pkg.id, &mut conn).await?;
+
+    if !dependents.is_empty() && !force.unwrap_or(false) {
+        // A version can depend on more than one version of this project.
+        let versions = dependents.iter().map(|v| v.version_id).unique().count();
+
+        return Err(AppError::HasDependents(versions));
+    }
+
     delete(projects::table)
         .filter(projects::id.eq(pkg.id))
         .execute(&mut conn)
@@ -13,8 +13,8 @@ use diesel_async::RunQueryDsl;
 use modhost_auth::get_user_from_req;
 use modhost_core::{AppError, Result};
 use modhost_db::{
-    GalleryImage, NewGalleryImage, Project, ProjectAuthor, PublicGalleryImage, gallery_images,
-    project_authors, projects,
+    GalleryImage, NewGalleryImage, Project, ProjectAuthor, PublicGalleryImage, TokenScope,
+    gallery_images, project_authors, projects,
 };
 use modhost_db_util::{gallery::transform_gallery_image, projects::get_project};
 use modhost_server_core::state::AppState;
@@ -69,7 +69,7 @@ pub async fn create_handler(
     mut data: Multipart,
 ) -> Result<Response> {
     let mut conn = state.pool.get().await?;
-    let user = get_user_from_req(&jar, &headers, &mut conn).await?;
+    let user = get_user_from_req(&jar, &headers, TokenScope::ProjectWrite, &mut conn).await?;
     let pkg = get_project(id, &mut conn).await?;
 
     let authors = project_authors::table
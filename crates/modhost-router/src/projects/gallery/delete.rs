@@ -11,7 +11,9 @@ use diesel::{ExpressionMethods, QueryDsl, SelectableHelper, delete};
 use diesel_async::RunQueryDsl;
 use modhost_auth::get_user_from_req;
 use modhost_core::Result;
-use modhost_db::{GalleryImage, ProjectAuthor, gallery_images, get_gallery_image, project_authors};
+use modhost_db::{
+    GalleryImage, ProjectAuthor, TokenScope, gallery_images, get_gallery_image, project_authors,
+};
 use modhost_db_util::projects::get_project;
 use modhost_server_core::state::AppState;
 use object_store::ObjectStore;
@@ -42,7 +44,7 @@ pub async fn delete_handler(
     State(state): State<AppState>,
 ) -> Result<Response> {
     let mut conn = state.pool.get().await?;
-    let user = get_user_from_req(&jar, &headers, &mut conn).await?;
+    let user = get_user_from_req(&jar, &headers, TokenScope::ProjectWrite, &mut conn).await?;
     let pkg = get_project(project, &mut conn).await?;
     let img = get_gallery_image(image, &mut conn).await?;
 
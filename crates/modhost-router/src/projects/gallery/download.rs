@@ -1,21 +1,31 @@
 //! The download gallery image route.
 
-use axum::extract::{Path, State};
+use crate::util::download::StoredFile;
+use axum::{
+    extract::{Path, State},
+    http::HeaderMap,
+    response::Response,
+};
 use modhost_core::Result;
 use modhost_db::get_gallery_image;
-use modhost_db_util::gallery::get_image;
 use modhost_server_core::state::AppState;
+use std::time::Duration;
 
 /// Get Gallery Image Data
 ///
 /// Get a gallery image file data from S3.
 /// A URL to this endpoint should be returned by any other gallery endpoints.
+/// Supports the `Range` and `If-None-Match` headers.
 #[utoipa::path(
     get,
     path = "/{image}/download",
     tag = "Gallery",
     responses(
         (status = 200, description = "The gallery image.", body = Vec<u8>),
+        (status = 206, description = "Part of the gallery image.", body = Vec<u8>),
+        (status = 304, description = "The image hasn't changed."),
+        (status = 307, description = "Redirecting to the image."),
+        (status = 416, description = "The requested range isn't satisfiable."),
         (status = INTERNAL_SERVER_ERROR, description = "Error: image might not exist, or another error occured!"),
     ),
     params(
@@ -24,11 +34,27 @@ use modhost_server_core::state::AppState;
 )]
 #[debug_handler]
 pub async fn download_handler(
+    headers: HeaderMap,
     Path((_project, id)): Path<(String, String)>,
     State(state): State<AppState>,
-) -> Result<Vec<u8>> {
+) -> Result<Response> {
     let mut conn = state.pool.get().await?;
     let img = get_gallery_image(id, &mut conn).await?;
+    let hash = img.s3_id.split('.').next().unwrap_or_default();
+    let content_type = mime_guess::from_path(&img.s3_id).first_or_octet_stream();
 
-    get_image(img.s3_id, &state.buckets.gallery).await
+    StoredFile {
+        path: format!("/{}", img.s3_id).into(),
+        hash,
+        file_name: &img.s3_id,
+        content_type: content_type.essence_str(),
+        inline: true,
+    }
+    .serve(
+        &state.buckets.gallery,
+        state.buckets.gallery_signer.as_ref(),
+        Duration::from_secs(state.config.storage.s3_presigned_expiry),
+        &headers,
+    )
+    .await
 }
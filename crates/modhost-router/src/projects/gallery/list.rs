@@ -9,7 +9,7 @@ use axum::{
 use axum_extra::extract::CookieJar;
 use modhost_auth::get_user_from_req;
 use modhost_core::{AppError, Result};
-use modhost_db::{ProjectVisibility, PublicGalleryImage};
+use modhost_db::{ProjectVisibility, PublicGalleryImage, TokenScope};
 use modhost_db_util::{
     gallery::transform_gallery,
     projects::{get_full_project, get_gallery},
@@ -39,9 +39,9 @@ pub async fn list_handler(
     let pkg = get_full_project(id.clone(), &mut conn).await?;
 
     if pkg.visibility == ProjectVisibility::Private {
-        match get_user_from_req(&jar, &headers, &mut conn).await {
+        match get_user_from_req(&jar, &headers, TokenScope::Read, &mut conn).await {
             Ok(user) => {
-                if !pkg.authors.iter().any(|v| v.github_id == user.github_id) && !user.admin {
+                if !pkg.authors.iter().any(|v| v.id == user.id) && !user.admin {
                     return Err(AppError::NotFound);
                 }
             }
@@ -14,7 +14,7 @@ use diesel_async::RunQueryDsl;
 use modhost_auth::get_user_from_req;
 use modhost_core::Result;
 use modhost_db::{
-    GalleryImage, ProjectAuthor, PublicGalleryImage, gallery_images, get_gallery_image,
+    GalleryImage, ProjectAuthor, PublicGalleryImage, TokenScope, gallery_images, get_gallery_image,
     project_authors,
 };
 use modhost_db_util::{gallery::transform_gallery_image, projects::get_project};
@@ -66,7 +66,7 @@ pub async fn update_handler(
     Json(data): Json<PartialGalleryImage>,
 ) -> Result<Response> {
     let mut conn = state.pool.get().await?;
-    let user = get_user_from_req(&jar, &headers, &mut conn).await?;
+    let user = get_user_from_req(&jar, &headers, TokenScope::ProjectWrite, &mut conn).await?;
     let pkg = get_project(project, &mut conn).await?;
     let img = get_gallery_image(image, &mut conn).await?;
 
@@ -10,7 +10,7 @@ use axum_extra::extract::CookieJar;
 use modhost_auth::get_user_from_req;
 use modhost_core::AppError;
 use modhost_core::Result;
-use modhost_db::{ProjectData, ProjectVisibility};
+use modhost_db::{ProjectData, ProjectVisibility, TokenScope};
 use modhost_db_util::projects::get_full_project;
 use modhost_server_core::state::AppState;
 
@@ -40,9 +40,9 @@ pub async fn info_handler(
     let pkg = get_full_project(id, &mut conn).await?;
 
     if pkg.visibility == ProjectVisibility::Private {
-        match get_user_from_req(&jar, &headers, &mut conn).await {
+        match get_user_from_req(&jar, &headers, TokenScope::Read, &mut conn).await {
             Ok(user) => {
-                if !pkg.authors.iter().any(|v| v.github_id == user.github_id) && !user.admin {
+                if !pkg.authors.iter().any(|v| v.id == user.id) && !user.admin {
                     return Err(AppError::NotFound);
                 }
             }
@@ -8,9 +8,9 @@ use axum::{
 use axum_extra::extract::CookieJar;
 use modhost_auth::get_user_from_req;
 use modhost_core::Result;
-use modhost_db::ProjectVisibility;
+use modhost_db::{ProjectVisibility, TokenScope};
 use modhost_search::{Facet, SearchResults, Sort, SortMode};
-use modhost_server_core::state::AppState;
+use modhost_server_core::{models::expand_game_versions, state::AppState};
 
 /// The absolute maximum items per-page for pagination.
 /// The value from a query will be clamped with this.
@@ -52,7 +52,7 @@ pub struct SearchQuery {
         ("per_page" = Option<usize>, Query, description = "How many items per page. Defaults to 25."),
         ("sort" = Option<Sort>, Query, description = "The sort mode. Defaults to None."),
         ("dir" = Option<SortMode>, Query, description = "The sort direction. Defaults to None."),
-        ("filters" = Option<Vec<Facet>>, Query, description = "The search filters. This should be serialized as a `Vec<(String, Vec<String>)>` where the first element of the tuple is the facet name and the second is the value(s)."),
+        ("filters" = Option<Vec<Facet>>, Query, description = "The search filters. This should be serialized as a `Vec<(String, Vec<String>)>` where the first element of the tuple is the facet name and the second is the value(s). Game versions can use ranges and wildcards, like `1.20.x` or `>=1.20 <1.21`."),
     ),
     responses(
         (status = 200, description = "Method returned ok", body = SearchResults),
@@ -80,7 +80,7 @@ pub async fn search_handler(
         serde_json::from_str::<Vec<(String, Vec<String>)>>(&filters.unwrap_or("[]".into()))?;
     let mut facets = Vec::new();
 
-    match get_user_from_req(&jar, &headers, &mut conn).await {
+    match get_user_from_req(&jar, &headers, TokenScope::Read, &mut conn).await {
         Ok(user) => {
             if !user.admin {
                 facets.push(Facet::Manual(format!(
@@ -94,8 +94,14 @@ pub async fn search_handler(
         Err(_) => facets.push(Facet::Visibility(ProjectVisibility::Public)),
     }
 
-    for item in filters {
-        facets.push(Facet::parse(item)?);
+    for (name, values) in filters {
+        let values = if name == "game_versions" {
+            expand_game_versions(values, &state.game_versions)?
+        } else {
+            values
+        };
+
+        facets.push(Facet::parse((name, values))?);
     }
 
     let mut real_sort = None;
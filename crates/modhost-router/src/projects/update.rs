@@ -1,9 +1,10 @@
 //! The project update route.
 
+use crate::util::metadata::{MetadataQueryParams, validate_metadata};
 use axum::{
     Json,
     body::Body,
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::{HeaderMap, StatusCode},
     response::Response,
 };
@@ -13,7 +14,7 @@ use diesel_async::RunQueryDsl;
 use modhost_auth::get_user_from_req;
 use modhost_core::Result;
 use modhost_db::{
-    Project, ProjectAuthor, ProjectData, ProjectVisibility, project_authors, projects,
+    Project, ProjectAuthor, ProjectData, ProjectVisibility, TokenScope, project_authors, projects,
 };
 use modhost_db_util::projects::{get_full_project, get_project};
 use modhost_server_core::state::AppState;
@@ -69,8 +70,12 @@ pub struct PartialProject {
     tag = "Projects",
     responses(
         (status = 200, description = "Project updated successfully!", body = ProjectData),
+        (status = 400, description = "Some tags aren't registered!"),
         (status = INTERNAL_SERVER_ERROR, description = "Error: project might not exist, or another error occured!"),
     ),
+    params(
+        ("skip_validation" = Option<bool>, Query, description = "Accept unregistered tags (admins only)."),
+    ),
     request_body(content = PartialProject, description = "The information to update"),
     security(
         ("api_auth_token" = []),
@@ -81,11 +86,12 @@ pub async fn update_handler(
     jar: CookieJar,
     headers: HeaderMap,
     Path(id): Path<String>,
+    Query(meta): Query<MetadataQueryParams>,
     State(state): State<AppState>,
     Json(data): Json<PartialProject>,
 ) -> Result<Response> {
     let mut conn = state.pool.get().await?;
-    let user = get_user_from_req(&jar, &headers, &mut conn).await?;
+    let user = get_user_from_req(&jar, &headers, TokenScope::ProjectWrite, &mut conn).await?;
     let pkg = get_project(id, &mut conn).await?;
 
     let authors = project_authors::table
@@ -100,6 +106,10 @@ pub async fn update_handler(
             .body(Body::empty())?);
     }
 
+    if !meta.skip(&user)? {
+        validate_metadata(&state, [], [], data.tags.iter().flatten())?;
+    }
+
     let pkg = update(projects::table)
         .filter(projects::id.eq(pkg.id))
         .set((
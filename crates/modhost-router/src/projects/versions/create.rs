@@ -1,38 +1,64 @@
 //! The version create route.
 
+use crate::util::{
+    duplicates::check_duplicate,
+    metadata::{MetadataQueryParams, validate_metadata},
+    scan::{load_rules, save_findings, scan_upload},
+    upload::{StagedUpload, UPLOAD_WARNINGS_HEADER},
+};
 use axum::{
     body::Body,
-    extract::{Multipart, Path, State},
+    extract::{Multipart, Path, Query, State},
     http::{HeaderMap, StatusCode},
     response::Response,
 };
 use axum_extra::extract::CookieJar;
 use chrono::Utc;
 use diesel::{ExpressionMethods, QueryDsl, SelectableHelper, insert_into, update};
-use diesel_async::RunQueryDsl;
+use diesel_async::{AsyncConnection, RunQueryDsl, scoped_futures::ScopedFutureExt};
 use modhost_auth::get_user_from_req;
 use modhost_core::{AppError, Result};
 use modhost_db::{
-    NewProjectFile, NewProjectVersion, Project, ProjectAuthor, ProjectFile, ProjectVersion,
-    ProjectVersionInit, project_authors, project_versions, projects, version_files,
+    NewProjectFile, NewProjectRelation, NewProjectVersion, Project, ProjectAuthor, ProjectFile,
+    ProjectManifest, ProjectVersion, ProjectVersionInit, ProjectVisibility, RelationKind,
+    TokenScope, VersionChannel, project_authors, project_relations, project_versions, projects,
+    version_files,
+};
+use modhost_db_util::{
+    projects::{get_full_project, get_project},
+    scheme::validate_version_number,
+};
+use modhost_server_core::{
+    models::expand_game_versions, state::AppState, validation::UploadValidator,
 };
-use modhost_db_util::projects::get_project;
-use modhost_server_core::state::AppState;
-use object_store::{ObjectStore, PutPayload};
-use semver::Version;
-use sha1::{Digest, Sha1};
 
 /// Upload Project Version
 ///
-/// Upload a new project version
+/// Upload a new project version.
+/// The `file` field can be repeated to upload multiple files. Each file is named by
+/// the matching `file_name` field (in order), or by the file name of its part.
+/// The `primary` field names the primary file, which defaults to the first one.
+/// If the server reads a manifest from the primary file, the version number, loaders,
+/// and game versions can be left out, and relations are added for the manifest's
+/// dependencies and incompatibilities on projects hosted here.
+/// Archives are scanned before they're stored, and if anything suspicious is found,
+/// the project is put under review.
 #[utoipa::path(
     put,
     path = "/",
     tag = "Versions",
     responses(
-        (status = 200, description = "Created project version!", body = ProjectVersion),
+        (status = 200, description = "Created project version!", body = ProjectVersion, headers(
+            ("X-Upload-Warnings" = String, description = "A JSON array of warnings from the server's validators."),
+        )),
+        (status = 400, description = "Some loaders or game versions aren't registered, or the manifest doesn't match!"),
+        (status = 413, description = "The uploaded file is too large!"),
+        (status = 422, description = "The primary file was rejected by the server's validators!"),
         (status = INTERNAL_SERVER_ERROR, description = "An internal error occured!"),
     ),
+    params(
+        ("skip_validation" = Option<bool>, Query, description = "Accept unregistered loaders and game versions (admins only)."),
+    ),
     request_body(content = inline(ProjectVersionInit), description = "The version data", content_type = "multipart/form-data"),
     security(
         ("api_auth_token" = []),
@@ -43,11 +69,12 @@ pub async fn create_handler(
     jar: CookieJar,
     headers: HeaderMap,
     Path(id): Path<String>,
+    Query(meta): Query<MetadataQueryParams>,
     State(state): State<AppState>,
     mut data: Multipart,
 ) -> Result<Response> {
     let mut conn = state.pool.get().await?;
-    let user = get_user_from_req(&jar, &headers, &mut conn).await?;
+    let user = get_user_from_req(&jar, &headers, TokenScope::VersionUpload, &mut conn).await?;
     let pkg = get_project(id, &mut conn).await?;
 
     let authors = project_authors::table
@@ -62,97 +89,182 @@ pub async fn create_handler(
             .body(Body::empty())?);
     }
 
+    let skip_validation = meta.skip(&user)?;
+
     let mut name = None;
     let mut version_number = None;
     let mut changelog = None;
+    let mut channel = None;
     let mut loaders = None;
     let mut game_versions = None;
-    let mut file = None;
-    let mut file_name = None;
-
-    while let Ok(Some(field)) = data.next_field().await {
-        match field.name().ok_or(AppError::MissingFieldName)? {
-            "name" => name = Some(field.text().await?),
-            "version_number" => version_number = Some(field.text().await?),
-            "changelog" => changelog = Some(field.text().await?),
-            "loaders" => {
-                loaders = Some(
-                    field
-                        .text()
-                        .await?
-                        .split(",")
-                        .map(|v| Some(v.to_string()))
-                        .collect::<Vec<_>>(),
-                )
-            }
-            "game_versions" => {
-                game_versions = Some(
-                    field
-                        .text()
-                        .await?
-                        .split(",")
-                        .map(|v| Some(v.to_string()))
-                        .collect::<Vec<_>>(),
-                )
+    let mut files = Vec::new();
+    let mut file_names = Vec::new();
+    let mut primary = None;
+
+    let parsed: Result<()> = async {
+        while let Some(field) = data.next_field().await? {
+            match field.name().ok_or(AppError::MissingFieldName)? {
+                "name" => name = Some(field.text().await?),
+                "version_number" => version_number = Some(field.text().await?),
+                "changelog" => changelog = Some(field.text().await?),
+                "channel" => channel = Some(field.text().await?.parse::<VersionChannel>()?),
+                "loaders" => {
+                    loaders = Some(
+                        field
+                            .text()
+                            .await?
+                            .split(",")
+                            .map(|v| Some(v.to_string()))
+                            .collect::<Vec<_>>(),
+                    )
+                }
+                "game_versions" => {
+                    game_versions = Some(
+                        expand_game_versions(field.text().await?.split(","), &state.game_versions)?
+                            .into_iter()
+                            .map(Some)
+                            .collect::<Vec<_>>(),
+                    )
+                }
+                "file" => {
+                    let part_name = field.file_name().map(|v| v.to_string());
+
+                    let file = StagedUpload::stream(
+                        field,
+                        &state.buckets.projects,
+                        state.config.storage.max_upload_size,
+                        state.config.storage.verify_buffer_size,
+                    )
+                    .await?;
+
+                    files.push((part_name, file));
+                }
+                "file_name" => file_names.push(field.text().await?),
+                "primary" => primary = Some(field.text().await?),
+                _ => {}
             }
-            "file" => file = Some(field.bytes().await?),
-            "file_name" => file_name = Some(field.text().await?),
-            _ => {}
         }
-    }
 
-    if name.is_none() {
-        Err(AppError::MissingField("name".into()))?;
+        Ok(())
     }
+    .await;
 
-    if version_number.is_none() {
-        Err(AppError::MissingField("version_number".into()))?;
-    }
+    let fields: Result<_> = async {
+        parsed?;
 
-    if loaders.is_none() {
-        Err(AppError::MissingField("loaders".into()))?;
-    }
+        let name = name.ok_or_else(|| AppError::MissingField("name".into()))?;
 
-    if game_versions.is_none() {
-        Err(AppError::MissingField("game_versions".into()))?;
-    }
+        if files.is_empty() {
+            Err(AppError::MissingField("file".into()))?;
+        }
 
-    if file.is_none() {
-        Err(AppError::MissingField("file".into()))?;
-    }
+        let names = resolve_file_names(&files, file_names)?;
 
-    if file_name.is_none() {
-        Err(AppError::MissingField("file_name".into()))?;
-    }
+        let primary = match primary {
+            Some(primary) => names
+                .iter()
+                .position(|v| *v == primary)
+                .ok_or(AppError::UnknownFile(primary))?,
+
+            None => 0,
+        };
+
+        let warnings = state
+            .validator
+            .validate(&names[primary], files[primary].1.prefix.clone())
+            .await?
+            .check()?;
+
+        let rules = load_rules(&mut conn).await?;
+        let mut findings = Vec::new();
 
-    let name = name.unwrap();
-    let version_number = version_number.unwrap();
-    let loaders = loaders.unwrap();
-    let game_versions = game_versions.unwrap();
-    let file = file.unwrap();
-    let file_name = file_name.unwrap();
+        for ((_, file), file_name) in files.iter().zip(&names) {
+            findings.push(
+                scan_upload(
+                    &state.config.scan,
+                    &rules,
+                    file_name,
+                    file,
+                    &state.buckets.projects,
+                )
+                .await?,
+            );
+        }
+
+        let manifest = state
+            .extractor
+            .as_ref()
+            .and_then(|extractor| extractor(files[primary].1.prefix.clone()));
+
+        let (version_number, loaders, game_versions) = match &manifest {
+            Some(manifest) => {
+                apply_manifest(&state, manifest, version_number, loaders, game_versions)?
+            }
+
+            None => (version_number, loaders, game_versions),
+        };
+
+        let version_number =
+            version_number.ok_or_else(|| AppError::MissingField("version_number".into()))?;
+
+        let loaders = loaders.ok_or_else(|| AppError::MissingField("loaders".into()))?;
+
+        let game_versions =
+            game_versions.ok_or_else(|| AppError::MissingField("game_versions".into()))?;
+
+        if !skip_validation {
+            validate_metadata(
+                &state,
+                loaders.iter().flatten(),
+                game_versions.iter().flatten(),
+                [],
+            )?;
+        }
 
-    Version::parse(&version_number)?;
+        validate_version_number(state.config.server.version_scheme, &version_number)?;
 
-    if !(state.verifier)(file.clone()) {
-        Err(AppError::NotFound)?;
+        Ok((
+            name,
+            version_number,
+            loaders,
+            game_versions,
+            names,
+            primary,
+            manifest,
+            warnings,
+            findings,
+        ))
     }
+    .await;
 
-    let mut hasher = Sha1::new();
+    let (
+        name,
+        version_number,
+        loaders,
+        game_versions,
+        names,
+        primary,
+        manifest,
+        warnings,
+        findings,
+    ) = match fields {
+        Ok(it) => it,
+
+        Err(err) => {
+            for (_, file) in files {
+                file.discard(&state.buckets.projects).await?;
+            }
 
-    hasher.update(&file);
+            return Err(err);
+        }
+    };
 
-    let file_id = format!("{:x}", hasher.finalize());
-    let file_size = file.len() as i64;
+    let mut uploaded = Vec::new();
 
-    state
-        .buckets
-        .projects
-        .put(
-            &format!("/{}", file_id).into(),
-            PutPayload::from_bytes(file),
-        )
-        .await?;
+    for ((_, file), file_name) in files.into_iter().zip(&names) {
+        uploaded.push((file_name.clone(), file.hashes.clone(), file.size));
+        file.commit(&state.buckets.projects).await?;
+    }
 
     let data = NewProjectVersion {
         project: pkg.id,
@@ -162,39 +274,206 @@ pub async fn create_handler(
         loaders,
         game_versions,
         downloads: 0,
+        channel: channel.unwrap_or_default(),
     };
 
-    update(projects::table)
-        .filter(projects::id.eq(pkg.id))
-        .set(projects::updated_at.eq(Utc::now().naive_utc()))
-        .returning(Project::as_returning())
-        .get_result(&mut conn)
-        .await
-        .unwrap();
-
-    let ver = insert_into(project_versions::table)
-        .values(&data)
-        .returning(ProjectVersion::as_returning())
-        .get_result(&mut conn)
-        .await?;
+    let state = &state;
+    let pkg = &pkg;
+    let user = &user;
 
-    let file = NewProjectFile {
-        file_name,
-        sha1: file_id.clone(),
-        s3_id: file_id,
-        version_id: ver.id,
-        size: file_size,
-    };
+    // The version, its files, and their findings are saved together, so a version
+    // can never be published without its scan results being applied.
+    let ver = conn
+        .transaction::<_, AppError, _>(|conn| {
+            async move {
+                update(projects::table)
+                    .filter(projects::id.eq(pkg.id))
+                    .set(projects::updated_at.eq(Utc::now().naive_utc()))
+                    .returning(Project::as_returning())
+                    .get_result(conn)
+                    .await?;
+
+                let ver = insert_into(project_versions::table)
+                    .values(&data)
+                    .returning(ProjectVersion::as_returning())
+                    .get_result(conn)
+                    .await?;
+
+                let files = uploaded
+                    .into_iter()
+                    .enumerate()
+                    .map(|(idx, (file_name, hashes, size))| NewProjectFile {
+                        file_name,
+                        sha1: hashes.sha1.clone(),
+                        s3_id: hashes.sha1,
+                        version_id: ver.id,
+                        size,
+                        is_primary: idx == primary,
+                        sha256: Some(hashes.sha256),
+                        sha512: Some(hashes.sha512),
+                    })
+                    .collect::<Vec<_>>();
+
+                let files = insert_into(version_files::table)
+                    .values(&files)
+                    .returning(ProjectFile::as_returning())
+                    .get_results(conn)
+                    .await?;
+
+                for (file_name, findings) in names.iter().zip(findings) {
+                    if let Some(file) = files.iter().find(|v| v.file_name == *file_name) {
+                        save_findings(pkg, file, findings, conn).await?;
+                    }
+                }
+
+                for file in &files {
+                    check_duplicate(pkg, file, conn).await?;
+                }
+
+                if let Some(manifest) = manifest {
+                    let mut relations: Vec<NewProjectRelation> = Vec::new();
+
+                    let targets = manifest
+                        .dependencies
+                        .into_iter()
+                        .map(|v| (v, RelationKind::Dependency))
+                        .chain(
+                            manifest
+                                .incompatibilities
+                                .into_iter()
+                                .map(|v| (v, RelationKind::Incompatibility)),
+                        );
+
+                    for (target, kind) in targets {
+                        // Manifests can refer to projects that aren't hosted here, so those are skipped.
+                        let Ok(target) = get_full_project(target, conn).await else {
+                            continue;
+                        };
+
+                        if target.id == pkg.id
+                            || (target.visibility == ProjectVisibility::Private
+                                && !target.authors.iter().any(|v| v.id == user.id)
+                                && !user.admin)
+                            || relations
+                                .iter()
+                                .any(|v| v.target_project == target.id && v.kind == kind)
+                        {
+                            continue;
+                        }
 
-    insert_into(version_files::table)
-        .values(&file)
-        .returning(ProjectFile::as_returning())
-        .get_result(&mut conn)
+                        relations.push(NewProjectRelation {
+                            version_id: ver.id,
+                            target_project: target.id,
+                            target_version: None,
+                            kind,
+                        });
+                    }
+
+                    insert_into(project_relations::table)
+                        .values(&relations)
+                        .execute(conn)
+                        .await?;
+                }
+
+                Ok(ver)
+            }
+            .scope_boxed()
+        })
         .await?;
 
     state.search.update_project(pkg.id, &mut conn).await?;
 
     Ok(Response::builder()
         .header("Content-Type", "application/json")
+        .header(UPLOAD_WARNINGS_HEADER, serde_json::to_string(&warnings)?)
         .body(Body::new(serde_json::to_string(&ver)?))?)
 }
+
+/// The version number, loaders, and game versions of an upload.
+type ManifestFields = (
+    Option<String>,
+    Option<Vec<Option<String>>>,
+    Option<Vec<Option<String>>>,
+);
+
+/// Fill in the fields that an upload left out from its manifest.
+/// Fields that were submitted must match the manifest.
+fn apply_manifest(
+    state: &AppState,
+    manifest: &ProjectManifest,
+    version_number: Option<String>,
+    loaders: Option<Vec<Option<String>>>,
+    game_versions: Option<Vec<Option<String>>>,
+) -> Result<ManifestFields> {
+    let version_number = match version_number {
+        Some(version_number) if version_number != manifest.version => {
+            return Err(AppError::ManifestMismatch("version_number".into()));
+        }
+
+        Some(version_number) => version_number,
+        None => manifest.version.clone(),
+    };
+
+    let manifest_loaders = manifest.loaders.clone();
+    let manifest_game_versions =
+        expand_game_versions(&manifest.game_versions, &state.game_versions)?;
+
+    let loaders = match loaders {
+        Some(loaders) if !same_values(&loaders, &manifest_loaders) => {
+            return Err(AppError::ManifestMismatch("loaders".into()));
+        }
+
+        Some(loaders) => loaders,
+        None => manifest_loaders.into_iter().map(Some).collect(),
+    };
+
+    let game_versions = match game_versions {
+        Some(game_versions) if !same_values(&game_versions, &manifest_game_versions) => {
+            return Err(AppError::ManifestMismatch("game_versions".into()));
+        }
+
+        Some(game_versions) => game_versions,
+        None => manifest_game_versions.into_iter().map(Some).collect(),
+    };
+
+    Ok((Some(version_number), Some(loaders), Some(game_versions)))
+}
+
+/// Check whether submitted values contain the same items as a manifest's, ignoring
+/// order and case.
+fn same_values(values: &[Option<String>], manifest: &[String]) -> bool {
+    let values = values.iter().flatten().collect::<Vec<_>>();
+
+    values
+        .iter()
+        .all(|v| manifest.iter().any(|m| m.eq_ignore_ascii_case(v)))
+        && manifest
+            .iter()
+            .all(|m| values.iter().any(|v| v.eq_ignore_ascii_case(m)))
+}
+
+/// Figure out the names of uploaded files. Names from `file_name` fields are
+/// matched to files in order, falling back to the file name of the part itself.
+fn resolve_file_names(
+    files: &[(Option<String>, StagedUpload)],
+    mut file_names: Vec<String>,
+) -> Result<Vec<String>> {
+    let mut names: Vec<String> = Vec::new();
+
+    file_names.reverse();
+
+    for (part_name, _) in files {
+        let name = file_names
+            .pop()
+            .or_else(|| part_name.clone())
+            .ok_or_else(|| AppError::MissingField("file_name".into()))?;
+
+        if names.contains(&name) {
+            return Err(AppError::DuplicateFileName(name));
+        }
+
+        names.push(name);
+    }
+
+    Ok(names)
+}
@@ -0,0 +1,154 @@
+//! The version diff route.
+
+use crate::util::{
+    access::{check_not_blocked, get_visible_project},
+    archive::hash_entries,
+    diff::{EntryChange, diff_entries, diffable_paths},
+    reader::ObjectReader,
+};
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::HeaderMap,
+};
+use axum_extra::extract::CookieJar;
+use modhost_auth::get_user_from_req;
+use modhost_core::{AppError, Result};
+use modhost_db::{ProjectFile, ProjectVersionData, TokenScope};
+use modhost_db_util::vers::get_full_version;
+use modhost_server_core::state::AppState;
+use object_store::path::Path as ObjectPath;
+
+/// Query params for the version diff route.
+#[derive(
+    Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, ToSchema, ToResponse, Serialize, Deserialize,
+)]
+pub struct VersionDiffQueryParams {
+    /// The older version's ID/name/number.
+    pub from: String,
+
+    /// The newer version's ID/name/number.
+    pub to: String,
+}
+
+/// The differences between two versions of a project.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema, ToResponse)]
+pub struct VersionDiff {
+    /// The older version.
+    pub from: ProjectVersionData,
+
+    /// The newer version.
+    pub to: ProjectVersionData,
+
+    /// The entries that changed between the versions' primary files, sorted by path.
+    pub changes: Vec<EntryChange>,
+
+    /// Whether some diffs were left out because the comparison was too large.
+    pub truncated: bool,
+}
+
+/// Diff Project Versions
+///
+/// Compare the primary files of two versions of a project, listing the archive entries
+/// that were added, removed, or modified. Changed text files include a unified diff, as
+/// long as both sides fit in the server's preview size limit, until the total size of
+/// the diffs (or the time spent on them) reaches the server's limit.
+/// Files that aren't archives are compared as a whole.
+#[utoipa::path(
+    get,
+    path = "/diff",
+    tag = "Versions",
+    params(
+        ("from" = String, Query, description = "The older version's ID/name/number."),
+        ("to" = String, Query, description = "The newer version's ID/name/number."),
+    ),
+    responses(
+        (status = 200, description = "Compared the versions!", body = VersionDiff),
+        (status = 403, description = "One of the versions is blocked until a moderator reviews it!"),
+        (status = 422, description = "One of the archives couldn't be read!"),
+        (status = 503, description = "Too many archives are being read right now!"),
+        (status = INTERNAL_SERVER_ERROR, description = "An internal error occured!"),
+    ),
+)]
+#[debug_handler]
+pub async fn diff_handler(
+    jar: CookieJar,
+    headers: HeaderMap,
+    Path(project): Path<String>,
+    Query(params): Query<VersionDiffQueryParams>,
+    State(state): State<AppState>,
+) -> Result<Json<VersionDiff>> {
+    let mut conn = state.pool.get().await?;
+    let user = get_user_from_req(&jar, &headers, TokenScope::Read, &mut conn)
+        .await
+        .ok();
+    let pkg = get_visible_project(project, user.as_ref(), &mut conn).await?;
+    let from = get_full_version(pkg.id, params.from, &mut conn).await?;
+    let to = get_full_version(pkg.id, params.to, &mut conn).await?;
+
+    check_not_blocked(from.blocked, user.as_ref())?;
+    check_not_blocked(to.blocked, user.as_ref())?;
+
+    // Files are sorted so the primary one comes first.
+    let old_file = from.files.first().ok_or(AppError::NotFound)?.clone();
+    let new_file = to.files.first().ok_or(AppError::NotFound)?.clone();
+
+    let _permit = state
+        .archive_reads
+        .try_acquire()
+        .map_err(|_| AppError::Busy)?;
+
+    let bucket = state.buckets.projects.clone();
+
+    let (keep, max_entries, max_unpacked) = (
+        state.config.storage.max_preview_size,
+        state.config.scan.max_entries,
+        state.config.scan.max_unpacked_size,
+    );
+
+    let (changes, truncated) = tokio::task::spawn_blocking(move || -> Result<_> {
+        // Everything is hashed first, and then only the changed entries that are
+        // small enough to diff are read again with their contents. The files are
+        // read in ranges, so they're never held in memory all at once.
+        let hash = |file: &ProjectFile, keep, only| {
+            let reader = ObjectReader::new(
+                bucket.clone(),
+                ObjectPath::from(format!("/{}", file.s3_id)),
+                file.size as u64,
+            );
+
+            hash_entries(
+                &file.file_name,
+                reader,
+                keep,
+                only,
+                max_entries,
+                max_unpacked,
+            )
+        };
+
+        let mut old = hash(&old_file, 0, None)?;
+        let mut new = hash(&new_file, 0, None)?;
+        let (paths, skipped) = diffable_paths(&old, &new, keep);
+
+        for (entries, file) in [(&mut old, &old_file), (&mut new, &new_file)] {
+            for (path, entry) in hash(file, keep, Some(&paths))? {
+                if let Some(it) = entries.get_mut(&path) {
+                    it.contents = entry.contents;
+                }
+            }
+        }
+
+        let (changes, truncated) = diff_entries(&old, &new);
+
+        Ok((changes, truncated || skipped))
+    })
+    .await??;
+
+    Ok(Json(VersionDiff {
+        from,
+        to,
+        changes,
+        truncated,
+    }))
+}
@@ -1,31 +1,40 @@
 //! The version download route.
 
+use crate::util::{
+    access::{check_not_blocked, get_visible_project},
+    download::{StoredFile, counts_as_download},
+};
 use axum::{
     extract::{Path, State},
     http::HeaderMap,
+    response::Response,
 };
 use axum_extra::extract::CookieJar;
 use diesel::{ExpressionMethods, SelectableHelper, update};
 use diesel_async::RunQueryDsl;
 use modhost_auth::get_user_from_req;
-use modhost_core::AppError;
 use modhost_core::Result;
-use modhost_db::{
-    Project, ProjectVersion, ProjectVisibility, get_version, project_versions, projects,
-};
-use modhost_db_util::{projects::get_full_project, vers::get_version_file};
+use modhost_db::{Project, ProjectVersion, TokenScope, get_version, project_versions, projects};
+use modhost_db_util::vers::get_version_file;
 use modhost_server_core::state::AppState;
-use object_store::ObjectStore;
+use std::time::Duration;
 
 /// Download Project Version
 ///
-/// Download a specific project version
+/// Download a specific project version.
+/// Supports resuming downloads with the `Range` header and caching with `If-None-Match`.
+/// Versions blocked by a scan rule can only be downloaded by moderators.
 #[utoipa::path(
     get,
     path = "/{version}/download/{file}",
     tag = "Versions",
     responses(
+        (status = 200, description = "The file's contents.", body = Vec<u8>),
+        (status = 206, description = "Part of the file's contents.", body = Vec<u8>),
+        (status = 304, description = "The file hasn't changed."),
         (status = 307, description = "Redirecting to download"),
+        (status = 403, description = "The version is blocked until a moderator reviews it!"),
+        (status = 416, description = "The requested range isn't satisfiable."),
         (status = INTERNAL_SERVER_ERROR, description = "An internal error occured!"),
     ),
     params(
@@ -39,55 +48,56 @@ pub async fn download_handler(
     headers: HeaderMap,
     Path((project, version, file)): Path<(String, String, String)>,
     State(state): State<AppState>,
-) -> Result<Vec<u8>> {
+) -> Result<Response> {
     let mut conn = state.pool.get().await?;
-    let pkg = get_full_project(project, &mut conn).await?;
+    let user = get_user_from_req(&jar, &headers, TokenScope::Read, &mut conn)
+        .await
+        .ok();
 
-    if pkg.visibility == ProjectVisibility::Private {
-        match get_user_from_req(&jar, &headers, &mut conn).await {
-            Ok(user) => {
-                if !pkg.authors.iter().any(|v| v.github_id == user.github_id) && !user.admin {
-                    return Err(AppError::NotFound);
-                }
-            }
+    let pkg = get_visible_project(project, user.as_ref(), &mut conn).await?;
+    let ver = get_version(pkg.id, version, &mut conn).await?;
 
-            Err(_) => return Err(AppError::NotFound),
-        }
-    }
+    // Moderators can still download blocked versions, so they can review them.
+    check_not_blocked(ver.blocked, user.as_ref())?;
 
-    let ver = get_version(pkg.id, version, &mut conn).await?;
     let file = get_version_file(ver.id, file, &mut conn).await?;
 
-    update(projects::table)
-        .filter(projects::id.eq(pkg.id))
-        .set((
-            projects::downloads.eq(pkg.downloads + 1),
-            projects::updated_at.eq(pkg.updated_at),
-        ))
-        .returning(Project::as_returning())
-        .get_result(&mut conn)
-        .await?;
-
-    update(project_versions::table)
-        .filter(project_versions::id.eq(ver.id))
-        .set((
-            project_versions::downloads.eq(ver.downloads + 1),
-            project_versions::updated_at.eq(ver.updated_at),
-        ))
-        .returning(ProjectVersion::as_returning())
-        .get_result(&mut conn)
-        .await?;
+    if counts_as_download(&headers, &file.sha1) {
+        update(projects::table)
+            .filter(projects::id.eq(pkg.id))
+            .set((
+                projects::downloads.eq(pkg.downloads + 1),
+                projects::updated_at.eq(pkg.updated_at),
+            ))
+            .returning(Project::as_returning())
+            .get_result(&mut conn)
+            .await?;
 
-    state.search.update_project(pkg.id, &mut conn).await?;
+        update(project_versions::table)
+            .filter(project_versions::id.eq(ver.id))
+            .set((
+                project_versions::downloads.eq(ver.downloads + 1),
+                project_versions::updated_at.eq(ver.updated_at),
+            ))
+            .returning(ProjectVersion::as_returning())
+            .get_result(&mut conn)
+            .await?;
 
-    let bytes = state
-        .buckets
-        .projects
-        .get(&format!("/{}", file.s3_id).into())
-        .await?
-        .bytes()
-        .await?
-        .to_vec();
+        state.search.update_project(pkg.id, &mut conn).await?;
+    }
 
-    Ok(bytes)
+    StoredFile {
+        path: format!("/{}", file.s3_id).into(),
+        hash: &file.sha1,
+        file_name: &file.file_name,
+        content_type: "application/octet-stream",
+        inline: false,
+    }
+    .serve(
+        &state.buckets.projects,
+        state.buckets.projects_signer.as_ref(),
+        Duration::from_secs(state.config.storage.s3_presigned_expiry),
+        &headers,
+    )
+    .await
 }
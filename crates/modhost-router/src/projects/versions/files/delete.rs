@@ -0,0 +1,107 @@
+//! The version file delete route.
+
+use axum::{
+    body::Body,
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::Response,
+};
+use axum_extra::extract::CookieJar;
+use diesel::{ExpressionMethods, QueryDsl, SelectableHelper, delete, update};
+use diesel_async::{AsyncConnection, RunQueryDsl, scoped_futures::ScopedFutureExt};
+use modhost_auth::get_user_from_req;
+use modhost_core::{AppError, Result};
+use modhost_db::{ProjectAuthor, TokenScope, project_authors, version_files};
+use modhost_db_util::{
+    projects::get_project,
+    vers::{get_full_version, get_version_file},
+};
+use modhost_server_core::state::AppState;
+
+/// Delete Version File
+///
+/// Remove a file from a project version.
+/// If it was the primary file, the oldest remaining file becomes the primary one.
+#[utoipa::path(
+    delete,
+    path = "/{version}/files/{file}",
+    tag = "Versions",
+    responses(
+        (status = 200, description = "Deleted the file!", body = String),
+        (status = 400, description = "The file is the version's only file!"),
+        (status = INTERNAL_SERVER_ERROR, description = "An internal error occured!"),
+    ),
+    params(
+        ("version" = String, Path, description = "The version ID/name/number."),
+        ("file" = String, Path, description = "The file ID/name."),
+    ),
+    security(
+        ("api_auth_token" = []),
+    ),
+)]
+#[debug_handler]
+pub async fn delete_handler(
+    jar: CookieJar,
+    headers: HeaderMap,
+    Path((project, version, file)): Path<(String, String, String)>,
+    State(state): State<AppState>,
+) -> Result<Response> {
+    let mut conn = state.pool.get().await?;
+    let user = get_user_from_req(&jar, &headers, TokenScope::VersionUpload, &mut conn).await?;
+    let pkg = get_project(project, &mut conn).await?;
+    let ver = get_full_version(pkg.id, version, &mut conn).await?;
+    let file = get_version_file(ver.id, file, &mut conn).await?;
+
+    let authors = project_authors::table
+        .filter(project_authors::project.eq(pkg.id))
+        .select(ProjectAuthor::as_select())
+        .load(&mut conn)
+        .await?;
+
+    if !authors.iter().any(|v| v.user_id == user.id) && !user.admin {
+        return Ok(Response::builder()
+            .status(StatusCode::UNAUTHORIZED)
+            .body(Body::empty())?);
+    }
+
+    if ver.files.len() <= 1 {
+        return Err(AppError::LastVersionFile);
+    }
+
+    let ver = &ver;
+    let file = &file;
+
+    // The file is deleted and the next one made primary together, so the version
+    // is never left without a primary file.
+    conn.transaction::<_, AppError, _>(|conn| {
+        async move {
+            delete(version_files::table)
+                .filter(version_files::id.eq(file.id))
+                .execute(conn)
+                .await?;
+
+            if file.is_primary
+                && let Some(next) = ver
+                    .files
+                    .iter()
+                    .filter(|v| v.id != file.id)
+                    .min_by_key(|v| v.id)
+            {
+                update(version_files::table)
+                    .filter(version_files::id.eq(next.id))
+                    .set(version_files::is_primary.eq(true))
+                    .execute(conn)
+                    .await?;
+            }
+
+            Ok(())
+        }
+        .scope_boxed()
+    })
+    .await?;
+
+    // The object is left for the garbage collector, since another upload of the
+    // same file may be about to reference it again.
+
+    Ok(Response::builder().body(Body::new("Deleted file successfully!".to_string()))?)
+}
@@ -0,0 +1,65 @@
+//! The version file scan findings route.
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::HeaderMap,
+};
+use axum_extra::extract::CookieJar;
+use diesel::{ExpressionMethods, QueryDsl, SelectableHelper};
+use diesel_async::RunQueryDsl;
+use modhost_auth::get_user_from_req;
+use modhost_core::{AppError, Result};
+use modhost_db::{ProjectAuthor, ScanFinding, TokenScope, project_authors};
+use modhost_db_util::{
+    moderation::get_scan_findings,
+    projects::get_project,
+    vers::{get_full_version, get_version_file},
+};
+use modhost_server_core::state::AppState;
+
+/// List Version File Findings
+///
+/// List the problems the archive scanner found in a version file.
+/// Only the project's authors and moderators can see these.
+#[utoipa::path(
+    get,
+    path = "/{version}/files/{file}/findings",
+    tag = "Versions",
+    responses(
+        (status = 200, description = "Got the findings!", body = Vec<ScanFinding>),
+        (status = INTERNAL_SERVER_ERROR, description = "An internal error occured!"),
+    ),
+    params(
+        ("version" = String, Path, description = "The version ID/name/number."),
+        ("file" = String, Path, description = "The file ID/name."),
+    ),
+    security(
+        ("api_auth_token" = []),
+    ),
+)]
+#[debug_handler]
+pub async fn findings_handler(
+    jar: CookieJar,
+    headers: HeaderMap,
+    Path((project, version, file)): Path<(String, String, String)>,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<ScanFinding>>> {
+    let mut conn = state.pool.get().await?;
+    let user = get_user_from_req(&jar, &headers, TokenScope::Read, &mut conn).await?;
+    let pkg = get_project(project, &mut conn).await?;
+    let ver = get_full_version(pkg.id, version, &mut conn).await?;
+    let file = get_version_file(ver.id, file, &mut conn).await?;
+
+    let authors = project_authors::table
+        .filter(project_authors::project.eq(pkg.id))
+        .select(ProjectAuthor::as_select())
+        .load(&mut conn)
+        .await?;
+
+    if !authors.iter().any(|v| v.user_id == user.id) && !user.admin && !user.moderator {
+        return Err(AppError::NoAccess);
+    }
+
+    Ok(Json(get_scan_findings(&file, &mut conn).await?))
+}
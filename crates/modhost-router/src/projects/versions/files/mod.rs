@@ -0,0 +1,6 @@
+//! Routes concerning the files of a project version.
+
+pub mod delete;
+pub mod findings;
+pub mod tree;
+pub mod upload;
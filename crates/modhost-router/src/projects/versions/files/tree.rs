@@ -0,0 +1,211 @@
+//! The version file archive browsing routes.
+
+use crate::util::{
+    access::{check_not_blocked, get_visible_project},
+    archive::{ArchiveKind, as_text, list_entries, read_entry},
+    reader::ObjectReader,
+};
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::HeaderMap,
+};
+use axum_extra::extract::CookieJar;
+use modhost_auth::get_user_from_req;
+use modhost_core::{AppError, Result};
+use modhost_db::{ArchiveEntry, DbConn, ProjectFile, TokenScope, get_version};
+use modhost_db_util::{
+    files::{get_archive_entries, save_archive_entries},
+    vers::get_version_file,
+};
+use modhost_server_core::state::AppState;
+use object_store::{ObjectStore, path::Path as ObjectPath};
+
+/// A preview of a single archive entry.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema, ToResponse)]
+pub struct ArchiveEntryPreview {
+    /// The entry.
+    pub entry: ArchiveEntry,
+
+    /// Whether the entry looks like binary data.
+    pub binary: bool,
+
+    /// Whether the contents were cut short because the entry is larger
+    /// than the server's preview size limit.
+    pub truncated: bool,
+
+    /// The entry's contents, if it's a text file.
+    pub content: Option<String>,
+}
+
+/// Open a version file as an archive. Only the parts of the file that
+/// are actually read get downloaded.
+async fn open_archive(state: &AppState, file: &ProjectFile) -> Result<(ArchiveKind, ObjectReader)> {
+    let path = ObjectPath::from(format!("/{}", file.s3_id));
+    let magic = state
+        .buckets
+        .projects
+        .get_range(&path, 0..(file.size as usize).min(4))
+        .await?;
+
+    let kind = ArchiveKind::detect(&file.file_name, &magic).ok_or(AppError::NotAnArchive)?;
+    let reader = ObjectReader::new(state.buckets.projects.clone(), path, file.size as u64);
+
+    Ok((kind, reader))
+}
+
+/// Get the entries of a version file, listing (and caching) them if this
+/// hasn't been done yet.
+async fn get_entries(
+    state: &AppState,
+    file: &ProjectFile,
+    conn: &mut DbConn,
+) -> Result<Vec<ArchiveEntry>> {
+    if file.entries_indexed {
+        return get_archive_entries(file, conn).await;
+    }
+
+    let (kind, reader) = open_archive(state, file).await?;
+    let _permit = state
+        .archive_reads
+        .try_acquire()
+        .map_err(|_| AppError::Busy)?;
+
+    let (id, max_entries, max_unpacked) = (
+        file.id,
+        state.config.scan.max_entries,
+        state.config.scan.max_unpacked_size,
+    );
+
+    let entries = tokio::task::spawn_blocking(move || {
+        list_entries(kind, id, reader, max_entries, max_unpacked)
+    })
+    .await??;
+
+    save_archive_entries(file, &entries, conn).await
+}
+
+/// List Version File Entries
+///
+/// List the entries inside of a version file, if it's a `.zip`, `.jar`, or `.tar.gz` archive.
+/// Nested archives aren't expanded. The listing is cached after the first request.
+#[utoipa::path(
+    get,
+    path = "/{version}/files/{file}/tree",
+    tag = "Versions",
+    responses(
+        (status = 200, description = "Got the entries!", body = Vec<ArchiveEntry>),
+        (status = 400, description = "The file isn't an archive!"),
+        (status = 409, description = "The file was replaced while it was being read!"),
+        (status = 422, description = "The archive couldn't be read!"),
+        (status = 503, description = "Too many archives are being read right now!"),
+        (status = INTERNAL_SERVER_ERROR, description = "An internal error occured!"),
+    ),
+    params(
+        ("version" = String, Path, description = "The version ID/name/number."),
+        ("file" = String, Path, description = "The file ID/name."),
+    ),
+)]
+#[debug_handler]
+pub async fn tree_handler(
+    jar: CookieJar,
+    headers: HeaderMap,
+    Path((project, version, file)): Path<(String, String, String)>,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<ArchiveEntry>>> {
+    let mut conn = state.pool.get().await?;
+    let user = get_user_from_req(&jar, &headers, TokenScope::Read, &mut conn)
+        .await
+        .ok();
+    let pkg = get_visible_project(project, user.as_ref(), &mut conn).await?;
+    let ver = get_version(pkg.id, version, &mut conn).await?;
+    let file = get_version_file(ver.id, file, &mut conn).await?;
+
+    Ok(Json(get_entries(&state, &file, &mut conn).await?))
+}
+
+/// Preview Version File Entry
+///
+/// Get a single entry inside of a version file. If it's a text file, its contents are
+/// included, up to the server's preview size limit.
+/// Entries of versions blocked by a scan rule can only be previewed by moderators.
+#[utoipa::path(
+    get,
+    path = "/{version}/files/{file}/tree/{path}",
+    tag = "Versions",
+    responses(
+        (status = 200, description = "Got the entry!", body = ArchiveEntryPreview),
+        (status = 400, description = "The file isn't an archive!"),
+        (status = 403, description = "The version is blocked until a moderator reviews it!"),
+        (status = 409, description = "The file was replaced while it was being read!"),
+        (status = 422, description = "The archive couldn't be read!"),
+        (status = 503, description = "Too many archives are being read right now!"),
+        (status = INTERNAL_SERVER_ERROR, description = "An internal error occured!"),
+    ),
+    params(
+        ("version" = String, Path, description = "The version ID/name/number."),
+        ("file" = String, Path, description = "The file ID/name."),
+        ("path" = String, Path, description = "The entry's path inside of the archive."),
+    ),
+)]
+#[debug_handler]
+pub async fn entry_handler(
+    jar: CookieJar,
+    headers: HeaderMap,
+    Path((project, version, file, path)): Path<(String, String, String, String)>,
+    State(state): State<AppState>,
+) -> Result<Json<ArchiveEntryPreview>> {
+    let mut conn = state.pool.get().await?;
+    let user = get_user_from_req(&jar, &headers, TokenScope::Read, &mut conn)
+        .await
+        .ok();
+    let pkg = get_visible_project(project, user.as_ref(), &mut conn).await?;
+    let ver = get_version(pkg.id, version, &mut conn).await?;
+
+    // Previewing an entry is as good as downloading it.
+    check_not_blocked(ver.blocked, user.as_ref())?;
+
+    let file = get_version_file(ver.id, file, &mut conn).await?;
+
+    let entry = get_entries(&state, &file, &mut conn)
+        .await?
+        .into_iter()
+        .find(|v| v.path == path || (v.directory && v.path.trim_end_matches('/') == path))
+        .ok_or(AppError::NotFound)?;
+
+    if entry.directory {
+        return Ok(Json(ArchiveEntryPreview {
+            entry,
+            binary: false,
+            truncated: false,
+            content: None,
+        }));
+    }
+
+    let (kind, reader) = open_archive(&state, &file).await?;
+    let _permit = state
+        .archive_reads
+        .try_acquire()
+        .map_err(|_| AppError::Busy)?;
+
+    let (entry_path, limit, max_unpacked) = (
+        entry.path.clone(),
+        state.config.storage.max_preview_size,
+        state.config.scan.max_unpacked_size,
+    );
+
+    let contents = tokio::task::spawn_blocking(move || {
+        read_entry(kind, reader, &entry_path, limit, max_unpacked)
+    })
+    .await??
+    .ok_or(AppError::NotFound)?;
+
+    let content = as_text(&contents.data, contents.truncated);
+
+    Ok(Json(ArchiveEntryPreview {
+        entry,
+        binary: content.is_none(),
+        truncated: contents.truncated,
+        content,
+    }))
+}
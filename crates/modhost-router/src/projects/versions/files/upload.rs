@@ -0,0 +1,251 @@
+//! The version file upload route.
+
+use crate::util::{
+    duplicates::check_duplicate,
+    scan::{load_rules, save_findings, scan_upload},
+    upload::{StagedUpload, UPLOAD_WARNINGS_HEADER},
+};
+use axum::{
+    body::Body,
+    extract::{Multipart, Path, State},
+    http::{HeaderMap, StatusCode},
+    response::Response,
+};
+use axum_extra::extract::CookieJar;
+use diesel::{ExpressionMethods, QueryDsl, SelectableHelper, insert_into, update};
+use diesel_async::{AsyncConnection, RunQueryDsl, scoped_futures::ScopedFutureExt};
+use modhost_auth::get_user_from_req;
+use modhost_core::{AppError, Result};
+use modhost_db::{
+    NewProjectFile, ProjectAuthor, ProjectFile, TokenScope, project_authors, version_files,
+};
+use modhost_db_util::{
+    files::clear_archive_entries, projects::get_project, vers::get_full_version,
+};
+use modhost_server_core::{state::AppState, validation::UploadValidator};
+
+/// The data for uploading a file to an existing project version.
+/// This should be formatted as "multipart/form-data".
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, ToSchema, ToResponse)]
+pub struct VersionFileUpload {
+    /// Whether this should become the version's primary file.
+    /// Defaults to `false`, unless the version has no primary file.
+    /// Replacing the primary file always keeps it primary.
+    pub primary: Option<bool>,
+
+    /// The file content.
+    #[schema(content_media_type = "application/octet-stream")]
+    pub file: Vec<u8>,
+}
+
+/// Upload Version File
+///
+/// Add a file to a project version, or replace the file with the same name.
+/// Archives are scanned before they're stored, and if anything suspicious is found,
+/// the project is put under review.
+#[utoipa::path(
+    put,
+    path = "/{version}/files/{file}",
+    tag = "Versions",
+    responses(
+        (status = 200, description = "Uploaded the file!", body = ProjectFile, headers(
+            ("X-Upload-Warnings" = String, description = "A JSON array of warnings from the server's validators."),
+        )),
+        (status = 413, description = "The uploaded file is too large!"),
+        (status = 422, description = "The file was rejected by the server's validators!"),
+        (status = INTERNAL_SERVER_ERROR, description = "An internal error occured!"),
+    ),
+    params(
+        ("version" = String, Path, description = "The version ID/name/number."),
+        ("file" = String, Path, description = "The file name."),
+    ),
+    request_body(content = inline(VersionFileUpload), description = "The file data", content_type = "multipart/form-data"),
+    security(
+        ("api_auth_token" = []),
+    ),
+)]
+#[debug_handler]
+pub async fn upload_handler(
+    jar: CookieJar,
+    headers: HeaderMap,
+    Path((project, version, file_name)): Path<(String, String, String)>,
+    State(state): State<AppState>,
+    mut data: Multipart,
+) -> Result<Response> {
+    let mut conn = state.pool.get().await?;
+    let user = get_user_from_req(&jar, &headers, TokenScope::VersionUpload, &mut conn).await?;
+    let pkg = get_project(project, &mut conn).await?;
+    let ver = get_full_version(pkg.id, version, &mut conn).await?;
+
+    let authors = project_authors::table
+        .filter(project_authors::project.eq(pkg.id))
+        .select(ProjectAuthor::as_select())
+        .load(&mut conn)
+        .await?;
+
+    if !authors.iter().any(|v| v.user_id == user.id) && !user.admin {
+        return Ok(Response::builder()
+            .status(StatusCode::UNAUTHORIZED)
+            .body(Body::empty())?);
+    }
+
+    let mut file = None;
+    let mut primary = None;
+
+    let parsed: Result<()> = async {
+        while let Some(field) = data.next_field().await? {
+            match field.name().ok_or(AppError::MissingFieldName)? {
+                "file" => {
+                    if file.is_some() {
+                        return Err(AppError::DuplicateField("file".into()));
+                    }
+
+                    file = Some(
+                        StagedUpload::stream(
+                            field,
+                            &state.buckets.projects,
+                            state.config.storage.max_upload_size,
+                            state.config.storage.verify_buffer_size,
+                        )
+                        .await?,
+                    )
+                }
+                "primary" => primary = Some(field.text().await? == "true"),
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+    .await;
+
+    let existing = ver.files.iter().find(|v| v.file_name == file_name).cloned();
+
+    // The file becomes primary if it replaces the primary file (so the version is
+    // never left without one), if requested, or if the version doesn't have one at all.
+    let is_primary = existing.as_ref().is_some_and(|v| v.is_primary)
+        || primary.unwrap_or_else(|| !ver.files.iter().any(|v| v.is_primary));
+
+    let checked: Result<_> = async {
+        parsed?;
+
+        let file = file
+            .as_ref()
+            .ok_or_else(|| AppError::MissingField("file".into()))?;
+
+        let warnings = if is_primary {
+            state
+                .validator
+                .validate(&file_name, file.prefix.clone())
+                .await?
+                .check()?
+        } else {
+            Vec::new()
+        };
+
+        let rules = load_rules(&mut conn).await?;
+
+        let findings = scan_upload(
+            &state.config.scan,
+            &rules,
+            &file_name,
+            file,
+            &state.buckets.projects,
+        )
+        .await?;
+
+        Ok((warnings, findings))
+    }
+    .await;
+
+    let (warnings, findings) = match checked {
+        Ok(warnings) => warnings,
+
+        Err(err) => {
+            if let Some(file) = file {
+                file.discard(&state.buckets.projects).await?;
+            }
+
+            return Err(err);
+        }
+    };
+
+    let file = file.ok_or(AppError::Unknown)?;
+    let hashes = file.hashes.clone();
+    let size = file.size;
+
+    file.commit(&state.buckets.projects).await?;
+
+    let pkg = &pkg;
+    let ver = &ver;
+    let existing = &existing;
+
+    // The file and its findings are saved together, so it can never be
+    // published without its scan results being applied.
+    let file = conn
+        .transaction::<_, AppError, _>(|conn| {
+            async move {
+                if is_primary {
+                    update(version_files::table)
+                        .filter(version_files::version_id.eq(ver.id))
+                        .set(version_files::is_primary.eq(false))
+                        .execute(conn)
+                        .await?;
+                }
+
+                let file = match existing {
+                    Some(existing) => {
+                        // The old file's contents are gone, so its listing is too.
+                        clear_archive_entries(existing.id, conn).await?;
+
+                        update(version_files::table)
+                            .filter(version_files::id.eq(existing.id))
+                            .set((
+                                version_files::sha1.eq(&hashes.sha1),
+                                version_files::s3_id.eq(&hashes.sha1),
+                                version_files::sha256.eq(&hashes.sha256),
+                                version_files::sha512.eq(&hashes.sha512),
+                                version_files::size.eq(size),
+                                version_files::is_primary.eq(is_primary),
+                                version_files::uploaded_at.eq(diesel::dsl::now),
+                            ))
+                            .returning(ProjectFile::as_returning())
+                            .get_result(conn)
+                            .await?
+                    }
+
+                    None => {
+                        insert_into(version_files::table)
+                            .values(&NewProjectFile {
+                                file_name,
+                                sha1: hashes.sha1.clone(),
+                                s3_id: hashes.sha1,
+                                version_id: ver.id,
+                                size,
+                                is_primary,
+                                sha256: Some(hashes.sha256),
+                                sha512: Some(hashes.sha512),
+                            })
+                            .returning(ProjectFile::as_returning())
+                            .get_result(conn)
+                            .await?
+                    }
+                };
+
+                save_findings(pkg, &file, findings, conn).await?;
+                check_duplicate(pkg, &file, conn).await?;
+
+                Ok(file)
+            }
+            .scope_boxed()
+        })
+        .await?;
+
+    // A replaced file's object is left for the garbage collector, since another
+    // upload of the same file may be about to reference it again.
+
+    Ok(Response::builder()
+        .header("Content-Type", "application/json")
+        .header(UPLOAD_WARNINGS_HEADER, serde_json::to_string(&warnings)?)
+        .body(Body::new(serde_json::to_string(&file)?))?)
+}
@@ -9,7 +9,7 @@ use axum_extra::extract::CookieJar;
 use modhost_auth::get_user_from_req;
 use modhost_core::AppError;
 use modhost_core::Result;
-use modhost_db::{ProjectVersion, ProjectVersionData, ProjectVisibility};
+use modhost_db::{ProjectVersion, ProjectVersionData, ProjectVisibility, TokenScope};
 use modhost_db_util::{projects::get_full_project, vers::get_full_version};
 use modhost_server_core::state::AppState;
 
@@ -39,9 +39,9 @@ pub async fn info_handler(
     let pkg = get_full_project(project, &mut conn).await?;
 
     if pkg.visibility == ProjectVisibility::Private {
-        match get_user_from_req(&jar, &headers, &mut conn).await {
+        match get_user_from_req(&jar, &headers, TokenScope::Read, &mut conn).await {
             Ok(user) => {
-                if !pkg.authors.iter().any(|v| v.github_id == user.github_id) && !user.admin {
+                if !pkg.authors.iter().any(|v| v.id == user.id) && !user.admin {
                     return Err(AppError::NotFound);
                 }
             }
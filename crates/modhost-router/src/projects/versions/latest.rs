@@ -1,25 +1,47 @@
 //! The latest version route.
 
+use crate::util::access::{can_see_blocked, get_visible_project};
 use axum::{
     Json,
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::HeaderMap,
 };
 use axum_extra::extract::CookieJar;
 use modhost_auth::get_user_from_req;
-use modhost_core::AppError;
 use modhost_core::Result;
-use modhost_db::{ProjectVersion, ProjectVersionData, ProjectVisibility};
-use modhost_db_util::{projects::get_full_project, vers::get_latest_full_version};
+use modhost_db::{ProjectVersion, ProjectVersionData, TokenScope, VersionChannel};
+use modhost_db_util::vers::get_latest_compatible_version;
 use modhost_server_core::state::AppState;
 
+/// Query params for the latest version route.
+#[derive(
+    Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, ToSchema, ToResponse, Serialize, Deserialize,
+)]
+pub struct LatestVersionQueryParams {
+    /// Only consider versions in this release channel.
+    pub channel: Option<VersionChannel>,
+
+    /// Only consider versions that support this loader.
+    pub loader: Option<String>,
+
+    /// Only consider versions that support this game version.
+    pub game_version: Option<String>,
+}
+
 /// Get Latest Project Version
 ///
-/// Get information about the latest project version
+/// Get information about the latest project version,
+/// optionally filtered by release channel, loader, and game version.
+/// Versions blocked by a scan rule are skipped, except for moderators.
 #[utoipa::path(
     get,
     path = "/versions/latest",
     tag = "Versions",
+    params(
+        ("channel" = Option<VersionChannel>, Query, description = "Only consider versions in this release channel."),
+        ("loader" = Option<String>, Query, description = "Only consider versions that support this loader."),
+        ("game_version" = Option<String>, Query, description = "Only consider versions that support this game version."),
+    ),
     responses(
         (status = 200, description = "Found latest version!", body = ProjectVersion),
         (status = INTERNAL_SERVER_ERROR, description = "An internal error occured!"),
@@ -30,22 +52,30 @@ pub async fn latest_handler(
     jar: CookieJar,
     headers: HeaderMap,
     Path(project): Path<String>,
+    Query(LatestVersionQueryParams {
+        channel,
+        loader,
+        game_version,
+    }): Query<LatestVersionQueryParams>,
     State(state): State<AppState>,
 ) -> Result<Json<ProjectVersionData>> {
     let mut conn = state.pool.get().await?;
-    let pkg = get_full_project(project, &mut conn).await?;
-
-    if pkg.visibility == ProjectVisibility::Private {
-        match get_user_from_req(&jar, &headers, &mut conn).await {
-            Ok(user) => {
-                if !pkg.authors.iter().any(|v| v.github_id == user.github_id) && !user.admin {
-                    return Err(AppError::NotFound);
-                }
-            }
+    let user = get_user_from_req(&jar, &headers, TokenScope::Read, &mut conn)
+        .await
+        .ok();
 
-            Err(_) => return Err(AppError::NotFound),
-        }
-    }
+    let pkg = get_visible_project(project, user.as_ref(), &mut conn).await?;
 
-    Ok(Json(get_latest_full_version(pkg.id, &mut conn).await?))
+    Ok(Json(
+        get_latest_compatible_version(
+            pkg.id,
+            loader.as_deref(),
+            game_version.as_deref(),
+            channel.as_slice(),
+            can_see_blocked(user.as_ref()),
+            state.config.server.version_scheme,
+            &mut conn,
+        )
+        .await?,
+    ))
 }
@@ -9,7 +9,7 @@ use axum_extra::extract::CookieJar;
 use modhost_auth::get_user_from_req;
 use modhost_core::AppError;
 use modhost_core::Result;
-use modhost_db::{ProjectVersionData, ProjectVisibility};
+use modhost_db::{ProjectVersionData, ProjectVisibility, TokenScope};
 use modhost_db_util::{projects::get_full_project, vers::get_versions};
 use modhost_server_core::state::AppState;
 
@@ -36,9 +36,9 @@ pub async fn list_handler(
     let pkg = get_full_project(id, &mut conn).await?;
 
     if pkg.visibility == ProjectVisibility::Private {
-        match get_user_from_req(&jar, &headers, &mut conn).await {
+        match get_user_from_req(&jar, &headers, TokenScope::Read, &mut conn).await {
             Ok(user) => {
-                if !pkg.authors.iter().any(|v| v.github_id == user.github_id) && !user.admin {
+                if !pkg.authors.iter().any(|v| v.id == user.id) && !user.admin {
                     return Err(AppError::NotFound);
                 }
             }
@@ -47,5 +47,7 @@ pub async fn list_handler(
         }
     }
 
-    Ok(Json(get_versions(pkg.id, &mut conn).await?))
+    Ok(Json(
+        get_versions(pkg.id, state.config.server.version_scheme, &mut conn).await?,
+    ))
 }
@@ -2,25 +2,37 @@
 
 use axum::{
     Router,
+    extract::DefaultBodyLimit,
     routing::{delete, get, patch, put},
 };
 use modhost_server_core::state::AppState;
 
 pub mod create;
 pub mod delete;
+pub mod diff;
 pub mod download;
+pub mod files;
 pub mod info;
 pub mod latest;
 pub mod list;
+pub mod relations;
 pub mod update;
 
 /// Register project versions API routes.
 /// Should be nested at `/api/v1/projects/{id}/versions`.
 pub fn router(state: AppState) -> Router<AppState> {
+    // The size of the file itself is checked while it's being uploaded,
+    // so this only needs to leave some room for the other fields.
+    let upload_limit = state.config.storage.max_upload_size as usize + 1024 * 1024;
+
     Router::new()
         .route("/", get(list::list_handler))
-        .route("/", put(create::create_handler))
+        .route(
+            "/",
+            put(create::create_handler).layer(DefaultBodyLimit::max(upload_limit)),
+        )
         .route("/latest", get(latest::latest_handler))
+        .route("/diff", get(diff::diff_handler))
         .route("/{version}", get(info::info_handler))
         .route("/{version}", patch(update::update_handler))
         .route("/{version}", delete(delete::delete_handler))
@@ -28,6 +40,35 @@ pub fn router(state: AppState) -> Router<AppState> {
             "/{version}/download/{file}",
             get(download::download_handler),
         )
+        .route(
+            "/{version}/files/{file}",
+            put(files::upload::upload_handler).layer(DefaultBodyLimit::max(upload_limit)),
+        )
+        .route(
+            "/{version}/files/{file}",
+            delete(files::delete::delete_handler),
+        )
+        .route(
+            "/{version}/files/{file}/findings",
+            get(files::findings::findings_handler),
+        )
+        .route(
+            "/{version}/files/{file}/tree",
+            get(files::tree::tree_handler),
+        )
+        .route(
+            "/{version}/files/{file}/tree/{*path}",
+            get(files::tree::entry_handler),
+        )
+        .route("/{version}/relations", get(relations::list::list_handler))
+        .route(
+            "/{version}/relations",
+            put(relations::create::create_handler),
+        )
+        .route(
+            "/{version}/relations/{relation}",
+            delete(relations::delete::delete_handler),
+        )
         .with_state(state)
 }
 
@@ -42,5 +83,14 @@ pub fn router(state: AppState) -> Router<AppState> {
     list::list_handler,
     update::update_handler,
     latest::latest_handler,
+    diff::diff_handler,
+    files::upload::upload_handler,
+    files::delete::delete_handler,
+    files::findings::findings_handler,
+    files::tree::tree_handler,
+    files::tree::entry_handler,
+    relations::list::list_handler,
+    relations::create::create_handler,
+    relations::delete::delete_handler,
 ))]
 pub struct ProjectVersionsApi;
@@ -0,0 +1,145 @@
+//! The version relation create route.
+
+use axum::{
+    Json,
+    body::Body,
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use axum_extra::extract::CookieJar;
+use diesel::{ExpressionMethods, OptionalExtension, QueryDsl, SelectableHelper, insert_into};
+use diesel_async::RunQueryDsl;
+use modhost_auth::get_user_from_req;
+use modhost_core::{AppError, Result};
+use modhost_db::{
+    NewProjectRelation, ProjectAuthor, ProjectRelation, ProjectVisibility, RelationKind,
+    TokenScope, get_version, project_authors, project_relations,
+};
+use modhost_db_util::projects::{get_full_project, get_project};
+use modhost_server_core::state::AppState;
+
+/// The data for adding a relation to a project version.
+#[derive(
+    Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, ToSchema, ToResponse, Serialize, Deserialize,
+)]
+pub struct VersionRelationInit {
+    /// The target project's ID or slug.
+    pub project: String,
+
+    /// The target version's ID, name, or number.
+    /// If this is omitted, the relation applies to any version of the target project.
+    #[serde(default)]
+    pub version: Option<String>,
+
+    /// The relation kind.
+    /// Defaults to [`RelationKind::Dependency`].
+    #[serde(default)]
+    pub kind: RelationKind,
+}
+
+/// Add Version Relation
+///
+/// Declare that a project version depends on, or is incompatible with,
+/// another project or a specific version of it.
+/// If the relation already exists, it is returned as-is.
+#[utoipa::path(
+    put,
+    path = "/{version}/relations",
+    tag = "Versions",
+    responses(
+        (status = 200, description = "Added the relation!", body = ProjectRelation),
+        (status = 400, description = "A project can't have a relation to itself!"),
+        (status = INTERNAL_SERVER_ERROR, description = "An internal error occured!"),
+    ),
+    params(
+        ("version" = String, Path, description = "The version ID/name/number."),
+    ),
+    request_body(content = VersionRelationInit, description = "The relation to add"),
+    security(
+        ("api_auth_token" = []),
+    ),
+)]
+#[debug_handler]
+pub async fn create_handler(
+    jar: CookieJar,
+    headers: HeaderMap,
+    Path((project, version)): Path<(String, String)>,
+    State(state): State<AppState>,
+    Json(data): Json<VersionRelationInit>,
+) -> Result<Response> {
+    let mut conn = state.pool.get().await?;
+    let user = get_user_from_req(&jar, &headers, TokenScope::VersionUpload, &mut conn).await?;
+    let pkg = get_project(project, &mut conn).await?;
+    let ver = get_version(pkg.id, version, &mut conn).await?;
+
+    let authors = project_authors::table
+        .filter(project_authors::project.eq(pkg.id))
+        .select(ProjectAuthor::as_select())
+        .load(&mut conn)
+        .await?;
+
+    if !authors.iter().any(|v| v.user_id == user.id) && !user.admin {
+        return Ok(Response::builder()
+            .status(StatusCode::UNAUTHORIZED)
+            .body(Body::empty())?);
+    }
+
+    let target = get_full_project(data.project, &mut conn).await?;
+
+    if target.visibility == ProjectVisibility::Private
+        && !target.authors.iter().any(|v| v.id == user.id)
+        && !user.admin
+    {
+        return Err(AppError::NotFound);
+    }
+
+    if target.id == pkg.id {
+        return Err(AppError::InvalidRelation(
+            "A project can't have a relation to itself!".into(),
+        ));
+    }
+
+    let target_version = match data.version {
+        Some(version) => Some(get_version(target.id, version, &mut conn).await?.id),
+        None => None,
+    };
+
+    // Adding a relation that already exists just returns it.
+    let inserted = insert_into(project_relations::table)
+        .values(&NewProjectRelation {
+            version_id: ver.id,
+            target_project: target.id,
+            target_version,
+            kind: data.kind,
+        })
+        .on_conflict_do_nothing()
+        .returning(ProjectRelation::as_returning())
+        .get_result(&mut conn)
+        .await
+        .optional()?;
+
+    let relation = match inserted {
+        Some(relation) => relation,
+
+        None => {
+            let existing = project_relations::table
+                .filter(project_relations::version_id.eq(ver.id))
+                .filter(project_relations::target_project.eq(target.id))
+                .filter(project_relations::kind.eq(data.kind))
+                .into_boxed();
+
+            let existing = match target_version {
+                Some(id) => existing.filter(project_relations::target_version.eq(id)),
+                None => existing.filter(project_relations::target_version.is_null()),
+            };
+
+            existing
+                .select(ProjectRelation::as_select())
+                .first(&mut conn)
+                .await?
+        }
+    };
+
+    Ok(Json(relation).into_response())
+}
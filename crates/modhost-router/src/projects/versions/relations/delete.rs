@@ -0,0 +1,72 @@
+//! The version relation delete route.
+
+use axum::{
+    body::Body,
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::Response,
+};
+use axum_extra::extract::CookieJar;
+use diesel::{ExpressionMethods, QueryDsl, SelectableHelper, delete};
+use diesel_async::RunQueryDsl;
+use modhost_auth::get_user_from_req;
+use modhost_core::{AppError, Result};
+use modhost_db::{ProjectAuthor, TokenScope, get_version, project_authors, project_relations};
+use modhost_db_util::projects::get_project;
+use modhost_server_core::state::AppState;
+
+/// Delete Version Relation
+///
+/// Remove a dependency or incompatibility from a project version.
+#[utoipa::path(
+    delete,
+    path = "/{version}/relations/{relation}",
+    tag = "Versions",
+    responses(
+        (status = 200, description = "Deleted the relation!", body = String),
+        (status = INTERNAL_SERVER_ERROR, description = "An internal error occured!"),
+    ),
+    params(
+        ("version" = String, Path, description = "The version ID/name/number."),
+        ("relation" = i32, Path, description = "The relation ID."),
+    ),
+    security(
+        ("api_auth_token" = []),
+    ),
+)]
+#[debug_handler]
+pub async fn delete_handler(
+    jar: CookieJar,
+    headers: HeaderMap,
+    Path((project, version, relation)): Path<(String, String, i32)>,
+    State(state): State<AppState>,
+) -> Result<Response> {
+    let mut conn = state.pool.get().await?;
+    let user = get_user_from_req(&jar, &headers, TokenScope::VersionUpload, &mut conn).await?;
+    let pkg = get_project(project, &mut conn).await?;
+    let ver = get_version(pkg.id, version, &mut conn).await?;
+
+    let authors = project_authors::table
+        .filter(project_authors::project.eq(pkg.id))
+        .select(ProjectAuthor::as_select())
+        .load(&mut conn)
+        .await?;
+
+    if !authors.iter().any(|v| v.user_id == user.id) && !user.admin {
+        return Ok(Response::builder()
+            .status(StatusCode::UNAUTHORIZED)
+            .body(Body::empty())?);
+    }
+
+    let deleted = delete(project_relations::table)
+        .filter(project_relations::id.eq(relation))
+        .filter(project_relations::version_id.eq(ver.id))
+        .execute(&mut conn)
+        .await?;
+
+    if deleted == 0 {
+        return Err(AppError::NotFound);
+    }
+
+    Ok(Response::builder().body(Body::new("Deleted relation successfully!".to_string()))?)
+}
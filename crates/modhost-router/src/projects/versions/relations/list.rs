@@ -0,0 +1,55 @@
+//! The version relation list route.
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::HeaderMap,
+};
+use axum_extra::extract::CookieJar;
+use modhost_auth::get_user_from_req;
+use modhost_core::{AppError, Result};
+use modhost_db::{ProjectRelation, ProjectVisibility, TokenScope, get_version};
+use modhost_db_util::{projects::get_full_project, relations::get_relations};
+use modhost_server_core::state::AppState;
+
+/// List Version Relations
+///
+/// List the dependencies and incompatibilities of a project version.
+#[utoipa::path(
+    get,
+    path = "/{version}/relations",
+    tag = "Versions",
+    responses(
+        (status = 200, description = "Found relations!", body = Vec<ProjectRelation>),
+        (status = INTERNAL_SERVER_ERROR, description = "An internal error occured!"),
+    ),
+    params(
+        ("version" = String, Path, description = "The version ID/name/number."),
+    ),
+)]
+#[debug_handler]
+pub async fn list_handler(
+    jar: CookieJar,
+    headers: HeaderMap,
+    Path((project, version)): Path<(String, String)>,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<ProjectRelation>>> {
+    let mut conn = state.pool.get().await?;
+    let pkg = get_full_project(project, &mut conn).await?;
+
+    if pkg.visibility == ProjectVisibility::Private {
+        match get_user_from_req(&jar, &headers, TokenScope::Read, &mut conn).await {
+            Ok(user) => {
+                if !pkg.authors.iter().any(|v| v.id == user.id) && !user.admin {
+                    return Err(AppError::NotFound);
+                }
+            }
+
+            Err(_) => return Err(AppError::NotFound),
+        }
+    }
+
+    let ver = get_version(pkg.id, version, &mut conn).await?;
+
+    Ok(Json(get_relations(&[ver.id], &mut conn).await?))
+}
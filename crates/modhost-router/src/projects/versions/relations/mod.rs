@@ -0,0 +1,5 @@
+//! Routes concerning the relations (dependencies and incompatibilities) of a project version.
+
+pub mod create;
+pub mod delete;
+pub mod list;
@@ -1,9 +1,10 @@
 //! The version update route.
 
+use crate::util::metadata::{MetadataQueryParams, validate_metadata};
 use axum::{
     Json,
     body::Body,
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::{HeaderMap, StatusCode},
     response::Response,
 };
@@ -13,10 +14,12 @@ use diesel::{ExpressionMethods, QueryDsl, SelectableHelper, update};
 use diesel_async::RunQueryDsl;
 use modhost_auth::get_user_from_req;
 use modhost_core::Result;
-use modhost_db::{ProjectAuthor, ProjectVersion, get_version, project_authors, project_versions};
-use modhost_db_util::projects::get_project;
-use modhost_server_core::state::AppState;
-use semver::Version;
+use modhost_db::{
+    ProjectAuthor, ProjectVersion, TokenScope, VersionChannel, get_version, project_authors,
+    project_versions,
+};
+use modhost_db_util::{projects::get_project, scheme::validate_version_number};
+use modhost_server_core::{models::expand_game_versions, state::AppState};
 
 /// Information for updaing a project version.
 #[derive(
@@ -28,7 +31,7 @@ pub struct PartialProjectVersion {
     pub name: Option<String>,
 
     /// The version number.
-    /// This must be a string confirming to the [SemVer](https://semver.org/) standard.
+    /// This must be valid under the server's configured version scheme.
     #[serde(default)]
     pub version_number: Option<String>,
 
@@ -41,8 +44,13 @@ pub struct PartialProjectVersion {
     pub loaders: Option<Vec<String>>,
 
     /// The game versions this version works on.
+    /// Items can be ranges or wildcards, like `1.20.x`, `>=1.20 <1.21`, or `1.19.2..1.20.1`.
     #[serde(default)]
     pub game_versions: Option<Vec<String>>,
+
+    /// The release channel of the version.
+    #[serde(default)]
+    pub channel: Option<VersionChannel>,
 }
 
 /// Update Project Version
@@ -54,10 +62,12 @@ pub struct PartialProjectVersion {
     tag = "Versions",
     responses(
         (status = 200, description = "Updated project version!", body = ProjectVersion),
+        (status = 400, description = "Some loaders or game versions aren't registered!"),
         (status = INTERNAL_SERVER_ERROR, description = "An internal error occured!"),
     ),
     params(
         ("version" = String, Path, description = "The version ID/name/number."),
+        ("skip_validation" = Option<bool>, Query, description = "Accept unregistered loaders and game versions (admins only)."),
     ),
     request_body(content = PartialProjectVersion, description = "The information to update"),
     security(
@@ -69,11 +79,12 @@ pub async fn update_handler(
     jar: CookieJar,
     headers: HeaderMap,
     Path((project, version)): Path<(String, String)>,
+    Query(meta): Query<MetadataQueryParams>,
     State(state): State<AppState>,
     Json(data): Json<PartialProjectVersion>,
 ) -> Result<Response> {
     let mut conn = state.pool.get().await?;
-    let user = get_user_from_req(&jar, &headers, &mut conn).await?;
+    let user = get_user_from_req(&jar, &headers, TokenScope::VersionUpload, &mut conn).await?;
     let pkg = get_project(project, &mut conn).await?;
     let ver = get_version(pkg.id, version, &mut conn).await?;
 
@@ -89,8 +100,22 @@ pub async fn update_handler(
             .body(Body::empty())?);
     }
 
+    let game_versions = data
+        .game_versions
+        .map(|v| expand_game_versions(v, &state.game_versions))
+        .transpose()?;
+
+    if !meta.skip(&user)? {
+        validate_metadata(
+            &state,
+            data.loaders.iter().flatten(),
+            game_versions.iter().flatten(),
+            [],
+        )?;
+    }
+
     if let Some(ver_num) = &data.version_number {
-        Version::parse(ver_num)?;
+        validate_version_number(state.config.server.version_scheme, ver_num)?;
     }
 
     let ver = update(project_versions::table)
@@ -103,10 +128,10 @@ pub async fn update_handler(
                 .loaders
                 .map(|v| v.iter().map(|v| Some(v.clone())).collect::<Vec<_>>())
                 .unwrap_or(ver.loaders)),
-            project_versions::game_versions.eq(data
-                .game_versions
-                .map(|v| v.iter().map(|v| Some(v.clone())).collect::<Vec<_>>())
+            project_versions::game_versions.eq(game_versions
+                .map(|v| v.into_iter().map(Some).collect::<Vec<_>>())
                 .unwrap_or(ver.game_versions)),
+            project_versions::channel.eq(data.channel.unwrap_or(ver.channel)),
             project_versions::updated_at.eq(Utc::now().naive_utc()),
         ))
         .returning(ProjectVersion::as_select())
@@ -0,0 +1,230 @@
+//! The dependency resolution route.
+
+use crate::util::{
+    access::{can_see_blocked, check_not_blocked},
+    resolve::{Requirement, ResolveProblem},
+};
+use axum::{
+    Json,
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use axum_extra::extract::CookieJar;
+use modhost_auth::get_user_from_req;
+use modhost_core::{AppError, Result};
+use modhost_db::{
+    ProjectData, ProjectVersionData, ProjectVisibility, RelationKind, TokenScope, User,
+};
+use modhost_db_util::{
+    projects::get_full_project,
+    vers::{get_full_version, get_versions},
+};
+use modhost_server_core::state::AppState;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// The maximum number of projects that are loaded for a single resolution.
+pub const MAX_PROJECTS: usize = 500;
+
+/// A project to include in a resolution.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema, ToResponse)]
+pub struct ResolveTarget {
+    /// The project's ID or slug.
+    pub project: String,
+
+    /// A version ID, name, or number to pin the project to.
+    /// Pinned versions are used even if they don't match the loader or game version.
+    #[serde(default)]
+    pub version: Option<String>,
+}
+
+/// A request to resolve the dependencies of a set of projects.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema, ToResponse)]
+pub struct ResolveRequest {
+    /// The projects to include.
+    pub projects: Vec<ResolveTarget>,
+
+    /// The loader that every version must support.
+    #[serde(default)]
+    pub loader: Option<String>,
+
+    /// The game version that every version must support.
+    #[serde(default)]
+    pub game_version: Option<String>,
+}
+
+/// A version chosen by the resolver.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema, ToResponse)]
+pub struct ResolvedVersion {
+    /// The project.
+    pub project: ProjectData,
+
+    /// The chosen version.
+    pub version: ProjectVersionData,
+}
+
+/// An explanation of why a resolution failed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema, ToResponse)]
+pub struct ResolveConflict {
+    /// A description of the conflict.
+    pub message: String,
+}
+
+/// Resolve Dependencies
+///
+/// Find a set of versions (one per project) that includes the requested projects and
+/// their dependencies, supports the given loader and game version, and has no
+/// incompatibilities. Newer versions are preferred.
+/// Versions blocked by a scan rule are skipped, except for moderators.
+#[utoipa::path(
+    post,
+    path = "/",
+    tag = "Versions",
+    request_body(content = ResolveRequest, description = "The projects to resolve"),
+    responses(
+        (status = 200, description = "Resolved the dependencies!", body = Vec<ResolvedVersion>),
+        (status = 403, description = "A pinned version is blocked until a moderator reviews it!"),
+        (status = 409, description = "There is no solution!", body = ResolveConflict),
+        (status = INTERNAL_SERVER_ERROR, description = "An internal error occured!"),
+    ),
+)]
+#[debug_handler]
+pub async fn resolve_handler(
+    jar: CookieJar,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Json(req): Json<ResolveRequest>,
+) -> Result<Response> {
+    let mut conn = state.pool.get().await?;
+    let user = get_user_from_req(&jar, &headers, TokenScope::Read, &mut conn)
+        .await
+        .ok();
+    let mut projects = HashMap::new();
+    let mut problem = ResolveProblem::default();
+    let mut roots = Vec::new();
+    let mut queue = VecDeque::new();
+    let mut seen = HashSet::new();
+
+    for target in req.projects {
+        let pkg = get_full_project(target.project, &mut conn).await?;
+
+        if !is_visible(&pkg, user.as_ref()) {
+            return Err(AppError::NotFound);
+        }
+
+        let version = match target.version {
+            Some(ver) => {
+                let ver = get_full_version(pkg.id, ver, &mut conn).await?;
+
+                check_not_blocked(ver.blocked, user.as_ref())?;
+
+                problem.candidates.insert(pkg.id, vec![ver.clone()]);
+                queue.extend(dependencies(&ver));
+                seen.insert(pkg.id);
+
+                Some(ver.id)
+            }
+
+            None => {
+                queue.push_back(pkg.id);
+                None
+            }
+        };
+
+        roots.push(Requirement {
+            project: pkg.id,
+            version,
+            required_by: None,
+        });
+
+        problem.names.insert(pkg.id, pkg.slug.clone());
+        projects.insert(pkg.id, pkg);
+    }
+
+    let include_blocked = can_see_blocked(user.as_ref());
+
+    while let Some(id) = queue.pop_front() {
+        if !seen.insert(id) {
+            continue;
+        }
+
+        if seen.len() > MAX_PROJECTS {
+            return Ok(conflict(format!(
+                "Too many projects are involved (the maximum is {})!",
+                MAX_PROJECTS
+            )));
+        }
+
+        let pkg = match projects.get(&id) {
+            Some(pkg) => pkg.clone(),
+
+            None => get_full_project(id.to_string(), &mut conn).await?,
+        };
+
+        if !is_visible(&pkg, user.as_ref()) {
+            continue;
+        }
+
+        let versions = get_versions(pkg.id, state.config.server.version_scheme, &mut conn)
+            .await?
+            .into_iter()
+            .filter(|v| {
+                (include_blocked || !v.blocked)
+                    && req
+                        .loader
+                        .as_ref()
+                        .is_none_or(|loader| v.loaders.iter().flatten().any(|v| v == loader))
+                    && req
+                        .game_version
+                        .as_ref()
+                        .is_none_or(|game| v.game_versions.iter().flatten().any(|v| v == game))
+            })
+            .collect::<Vec<_>>();
+
+        for ver in &versions {
+            queue.extend(dependencies(ver));
+        }
+
+        problem.names.insert(pkg.id, pkg.slug.clone());
+        problem.candidates.insert(pkg.id, versions);
+        projects.insert(pkg.id, pkg);
+    }
+
+    let solution = match problem.solve(&roots) {
+        Ok(solution) => solution,
+        Err(message) => return Ok(conflict(message)),
+    };
+
+    Ok(Json(
+        solution
+            .into_iter()
+            .filter_map(|ver| {
+                Some(ResolvedVersion {
+                    project: projects.get(&ver.project)?.clone(),
+                    version: ver.clone(),
+                })
+            })
+            .collect::<Vec<_>>(),
+    )
+    .into_response())
+}
+
+/// Check whether a user can see a project.
+fn is_visible(pkg: &ProjectData, user: Option<&User>) -> bool {
+    pkg.visibility != ProjectVisibility::Private
+        || user.is_some_and(|user| user.admin || pkg.authors.iter().any(|v| v.id == user.id))
+}
+
+/// Get the IDs of the projects a version depends on.
+fn dependencies(ver: &ProjectVersionData) -> Vec<i32> {
+    ver.relations
+        .iter()
+        .filter(|v| v.kind == RelationKind::Dependency)
+        .map(|v| v.target_project)
+        .collect()
+}
+
+/// Create a conflict response.
+fn conflict(message: String) -> Response {
+    (StatusCode::CONFLICT, Json(ResolveConflict { message })).into_response()
+}
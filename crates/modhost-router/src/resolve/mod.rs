@@ -0,0 +1,20 @@
+//! Routes concerning dependency resolution.
+
+use axum::{Router, routing::post};
+use modhost_server_core::state::AppState;
+
+pub mod deps;
+
+/// Register dependency resolution API routes.
+/// Should be nested at `/api/v1/resolve`.
+pub fn router(state: AppState) -> Router<AppState> {
+    Router::new()
+        .route("/", post(deps::resolve_handler))
+        .with_state(state)
+}
+
+/// The spec for the dependency resolution API.
+/// Should be nested at `/api/v1/resolve`.
+#[derive(OpenApi)]
+#[openapi(paths(deps::resolve_handler))]
+pub struct ResolveApi;
@@ -0,0 +1,90 @@
+//! Routes concerning the current user's linked identities.
+
+use axum::{
+    Json,
+    body::Body,
+    extract::{Path, State},
+    http::HeaderMap,
+    response::Response,
+};
+use axum_extra::extract::CookieJar;
+use diesel::ExpressionMethods;
+use diesel_async::RunQueryDsl;
+use modhost_auth::get_user_from_req;
+use modhost_core::{AppError, Result};
+use modhost_db::{TokenScope, UserIdentity, get_user_identities, user_identities};
+use modhost_server_core::state::AppState;
+
+/// List Identities
+///
+/// List the accounts from auth providers that are linked to the current user.
+/// More can be linked by logging in with another provider while authenticated.
+#[utoipa::path(
+    get,
+    path = "/me/identities",
+    tag = "Users",
+    responses(
+        (status = 200, description = "Got the identities!", body = Vec<UserIdentity>),
+        (status = INTERNAL_SERVER_ERROR, description = "An internal error occured! Are you authenticated?"),
+    ),
+    security(
+        ("api_auth_token" = []),
+    ),
+)]
+#[debug_handler]
+pub async fn list_handler(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    headers: HeaderMap,
+) -> Result<Json<Vec<UserIdentity>>> {
+    let mut conn = state.pool.get().await?;
+    let user = get_user_from_req(&jar, &headers, TokenScope::Account, &mut conn).await?;
+
+    Ok(Json(get_user_identities(&user, &mut conn).await?))
+}
+
+/// Unlink Identity
+///
+/// Unlink an account from the current user. A user's only identity can't be unlinked.
+#[utoipa::path(
+    delete,
+    path = "/me/identities/{identity}",
+    tag = "Users",
+    params(
+        ("identity" = i32, Path, description = "The identity ID."),
+    ),
+    responses(
+        (status = 200, description = "Unlinked the identity!", body = String),
+        (status = 400, description = "This is the user's only identity!"),
+        (status = INTERNAL_SERVER_ERROR, description = "An internal error occured! Are you authenticated?"),
+    ),
+    security(
+        ("api_auth_token" = []),
+    ),
+)]
+#[debug_handler]
+pub async fn unlink_handler(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    headers: HeaderMap,
+    Path(id): Path<i32>,
+) -> Result<Response> {
+    let mut conn = state.pool.get().await?;
+    let user = get_user_from_req(&jar, &headers, TokenScope::Account, &mut conn).await?;
+    let identities = get_user_identities(&user, &mut conn).await?;
+
+    if !identities.iter().any(|v| v.id == id) {
+        return Err(AppError::NotFound);
+    }
+
+    if identities.len() == 1 {
+        return Err(AppError::LastIdentity);
+    }
+
+    diesel::delete(user_identities::table)
+        .filter(user_identities::id.eq(id))
+        .execute(&mut conn)
+        .await?;
+
+    Ok(Response::builder().body(Body::new("Unlinked identity successfully!".to_string()))?)
+}
@@ -4,7 +4,7 @@ use axum::{body::Body, extract::State, http::HeaderMap, response::Response};
 use axum_extra::extract::CookieJar;
 use modhost_auth::get_user_from_req;
 use modhost_core::Result;
-use modhost_db::User;
+use modhost_db::{TokenScope, User};
 use modhost_server_core::state::AppState;
 
 /// Current User
@@ -30,7 +30,13 @@ pub async fn me_handler(
 ) -> Result<Response> {
     Ok(
         Response::builder().body(Body::new(serde_json::to_string_pretty(
-            &get_user_from_req(&jar, &headers, &mut state.pool.get().await?).await?,
+            &get_user_from_req(
+                &jar,
+                &headers,
+                TokenScope::Read,
+                &mut state.pool.get().await?,
+            )
+            .await?,
         )?))?,
     )
 }
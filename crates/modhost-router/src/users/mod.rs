@@ -1,10 +1,16 @@
 //! User-related routes.
 
+pub mod identities;
 pub mod info;
 pub mod me;
 pub mod pkg;
+pub mod sessions;
+pub mod tokens;
 
-use axum::{Router, routing::get};
+use axum::{
+    Router,
+    routing::{delete, get, put},
+};
 use modhost_server_core::state::AppState;
 
 /// Register user-related endpoints.
@@ -12,6 +18,16 @@ use modhost_server_core::state::AppState;
 pub fn router(state: AppState) -> Router<AppState> {
     Router::new()
         .route("/me", get(me::me_handler))
+        .route("/me/identities", get(identities::list_handler))
+        .route(
+            "/me/identities/{identity}",
+            delete(identities::unlink_handler),
+        )
+        .route("/me/sessions", get(sessions::list_handler))
+        .route("/me/sessions/{session}", delete(sessions::revoke_handler))
+        .route("/me/tokens", get(tokens::list_handler))
+        .route("/me/tokens", put(tokens::create_handler))
+        .route("/me/tokens/{token}", delete(tokens::revoke_handler))
         .route("/{id}", get(info::info_handler))
         .route("/{id}/projects", get(pkg::list_handler))
         .with_state(state)
@@ -20,5 +36,16 @@ pub fn router(state: AppState) -> Router<AppState> {
 /// The spec for the users API.
 /// Should be nested at `/api/v1/users`.
 #[derive(OpenApi)]
-#[openapi(paths(me::me_handler, info::info_handler, pkg::list_handler,))]
+#[openapi(paths(
+    me::me_handler,
+    identities::list_handler,
+    identities::unlink_handler,
+    sessions::list_handler,
+    sessions::revoke_handler,
+    tokens::list_handler,
+    tokens::create_handler,
+    tokens::revoke_handler,
+    info::info_handler,
+    pkg::list_handler,
+))]
 pub struct UsersApi;
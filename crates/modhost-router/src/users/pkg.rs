@@ -8,7 +8,7 @@ use axum::{
 use axum_extra::extract::CookieJar;
 use modhost_auth::get_user_from_req;
 use modhost_core::Result;
-use modhost_db::{ProjectData, get_user};
+use modhost_db::{ProjectData, TokenScope, get_user};
 use modhost_db_util::users::get_user_projects;
 use modhost_server_core::state::AppState;
 
@@ -39,7 +39,9 @@ pub async fn list_handler(
 
     Ok(Json(
         get_user_projects(
-            get_user_from_req(&jar, &headers, &mut conn).await.ok(),
+            get_user_from_req(&jar, &headers, TokenScope::Read, &mut conn)
+                .await
+                .ok(),
             user.id,
             &mut conn,
         )
@@ -0,0 +1,101 @@
+//! Routes concerning the current user's login sessions.
+
+use axum::{
+    Json,
+    body::Body,
+    extract::{Path, State},
+    http::HeaderMap,
+    response::Response,
+};
+use axum_extra::extract::CookieJar;
+use modhost_auth::{get_session_from_req, get_user_from_req};
+use modhost_core::{AppError, Result};
+use modhost_db::{TokenScope, UserToken, get_sessions, revoke_session};
+use modhost_server_core::state::AppState;
+
+/// A login session.
+#[derive(
+    Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, ToSchema, ToResponse, Serialize, Deserialize,
+)]
+pub struct UserSession {
+    /// The session's info.
+    #[serde(flatten)]
+    pub session: UserToken,
+
+    /// Whether this is the session the request was made with.
+    pub current: bool,
+}
+
+/// List Sessions
+///
+/// List the current user's active login sessions.
+#[utoipa::path(
+    get,
+    path = "/me/sessions",
+    tag = "Users",
+    responses(
+        (status = 200, description = "Got the sessions!", body = Vec<UserSession>),
+        (status = INTERNAL_SERVER_ERROR, description = "An internal error occured! Are you authenticated?"),
+    ),
+    security(
+        ("api_auth_token" = []),
+    ),
+)]
+#[debug_handler]
+pub async fn list_handler(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    headers: HeaderMap,
+) -> Result<Json<Vec<UserSession>>> {
+    let mut conn = state.pool.get().await?;
+    let user = get_user_from_req(&jar, &headers, TokenScope::Account, &mut conn).await?;
+    let current = get_session_from_req(&jar, &headers, &mut conn)
+        .await?
+        .map(|v| v.id);
+
+    Ok(Json(
+        get_sessions(&user, &mut conn)
+            .await?
+            .into_iter()
+            .map(|session| UserSession {
+                current: current == Some(session.id),
+                session,
+            })
+            .collect(),
+    ))
+}
+
+/// Revoke Session
+///
+/// End one of the current user's sessions, like one on a lost device.
+#[utoipa::path(
+    delete,
+    path = "/me/sessions/{session}",
+    tag = "Users",
+    params(
+        ("session" = i32, Path, description = "The session ID."),
+    ),
+    responses(
+        (status = 200, description = "Revoked the session!", body = String),
+        (status = INTERNAL_SERVER_ERROR, description = "An internal error occured! Are you authenticated?"),
+    ),
+    security(
+        ("api_auth_token" = []),
+    ),
+)]
+#[debug_handler]
+pub async fn revoke_handler(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    headers: HeaderMap,
+    Path(id): Path<i32>,
+) -> Result<Response> {
+    let mut conn = state.pool.get().await?;
+    let user = get_user_from_req(&jar, &headers, TokenScope::Account, &mut conn).await?;
+
+    if !revoke_session(user.id, id, &mut conn).await? {
+        return Err(AppError::NotFound);
+    }
+
+    Ok(Response::builder().body(Body::new("Revoked session successfully!".to_string()))?)
+}
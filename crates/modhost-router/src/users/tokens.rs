@@ -0,0 +1,183 @@
+//! Routes concerning the current user's personal access tokens.
+
+use axum::{
+    Json,
+    body::Body,
+    extract::{Path, State},
+    http::HeaderMap,
+    response::Response,
+};
+use axum_extra::extract::CookieJar;
+use chrono::{NaiveDateTime, Utc};
+use diesel::ExpressionMethods;
+use diesel_async::RunQueryDsl;
+use itertools::Itertools;
+use modhost_auth::{get_personal_token_from_req, get_user_from_req};
+use modhost_core::{AppError, Result};
+use modhost_db::{
+    PersonalAccessToken, TokenScope, create_personal_token, get_personal_tokens,
+    personal_access_tokens,
+};
+use modhost_server_core::state::AppState;
+
+/// The data for creating a personal access token.
+#[derive(
+    Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, ToSchema, ToResponse, Serialize, Deserialize,
+)]
+pub struct PersonalAccessTokenInit {
+    /// The token's name.
+    pub name: String,
+
+    /// The scopes to grant the token.
+    pub scopes: Vec<TokenScope>,
+
+    /// When the token expires. If this is missing, it never expires.
+    #[serde(default)]
+    pub expires: Option<NaiveDateTime>,
+}
+
+/// A newly created personal access token.
+#[derive(
+    Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, ToSchema, ToResponse, Serialize, Deserialize,
+)]
+pub struct CreatedPersonalAccessToken {
+    /// The token's info.
+    #[serde(flatten)]
+    pub token: PersonalAccessToken,
+
+    /// The token itself. This is only ever shown once!
+    pub value: String,
+}
+
+/// List Tokens
+///
+/// List the current user's personal access tokens.
+#[utoipa::path(
+    get,
+    path = "/me/tokens",
+    tag = "Users",
+    responses(
+        (status = 200, description = "Got the tokens!", body = Vec<PersonalAccessToken>),
+        (status = INTERNAL_SERVER_ERROR, description = "An internal error occured! Are you authenticated?"),
+    ),
+    security(
+        ("api_auth_token" = []),
+    ),
+)]
+#[debug_handler]
+pub async fn list_handler(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    headers: HeaderMap,
+) -> Result<Json<Vec<PersonalAccessToken>>> {
+    let mut conn = state.pool.get().await?;
+    let user = get_user_from_req(&jar, &headers, TokenScope::Account, &mut conn).await?;
+
+    Ok(Json(get_personal_tokens(&user, &mut conn).await?))
+}
+
+/// Create Token
+///
+/// Create a personal access token for the current user.
+/// The token's value is only returned here, so make sure to save it!
+/// Tokens can't create other tokens with scopes they don't have themselves,
+/// or that expire after they do.
+#[utoipa::path(
+    put,
+    path = "/me/tokens",
+    tag = "Users",
+    request_body(content = PersonalAccessTokenInit, description = "The token's data"),
+    responses(
+        (status = 200, description = "Created the token!", body = CreatedPersonalAccessToken),
+        (status = 400, description = "The token's scopes or expiration date were invalid!"),
+        (status = INTERNAL_SERVER_ERROR, description = "An internal error occured! Are you authenticated?"),
+    ),
+    security(
+        ("api_auth_token" = []),
+    ),
+)]
+#[debug_handler]
+pub async fn create_handler(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    headers: HeaderMap,
+    Json(body): Json<PersonalAccessTokenInit>,
+) -> Result<Json<CreatedPersonalAccessToken>> {
+    let mut conn = state.pool.get().await?;
+    let user = get_user_from_req(&jar, &headers, TokenScope::Account, &mut conn).await?;
+    let scopes = body.scopes.into_iter().sorted().dedup().collect_vec();
+
+    if body.name.trim().is_empty() {
+        return Err(AppError::MissingField("name".into()));
+    }
+
+    if scopes.is_empty() {
+        return Err(AppError::NoScopes);
+    }
+
+    if let Some(current) = get_personal_token_from_req(&jar, &headers, &mut conn).await? {
+        if let Some(scope) = scopes.iter().find(|v| !current.has_scope(**v)) {
+            return Err(AppError::MissingScope(scope.to_string()));
+        }
+
+        // Otherwise, a short-lived token could make itself permanent.
+        if let Some(limit) = current.expires
+            && body.expires.is_none_or(|expires| expires > limit)
+        {
+            return Err(AppError::OutlivesToken);
+        }
+    }
+
+    if body
+        .expires
+        .is_some_and(|expires| expires <= Utc::now().naive_utc())
+    {
+        return Err(AppError::InvalidExpiration);
+    }
+
+    let (token, value) =
+        create_personal_token(user.id, body.name.trim(), scopes, body.expires, &mut conn).await?;
+
+    Ok(Json(CreatedPersonalAccessToken { token, value }))
+}
+
+/// Revoke Token
+///
+/// Revoke one of the current user's personal access tokens.
+#[utoipa::path(
+    delete,
+    path = "/me/tokens/{token}",
+    tag = "Users",
+    params(
+        ("token" = i32, Path, description = "The token ID."),
+    ),
+    responses(
+        (status = 200, description = "Revoked the token!", body = String),
+        (status = INTERNAL_SERVER_ERROR, description = "An internal error occured! Are you authenticated?"),
+    ),
+    security(
+        ("api_auth_token" = []),
+    ),
+)]
+#[debug_handler]
+pub async fn revoke_handler(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    headers: HeaderMap,
+    Path(id): Path<i32>,
+) -> Result<Response> {
+    let mut conn = state.pool.get().await?;
+    let user = get_user_from_req(&jar, &headers, TokenScope::Account, &mut conn).await?;
+
+    let deleted = diesel::delete(personal_access_tokens::table)
+        .filter(personal_access_tokens::id.eq(id))
+        .filter(personal_access_tokens::user_id.eq(user.id))
+        .execute(&mut conn)
+        .await?;
+
+    if deleted == 0 {
+        return Err(AppError::NotFound);
+    }
+
+    Ok(Response::builder().body(Body::new("Revoked token successfully!".to_string()))?)
+}
@@ -0,0 +1,39 @@
+//! Utilities for checking what a user can see.
+
+use modhost_core::{AppError, Result};
+use modhost_db::{DbConn, ProjectData, ProjectVisibility, User};
+use modhost_db_util::projects::get_full_project;
+
+/// Get a project, making sure the user can see it.
+/// Private projects can only be seen by their authors and admins.
+pub async fn get_visible_project(
+    project: String,
+    user: Option<&User>,
+    conn: &mut DbConn,
+) -> Result<ProjectData> {
+    let pkg = get_full_project(project, conn).await?;
+
+    if pkg.visibility == ProjectVisibility::Private
+        && !user.is_some_and(|user| user.admin || pkg.authors.iter().any(|v| v.id == user.id))
+    {
+        return Err(AppError::NotFound);
+    }
+
+    Ok(pkg)
+}
+
+/// Check whether the user can see versions blocked by a scan rule.
+/// Only moderators (and admins) can, so they can review them.
+pub fn can_see_blocked(user: Option<&User>) -> bool {
+    user.is_some_and(|user| user.admin || user.moderator)
+}
+
+/// Make sure the user can see the contents of a version.
+/// Versions blocked by a scan rule can only be seen by moderators.
+pub fn check_not_blocked(blocked: bool, user: Option<&User>) -> Result<()> {
+    if blocked && !can_see_blocked(user) {
+        return Err(AppError::VersionBlocked);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,331 @@
+//! Utilities for reading the contents of archives.
+
+use flate2::read::GzDecoder;
+use modhost_core::{AppError, Result};
+use modhost_db::NewArchiveEntry;
+use sha1::{Digest, Sha1};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::{BufReader, Read, Seek},
+};
+use tar::Archive;
+use zip::ZipArchive;
+
+/// How many bytes are checked when deciding whether a file is binary.
+const BINARY_CHECK_SIZE: usize = 8000;
+
+/// The kinds of archives that can be opened.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveKind {
+    /// A `.zip` or `.jar` file.
+    Zip,
+
+    /// A `.tar.gz` or `.tgz` file.
+    TarGz,
+}
+
+impl ArchiveKind {
+    /// Figure out what kind of archive a file is, from its name and contents.
+    pub fn detect(name: &str, data: &[u8]) -> Option<Self> {
+        let name = name.to_lowercase();
+
+        if name.ends_with(".zip") || name.ends_with(".jar") || data.starts_with(b"PK\x03\x04") {
+            Some(Self::Zip)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(Self::TarGz)
+        } else {
+            None
+        }
+    }
+}
+
+/// The contents of an archive entry, which may have been cut short.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntryContents {
+    /// The entry's contents.
+    pub data: Vec<u8>,
+
+    /// Whether the contents were cut short because the entry is too large.
+    pub truncated: bool,
+}
+
+/// A hashed archive entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HashedEntry {
+    /// The entry's unpacked size in bytes.
+    pub size: u64,
+
+    /// The SHA-1 hash of the entry's contents.
+    pub sha1: String,
+
+    /// The entry's contents, if it's small enough to be kept.
+    pub contents: Option<Vec<u8>>,
+}
+
+/// Turn an error from reading an archive into an [`AppError`].
+fn unreadable(err: impl ToString) -> AppError {
+    AppError::UnreadableArchive(err.to_string())
+}
+
+/// Open a gzipped tarball, refusing to unpack more than `max_unpacked` bytes.
+fn open_tar(reader: impl Read, max_unpacked: u64) -> Archive<impl Read> {
+    Archive::new(GzDecoder::new(BufReader::new(reader)).take(max_unpacked))
+}
+
+/// List the entries of an archive (without descending into nested archives).
+/// At most `max_entries` entries are listed, and tarballs are only unpacked
+/// up to `max_unpacked` bytes, to keep archive bombs from hogging the server.
+pub fn list_entries(
+    kind: ArchiveKind,
+    file_id: i32,
+    reader: impl Read + Seek,
+    max_entries: usize,
+    max_unpacked: u64,
+) -> Result<Vec<NewArchiveEntry>> {
+    let mut entries = Vec::new();
+
+    match kind {
+        ArchiveKind::Zip => {
+            let mut archive = ZipArchive::new(reader).map_err(unreadable)?;
+
+            for idx in 0..archive.len().min(max_entries) {
+                let entry = archive.by_index_raw(idx).map_err(unreadable)?;
+
+                entries.push(NewArchiveEntry {
+                    file_id,
+                    path: entry.name().to_string(),
+                    size: entry.size() as i64,
+                    compressed_size: Some(entry.compressed_size() as i64),
+                    directory: entry.is_dir(),
+                });
+            }
+        }
+
+        ArchiveKind::TarGz => {
+            let mut archive = open_tar(reader, max_unpacked);
+
+            for entry in archive.entries().map_err(unreadable)?.take(max_entries) {
+                let entry = entry.map_err(unreadable)?;
+                let kind = entry.header().entry_type();
+
+                entries.push(NewArchiveEntry {
+                    file_id,
+                    path: String::from_utf8_lossy(&entry.path_bytes()).to_string(),
+                    size: entry.size() as i64,
+                    compressed_size: None,
+                    directory: kind.is_dir(),
+                });
+            }
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Read up to `limit` bytes of an archive entry.
+/// Returns [`None`] if the archive doesn't contain the entry.
+pub fn read_entry(
+    kind: ArchiveKind,
+    reader: impl Read + Seek,
+    path: &str,
+    limit: usize,
+    max_unpacked: u64,
+) -> Result<Option<EntryContents>> {
+    let mut buf = Vec::new();
+
+    match kind {
+        ArchiveKind::Zip => {
+            let mut archive = ZipArchive::new(reader).map_err(unreadable)?;
+
+            let entry = match archive.by_name(path) {
+                Ok(it) => it,
+                Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+                Err(err) => return Err(unreadable(err)),
+            };
+
+            entry
+                .take(limit as u64 + 1)
+                .read_to_end(&mut buf)
+                .map_err(unreadable)?;
+        }
+
+        ArchiveKind::TarGz => {
+            let mut archive = open_tar(reader, max_unpacked);
+            let mut found = false;
+
+            for entry in archive.entries().map_err(unreadable)? {
+                let entry = entry.map_err(unreadable)?;
+
+                if *entry.path_bytes() != *path.as_bytes() {
+                    continue;
+                }
+
+                entry
+                    .take(limit as u64 + 1)
+                    .read_to_end(&mut buf)
+                    .map_err(unreadable)?;
+
+                found = true;
+                break;
+            }
+
+            if !found {
+                return Ok(None);
+            }
+        }
+    }
+
+    let truncated = buf.len() > limit;
+
+    buf.truncate(limit);
+
+    Ok(Some(EntryContents {
+        data: buf,
+        truncated,
+    }))
+}
+
+/// Hash an entry, keeping its contents if it's at most `keep` bytes.
+/// `budget` is the number of bytes that can still be unpacked, and is
+/// reduced by the entry's size.
+fn hash_entry(mut entry: impl Read, keep: usize, budget: &mut u64) -> Result<HashedEntry> {
+    let mut hasher = Sha1::new();
+    let mut contents = Vec::new();
+    let mut buf = [0; 8192];
+    let mut size = 0;
+
+    loop {
+        let read = entry.read(&mut buf).map_err(unreadable)?;
+
+        if read == 0 {
+            break;
+        }
+
+        size += read as u64;
+
+        if size > *budget {
+            return Err(AppError::UnreadableArchive(
+                "The archive unpacks to too much data!".into(),
+            ));
+        }
+
+        hasher.update(&buf[..read]);
+
+        if size <= keep as u64 {
+            contents.extend_from_slice(&buf[..read]);
+        }
+    }
+
+    *budget -= size;
+
+    Ok(HashedEntry {
+        size,
+        sha1: format!("{:x}", hasher.finalize()),
+        contents: (size <= keep as u64).then_some(contents),
+    })
+}
+
+/// Hash the files in an archive (without descending into nested archives),
+/// keeping the contents of the ones that are at most `keep` bytes.
+/// Entries are hashed as they're unpacked, so only the kept contents stay in memory.
+/// If `only` is given, every other entry is skipped.
+/// Zip entries are looked up by name, so skipped entries aren't unpacked at all.
+/// If the file isn't an archive, it's treated as an archive containing
+/// only itself. The same limits as [`list_entries`] apply.
+pub fn hash_entries(
+    file_name: &str,
+    mut reader: impl Read + Seek,
+    keep: usize,
+    only: Option<&BTreeSet<String>>,
+    max_entries: usize,
+    max_unpacked: u64,
+) -> Result<BTreeMap<String, HashedEntry>> {
+    let mut entries = BTreeMap::new();
+    let mut budget = max_unpacked;
+    let mut magic = Vec::new();
+    let wanted = |name: &str| only.is_none_or(|v| v.contains(name));
+
+    (&mut reader)
+        .take(4)
+        .read_to_end(&mut magic)
+        .map_err(unreadable)?;
+
+    reader.rewind().map_err(unreadable)?;
+
+    match ArchiveKind::detect(file_name, &magic) {
+        Some(ArchiveKind::Zip) => {
+            let mut archive = ZipArchive::new(reader).map_err(unreadable)?;
+
+            let indices = match only {
+                Some(only) => only
+                    .iter()
+                    .filter_map(|name| archive.index_for_name(name))
+                    .collect::<Vec<_>>(),
+
+                None => (0..archive.len()).collect(),
+            };
+
+            for idx in indices.into_iter().take(max_entries) {
+                let entry = archive.by_index(idx).map_err(unreadable)?;
+
+                if entry.is_dir() {
+                    continue;
+                }
+
+                let name = entry.name().to_string();
+
+                entries.insert(name, hash_entry(entry, keep, &mut budget)?);
+            }
+        }
+
+        Some(ArchiveKind::TarGz) => {
+            let mut archive = open_tar(reader, max_unpacked);
+
+            for entry in archive.entries().map_err(unreadable)?.take(max_entries) {
+                let entry = entry.map_err(unreadable)?;
+
+                if !entry.header().entry_type().is_file() {
+                    continue;
+                }
+
+                let name = String::from_utf8_lossy(&entry.path_bytes()).to_string();
+
+                if !wanted(&name) {
+                    continue;
+                }
+
+                entries.insert(name, hash_entry(entry, keep, &mut budget)?);
+            }
+        }
+
+        None => {
+            if wanted(file_name) {
+                entries.insert(
+                    file_name.to_string(),
+                    hash_entry(reader, keep, &mut budget)?,
+                );
+            }
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Try to read some (possibly truncated) contents as text.
+/// Returns [`None`] if they look like binary data.
+pub fn as_text(data: &[u8], truncated: bool) -> Option<String> {
+    if data[..data.len().min(BINARY_CHECK_SIZE)].contains(&0) {
+        return None;
+    }
+
+    match std::str::from_utf8(data) {
+        Ok(text) => Some(text.to_string()),
+
+        // A truncated file may end in the middle of a character.
+        Err(err) if truncated && err.error_len().is_none() => {
+            Some(String::from_utf8_lossy(&data[..err.valid_up_to()]).to_string())
+        }
+
+        Err(_) => None,
+    }
+}
@@ -0,0 +1,198 @@
+//! Utilities for comparing the contents of version files.
+
+use crate::util::archive::{HashedEntry, as_text};
+use similar::TextDiff;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    time::{Duration, Instant},
+};
+
+/// The number of unchanged lines shown around each change in a diff.
+const CONTEXT_LINES: usize = 3;
+
+/// The maximum total size of the entries that are read to be diffed in a single
+/// comparison, in bytes.
+const MAX_DIFF_INPUT: u64 = 32 * 1024 * 1024;
+
+/// The maximum total size of the diffs included in a single comparison, in bytes.
+const MAX_DIFF_OUTPUT: usize = 4 * 1024 * 1024;
+
+/// How long a single comparison can spend diffing text. After this, diffs are
+/// approximated until it runs out, and then they're left out.
+const DIFF_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How an archive entry changed between two versions.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    ToSchema,
+    ToResponse,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryChangeKind {
+    /// The entry only exists in the newer version.
+    Added,
+
+    /// The entry only exists in the older version.
+    Removed,
+
+    /// The entry's contents changed.
+    Modified,
+}
+
+/// A change to a single archive entry.
+#[derive(
+    Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, ToSchema, ToResponse,
+)]
+pub struct EntryChange {
+    /// The entry's path inside of the archive.
+    pub path: String,
+
+    /// How the entry changed.
+    pub kind: EntryChangeKind,
+
+    /// The entry's size in the older version, in bytes.
+    pub old_size: Option<u64>,
+
+    /// The entry's size in the newer version, in bytes.
+    pub new_size: Option<u64>,
+
+    /// Whether either side of the entry looks like binary data.
+    /// Binary entries don't get a diff.
+    pub binary: bool,
+
+    /// A unified diff of the entry's contents.
+    /// This is missing for binary entries, for entries that are too large to diff,
+    /// and once the comparison's output or time limit is reached.
+    pub diff: Option<String>,
+}
+
+/// Get the text of an entry, if it was kept and isn't binary.
+/// Returns `Err(())` if the entry is binary.
+fn entry_text(entry: Option<&HashedEntry>) -> Result<Option<String>, ()> {
+    match entry.map(|v| v.contents.as_deref()) {
+        // A missing entry is just empty.
+        None => Ok(Some(String::new())),
+        Some(None) => Ok(None),
+        Some(Some(data)) => as_text(data, false).map(Some).ok_or(()),
+    }
+}
+
+/// Get the paths of the entries that changed between two archives, and that
+/// are at most `keep` bytes on both sides, so their contents can be diffed.
+/// Paths are picked in order until their total size reaches the input limit.
+/// The returned flag is `true` if any paths were left out because of it.
+pub fn diffable_paths(
+    old: &BTreeMap<String, HashedEntry>,
+    new: &BTreeMap<String, HashedEntry>,
+    keep: usize,
+) -> (BTreeSet<String>, bool) {
+    let mut paths = BTreeSet::new();
+    let mut input = 0;
+    let mut truncated = false;
+
+    for path in old.keys().chain(new.keys()) {
+        let (before, after) = (old.get(path), new.get(path));
+
+        if before.zip(after).is_some_and(|(a, b)| a.sha1 == b.sha1)
+            || before.is_some_and(|v| v.size > keep as u64)
+            || after.is_some_and(|v| v.size > keep as u64)
+            || paths.contains(path)
+        {
+            continue;
+        }
+
+        let size = before.map_or(0, |v| v.size) + after.map_or(0, |v| v.size);
+
+        if input + size > MAX_DIFF_INPUT {
+            truncated = true;
+            continue;
+        }
+
+        input += size;
+        paths.insert(path.clone());
+    }
+
+    (paths, truncated)
+}
+
+/// Compare the entries of two archives, producing a list of changes sorted by path.
+/// Text diffs are only included for entries whose contents were kept, until the
+/// total output or time limit is reached. The returned flag is `true` if any
+/// diffs were left out because of those limits.
+pub fn diff_entries(
+    old: &BTreeMap<String, HashedEntry>,
+    new: &BTreeMap<String, HashedEntry>,
+) -> (Vec<EntryChange>, bool) {
+    let mut paths = old.keys().chain(new.keys()).collect::<Vec<_>>();
+    let deadline = Instant::now() + DIFF_TIMEOUT;
+    let mut output = 0;
+    let mut truncated = false;
+
+    paths.sort();
+    paths.dedup();
+
+    let changes = paths
+        .into_iter()
+        .filter_map(|path| {
+            let (before, after) = (old.get(path), new.get(path));
+
+            let kind = match (before, after) {
+                (Some(a), Some(b)) if a.sha1 == b.sha1 => return None,
+                (Some(_), Some(_)) => EntryChangeKind::Modified,
+                (Some(_), None) => EntryChangeKind::Removed,
+                (None, _) => EntryChangeKind::Added,
+            };
+
+            let (binary, diff) = match (entry_text(before), entry_text(after)) {
+                (Ok(Some(_)), Ok(Some(_)))
+                    if output >= MAX_DIFF_OUTPUT || Instant::now() > deadline =>
+                {
+                    truncated = true;
+                    (false, None)
+                }
+
+                (Ok(Some(a)), Ok(Some(b))) => {
+                    let diff = TextDiff::configure()
+                        .deadline(deadline)
+                        .diff_lines(&a, &b)
+                        .unified_diff()
+                        .context_radius(CONTEXT_LINES)
+                        .header(&format!("a/{}", path), &format!("b/{}", path))
+                        .to_string();
+
+                    output += diff.len();
+
+                    if output > MAX_DIFF_OUTPUT {
+                        truncated = true;
+                        (false, None)
+                    } else {
+                        (false, Some(diff))
+                    }
+                }
+
+                (Ok(_), Ok(_)) => (false, None),
+                _ => (true, None),
+            };
+
+            Some(EntryChange {
+                path: path.clone(),
+                kind,
+                old_size: before.map(|v| v.size),
+                new_size: after.map(|v| v.size),
+                binary,
+                diff,
+            })
+        })
+        .collect();
+
+    (changes, truncated)
+}
@@ -0,0 +1,229 @@
+//! Utilities for serving files from storage.
+
+use axum::{
+    body::Body,
+    http::{HeaderMap, StatusCode, header},
+    response::Response,
+};
+use modhost_config::{Bucket, BucketSigner};
+use modhost_core::Result;
+use object_store::{GetOptions, GetRange, path::Path};
+use std::time::Duration;
+
+/// A file in a bucket that can be served to a client.
+pub struct StoredFile<'a> {
+    /// The path of the file in its bucket.
+    pub path: Path,
+
+    /// The file's hash, used as its `ETag`.
+    pub hash: &'a str,
+
+    /// The name the client should save the file as.
+    pub file_name: &'a str,
+
+    /// The file's MIME type.
+    pub content_type: &'a str,
+
+    /// Whether the file should be displayed inline (like images),
+    /// instead of being downloaded as an attachment.
+    pub inline: bool,
+}
+
+impl StoredFile<'_> {
+    /// Serve this file, honoring the `If-None-Match` and `Range` request headers.
+    /// If a `signer` is provided, the client is redirected to a presigned URL
+    /// that is valid for `expiry` instead of the file being streamed.
+    pub async fn serve(
+        &self,
+        bucket: &Bucket,
+        signer: Option<&BucketSigner>,
+        expiry: Duration,
+        headers: &HeaderMap,
+    ) -> Result<Response> {
+        let etag = format!("\"{}\"", self.hash);
+
+        if etag_matches(headers, &etag) {
+            return Ok(Response::builder()
+                .status(StatusCode::NOT_MODIFIED)
+                .header(header::ETAG, etag)
+                .body(Body::empty())?);
+        }
+
+        if let Some(signer) = signer {
+            let disposition = self.content_disposition();
+
+            let url = signer.signed_url(
+                &self.path,
+                expiry,
+                &[
+                    ("response-content-disposition", &disposition),
+                    ("response-content-type", self.content_type),
+                ],
+            )?;
+
+            return Ok(Response::builder()
+                .status(StatusCode::TEMPORARY_REDIRECT)
+                .header(header::LOCATION, url.as_str())
+                .header(header::ETAG, etag)
+                .body(Body::empty())?);
+        }
+
+        let response = Response::builder()
+            .header(header::CONTENT_TYPE, self.content_type)
+            .header(header::CONTENT_DISPOSITION, self.content_disposition())
+            .header(header::ACCEPT_RANGES, "bytes")
+            .header(header::ETAG, etag);
+
+        let Some(range) = requested_range(headers) else {
+            let data = bucket.get(&self.path).await?;
+
+            return Ok(response
+                .header(header::CONTENT_LENGTH, data.meta.size)
+                .body(Body::from_stream(data.into_stream()))?);
+        };
+
+        let size = bucket.head(&self.path).await?.size;
+
+        let Some((start, end)) = range.resolve(size) else {
+            return Ok(Response::builder()
+                .status(StatusCode::RANGE_NOT_SATISFIABLE)
+                .header(header::CONTENT_RANGE, format!("bytes */{}", size))
+                .body(Body::empty())?);
+        };
+
+        let opts = GetOptions {
+            range: Some(GetRange::Bounded(start..end + 1)),
+            ..Default::default()
+        };
+
+        let data = bucket.get_opts(&self.path, opts).await?;
+
+        Ok(response
+            .status(StatusCode::PARTIAL_CONTENT)
+            .header(header::CONTENT_LENGTH, end + 1 - start)
+            .header(
+                header::CONTENT_RANGE,
+                format!("bytes {}-{}/{}", start, end, size),
+            )
+            .body(Body::from_stream(data.into_stream()))?)
+    }
+
+    /// Create the `Content-Disposition` header value for this file.
+    fn content_disposition(&self) -> String {
+        let kind = if self.inline { "inline" } else { "attachment" };
+
+        let fallback = self
+            .file_name
+            .chars()
+            .map(|c| {
+                if c.is_ascii_graphic() && c != '"' && c != '\\' || c == ' ' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect::<String>();
+
+        let encoded = self
+            .file_name
+            .bytes()
+            .map(|b| {
+                if b.is_ascii_alphanumeric() || b"!#$&+-.^_`|~".contains(&b) {
+                    (b as char).to_string()
+                } else {
+                    format!("%{:02X}", b)
+                }
+            })
+            .collect::<String>();
+
+        format!(
+            "{}; filename=\"{}\"; filename*=UTF-8''{}",
+            kind, fallback, encoded
+        )
+    }
+}
+
+/// Check whether a request should count as a new download.
+/// Requests that are answered with `304 Not Modified`, and ranged requests
+/// that don't start at the beginning of the file (resumed downloads) don't count.
+pub fn counts_as_download(headers: &HeaderMap, hash: &str) -> bool {
+    if etag_matches(headers, &format!("\"{}\"", hash)) {
+        return false;
+    }
+
+    match requested_range(headers) {
+        Some(ByteRange::From(start)) | Some(ByteRange::Bounded(start, _)) => start == 0,
+        Some(ByteRange::Suffix(_)) => false,
+        None => true,
+    }
+}
+
+/// A single byte range requested with the `Range` header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ByteRange {
+    /// `bytes=start-end` (inclusive)
+    Bounded(usize, usize),
+
+    /// `bytes=start-`
+    From(usize),
+
+    /// `bytes=-length`
+    Suffix(usize),
+}
+
+impl ByteRange {
+    /// Resolve this range against a file's size, returning the inclusive
+    /// start and end offsets, or [`None`] if it isn't satisfiable.
+    fn resolve(self, size: usize) -> Option<(usize, usize)> {
+        if size == 0 {
+            return None;
+        }
+
+        match self {
+            Self::Bounded(start, end) if start < size && start <= end => {
+                Some((start, end.min(size - 1)))
+            }
+
+            Self::From(start) if start < size => Some((start, size - 1)),
+            Self::Suffix(len) if len > 0 => Some((size - len.min(size), size - 1)),
+            _ => None,
+        }
+    }
+}
+
+/// Check whether the `If-None-Match` header matches an `ETag`.
+fn etag_matches(headers: &HeaderMap, etag: &str) -> bool {
+    headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| {
+            v.split(',')
+                .map(|tag| tag.trim().trim_start_matches("W/"))
+                .any(|tag| tag == "*" || tag == etag)
+        })
+}
+
+/// Parse the `Range` header. Only single ranges are supported, so anything
+/// else is ignored and the whole file gets served instead.
+fn requested_range(headers: &HeaderMap) -> Option<ByteRange> {
+    let value = headers.get(header::RANGE)?.to_str().ok()?;
+    let spec = value.trim().strip_prefix("bytes=")?;
+
+    if spec.contains(',') {
+        return None;
+    }
+
+    let (start, end) = spec.split_once('-')?;
+    let (start, end) = (start.trim(), end.trim());
+
+    match (start.is_empty(), end.is_empty()) {
+        (true, false) => Some(ByteRange::Suffix(end.parse().ok()?)),
+        (false, true) => Some(ByteRange::From(start.parse().ok()?)),
+        (false, false) => {
+            let (start, end) = (start.parse().ok()?, end.parse().ok()?);
+
+            (end >= start).then_some(ByteRange::Bounded(start, end))
+        }
+        (true, true) => None,
+    }
+}
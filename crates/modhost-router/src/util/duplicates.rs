@@ -0,0 +1,190 @@
+//! Utilities for finding files that were uploaded to more than one project.
+
+use diesel::{ExpressionMethods, QueryDsl, SelectableHelper, dsl::count_distinct};
+use diesel_async::RunQueryDsl;
+use itertools::Itertools;
+use modhost_core::Result;
+use modhost_db::{
+    DbConn, ModerationQueueStatus, Project, ProjectAuthor, ProjectFile, project_authors,
+    project_versions, projects, version_files,
+};
+use modhost_db_util::moderation::{
+    create_system_comment, get_or_create_moderation_queue_item, set_moderation_status,
+};
+use std::collections::HashMap;
+
+/// A version file, along with the project and version it belongs to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema, ToResponse)]
+pub struct DuplicateFile {
+    /// The file.
+    pub file: ProjectFile,
+
+    /// The project ID.
+    pub project: i32,
+
+    /// The project's slug.
+    pub slug: String,
+
+    /// The version number.
+    pub version_number: String,
+
+    /// The IDs of the project's authors.
+    pub authors: Vec<i32>,
+}
+
+impl DuplicateFile {
+    /// Whether this file's project shares any authors with another file's project.
+    pub fn shares_authors(&self, other: &DuplicateFile) -> bool {
+        self.authors.iter().any(|v| other.authors.contains(v))
+    }
+
+    /// A Markdown link to this file's version.
+    pub fn link(&self) -> String {
+        format!(
+            "[{} {}](/p/{}/versions/{})",
+            self.slug, self.version_number, self.slug, self.file.version_id
+        )
+    }
+}
+
+/// A file that was uploaded to more than one project.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema, ToResponse)]
+pub struct DuplicateGroup {
+    /// The SHA-1 hash of the file.
+    pub sha1: String,
+
+    /// The first upload of the file.
+    pub original: DuplicateFile,
+
+    /// The later uploads of the file to other projects.
+    pub copies: Vec<DuplicateFile>,
+}
+
+/// Load every version file with one of the given SHA-1 hashes, oldest first.
+async fn load_files(hashes: &[String], conn: &mut DbConn) -> Result<Vec<DuplicateFile>> {
+    let files = version_files::table
+        .inner_join(project_versions::table.inner_join(projects::table))
+        .filter(version_files::sha1.eq_any(hashes))
+        .select((
+            ProjectFile::as_select(),
+            projects::id,
+            projects::slug,
+            project_versions::version_number,
+        ))
+        .order((version_files::uploaded_at, version_files::id))
+        .load::<(ProjectFile, i32, String, String)>(conn)
+        .await?;
+
+    let authors = project_authors::table
+        .filter(project_authors::project.eq_any(files.iter().map(|v| v.1).unique()))
+        .select(ProjectAuthor::as_select())
+        .load(conn)
+        .await?
+        .into_iter()
+        .map(|v| (v.project, v.user_id))
+        .into_group_map();
+
+    Ok(files
+        .into_iter()
+        .map(|(file, project, slug, version_number)| DuplicateFile {
+            file,
+            authors: authors.get(&project).cloned().unwrap_or_default(),
+            project,
+            slug,
+            version_number,
+        })
+        .collect())
+}
+
+/// Group files by their hash, keeping the groups that span more than one project.
+fn group_files(files: Vec<DuplicateFile>) -> Vec<DuplicateGroup> {
+    let mut groups: HashMap<String, Vec<DuplicateFile>> = HashMap::new();
+
+    for file in files {
+        groups.entry(file.file.sha1.clone()).or_default().push(file);
+    }
+
+    groups
+        .into_iter()
+        .filter_map(|(sha1, mut files)| {
+            let original = files.remove(0);
+
+            let copies = files
+                .into_iter()
+                .filter(|v| v.project != original.project)
+                .collect::<Vec<_>>();
+
+            if copies.is_empty() {
+                return None;
+            }
+
+            Some(DuplicateGroup {
+                sha1,
+                original,
+                copies,
+            })
+        })
+        .sorted_by_key(|v| v.original.file.id)
+        .collect()
+}
+
+/// Find every file that was uploaded to more than one project.
+/// Groups are sorted by the ID of their original file.
+pub async fn find_duplicates(conn: &mut DbConn) -> Result<Vec<DuplicateGroup>> {
+    let hashes = version_files::table
+        .inner_join(project_versions::table)
+        .group_by(version_files::sha1)
+        .having(count_distinct(project_versions::project).gt(1))
+        .select(version_files::sha1)
+        .load::<String>(conn)
+        .await?;
+
+    if hashes.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    Ok(group_files(load_files(&hashes, conn).await?))
+}
+
+/// Check whether a newly uploaded file is a copy of a file from another project
+/// that has none of the same authors, and that uploaded it first. If it is, the project is put under review
+/// in the moderation queue, and a system comment linking the original is left
+/// for the moderators.
+pub async fn check_duplicate(
+    project: &Project,
+    file: &ProjectFile,
+    conn: &mut DbConn,
+) -> Result<()> {
+    let files = load_files(std::slice::from_ref(&file.sha1), conn).await?;
+
+    let Some(copy) = files.iter().find(|v| v.file.id == file.id) else {
+        return Ok(());
+    };
+
+    // Only the earliest upload counts as the original, so an author re-uploading their
+    // own file isn't flagged because somebody else copied it in between.
+    let [original, ..] = files.as_slice() else {
+        return Ok(());
+    };
+
+    if original.project == project.id || original.shares_authors(copy) {
+        return Ok(());
+    }
+
+    get_or_create_moderation_queue_item(project, conn).await?;
+    set_moderation_status(project, ModerationQueueStatus::UnderReview, conn).await?;
+
+    create_system_comment(
+        project,
+        format!(
+            "`{}` is identical to `{}` from {}, which was uploaded earlier by different authors.",
+            file.file_name,
+            original.file.file_name,
+            original.link()
+        ),
+        conn,
+    )
+    .await?;
+
+    Ok(())
+}
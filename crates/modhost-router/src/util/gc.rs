@@ -0,0 +1,265 @@
+//! Garbage collection for stored objects.
+
+use chrono::{DateTime, Utc};
+use diesel::{ExpressionMethods, QueryDsl};
+use diesel_async::RunQueryDsl;
+use futures::TryStreamExt;
+use modhost_config::Bucket;
+use modhost_core::Result;
+use modhost_db::{DbConn, gallery_images, version_files};
+use modhost_server_core::state::{AppState, BucketState};
+use object_store::path::Path;
+use std::{collections::HashSet, time::Duration};
+use tokio::{
+    task::JoinHandle,
+    time::{Instant, interval_at},
+};
+
+/// An object in a bucket that isn't referenced by any database row.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, ToResponse)]
+pub struct OrphanedObject {
+    /// The bucket the object is in (`"projects"` or `"gallery"`).
+    pub bucket: String,
+
+    /// The object's path in its bucket.
+    pub path: String,
+
+    /// The object's size in bytes.
+    pub size: usize,
+
+    /// When the object was last modified.
+    pub last_modified: DateTime<Utc>,
+
+    /// Whether the object is older than the grace period.
+    /// Only these objects get deleted.
+    pub expired: bool,
+}
+
+/// A database row whose object is missing from its bucket.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, ToResponse)]
+pub struct MissingObject {
+    /// The kind of row (`"version_file"` or `"gallery_image"`).
+    pub kind: String,
+
+    /// The row's ID.
+    pub id: i32,
+
+    /// The ID of the object the row references.
+    pub s3_id: String,
+}
+
+/// The result of a garbage collection run.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, ToResponse)]
+pub struct GcReport {
+    /// Whether this was a dry run (nothing was deleted).
+    pub dry_run: bool,
+
+    /// The number of objects that were checked.
+    pub scanned: usize,
+
+    /// Objects that aren't referenced by anything.
+    pub orphaned: Vec<OrphanedObject>,
+
+    /// The number of objects that were deleted.
+    pub deleted: usize,
+
+    /// The number of bytes freed by deleting objects.
+    pub freed_bytes: usize,
+
+    /// Database rows whose objects are missing.
+    pub missing: Vec<MissingObject>,
+}
+
+/// Start the storage garbage collection thread.
+pub fn start_gc_thread(state: &AppState) -> JoinHandle<Result<()>> {
+    let buckets = state.buckets.clone();
+    let pool = state.pool.clone();
+    let period = state.config.storage.gc_interval;
+    let grace_period = state.config.storage.gc_grace_period;
+    let mut interval = interval_at(Instant::now() + period, period);
+
+    tokio::spawn(async move {
+        loop {
+            interval.tick().await;
+
+            // A failed run is retried on the next tick, so this keeps going.
+            let result = async {
+                let mut conn = pool.get().await?;
+
+                collect_garbage(&buckets, grace_period, false, &mut conn).await
+            }
+            .await;
+
+            match result {
+                Ok(report) => {
+                    info!(
+                        "Garbage collection finished: deleted {} object(s) ({} bytes), {} missing object(s).",
+                        report.deleted,
+                        report.freed_bytes,
+                        report.missing.len()
+                    );
+
+                    for missing in report.missing {
+                        warn!(
+                            "Missing object {} for {} (id: {})",
+                            missing.s3_id, missing.kind, missing.id
+                        );
+                    }
+                }
+
+                Err(err) => error!("Garbage collection failed: {}", err),
+            }
+        }
+    })
+}
+
+/// Compare the objects in the buckets against the `s3_id`s of version files and
+/// gallery images. Unreferenced objects older than `grace_period` are deleted
+/// (unless `dry_run` is set), and rows whose objects are missing are reported.
+pub async fn collect_garbage(
+    buckets: &BucketState,
+    grace_period: Duration,
+    dry_run: bool,
+    conn: &mut DbConn,
+) -> Result<GcReport> {
+    let files = version_files::table
+        .select((version_files::id, version_files::s3_id))
+        .load::<(i32, String)>(conn)
+        .await?;
+
+    let images = gallery_images::table
+        .select((gallery_images::id, gallery_images::s3_id))
+        .load::<(i32, String)>(conn)
+        .await?;
+
+    let mut report = GcReport {
+        dry_run,
+        scanned: 0,
+        orphaned: Vec::new(),
+        deleted: 0,
+        freed_bytes: 0,
+        missing: Vec::new(),
+    };
+
+    let present = scan_bucket(
+        "projects",
+        &buckets.projects,
+        files.iter().map(|(_, id)| id.as_str()).collect(),
+        grace_period,
+        &mut report,
+        conn,
+    )
+    .await?;
+
+    for (id, s3_id) in files {
+        if !present.contains(&s3_id) {
+            report.missing.push(MissingObject {
+                kind: "version_file".into(),
+                id,
+                s3_id,
+            });
+        }
+    }
+
+    let present = scan_bucket(
+        "gallery",
+        &buckets.gallery,
+        images.iter().map(|(_, id)| id.as_str()).collect(),
+        grace_period,
+        &mut report,
+        conn,
+    )
+    .await?;
+
+    for (id, s3_id) in images {
+        if !present.contains(&s3_id) {
+            report.missing.push(MissingObject {
+                kind: "gallery_image".into(),
+                id,
+                s3_id,
+            });
+        }
+    }
+
+    Ok(report)
+}
+
+/// Scan a bucket, recording (and maybe deleting) unreferenced objects,
+/// and returning the IDs of the referenced objects that were found.
+async fn scan_bucket(
+    name: &str,
+    bucket: &Bucket,
+    referenced: HashSet<&str>,
+    grace_period: Duration,
+    report: &mut GcReport,
+    conn: &mut DbConn,
+) -> Result<HashSet<String>> {
+    let objects = bucket.list(None).try_collect::<Vec<_>>().await?;
+    let cutoff = Utc::now() - grace_period;
+    let mut present = HashSet::new();
+
+    for obj in objects {
+        let path = obj.location.to_string();
+
+        report.scanned += 1;
+
+        if referenced.contains(path.as_str()) {
+            present.insert(path);
+            continue;
+        }
+
+        let expired = obj.last_modified < cutoff;
+
+        // The object may have been uploaded again since we loaded the references,
+        // or it may be in the middle of being uploaded again, which would make the
+        // listing stale, so it's checked once more right before deleting it.
+        if expired
+            && !report.dry_run
+            && !is_referenced(name, &path, conn).await?
+            && !modified_since(bucket, &obj.location, cutoff).await?
+        {
+            bucket.delete(&obj.location).await?;
+
+            report.deleted += 1;
+            report.freed_bytes += obj.size;
+        }
+
+        report.orphaned.push(OrphanedObject {
+            bucket: name.into(),
+            path,
+            size: obj.size,
+            last_modified: obj.last_modified,
+            expired,
+        });
+    }
+
+    Ok(present)
+}
+
+/// Check whether an object was modified (or removed) after `cutoff`.
+async fn modified_since(bucket: &Bucket, path: &Path, cutoff: DateTime<Utc>) -> Result<bool> {
+    match bucket.head(path).await {
+        Ok(meta) => Ok(meta.last_modified >= cutoff),
+        Err(object_store::Error::NotFound { .. }) => Ok(true),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Check whether an object is currently referenced by any row.
+async fn is_referenced(bucket: &str, s3_id: &str, conn: &mut DbConn) -> Result<bool> {
+    let count = if bucket == "gallery" {
+        gallery_images::table
+            .filter(gallery_images::s3_id.eq(s3_id))
+            .count()
+            .get_result::<i64>(conn)
+            .await?
+    } else {
+        version_files::table
+            .filter(version_files::s3_id.eq(s3_id))
+            .count()
+            .get_result::<i64>(conn)
+            .await?
+    };
+
+    Ok(count > 0)
+}
@@ -0,0 +1,88 @@
+//! Utilities for validating loaders, game versions, and tags.
+
+use modhost_core::{AppError, Result};
+use modhost_db::User;
+use modhost_server_core::state::AppState;
+
+/// Query params for routes that validate loaders, game versions, or tags.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    ToSchema,
+    ToResponse,
+    Serialize,
+    Deserialize,
+    Default,
+)]
+pub struct MetadataQueryParams {
+    /// Accept loaders, game versions, and tags that aren't registered on the server.
+    /// Only admins can set this.
+    pub skip_validation: Option<bool>,
+}
+
+impl MetadataQueryParams {
+    /// Check whether validation should be skipped for this user.
+    /// This fails if a user who isn't an admin tries to skip validation.
+    pub fn skip(&self, user: &User) -> Result<bool> {
+        let skip = self.skip_validation.unwrap_or(false);
+
+        if skip && !user.admin {
+            return Err(AppError::NoAccess);
+        }
+
+        Ok(skip)
+    }
+}
+
+/// Check that loaders, game versions, and tags are registered in the [`AppState`],
+/// returning an [`AppError::UnknownMetadata`] listing any that aren't.
+/// If the server has no loaders, game versions, or tags registered, that kind isn't checked.
+pub fn validate_metadata<'a>(
+    state: &AppState,
+    loaders: impl IntoIterator<Item = &'a String>,
+    game_versions: impl IntoIterator<Item = &'a String>,
+    tags: impl IntoIterator<Item = &'a String>,
+) -> Result<()> {
+    let loaders = find_unknown(loaders, state.loaders.iter().map(|v| &v.id));
+    let game_versions = find_unknown(game_versions, state.game_versions.iter().map(|v| &v.id));
+    let tags = find_unknown(tags, state.tags.iter().map(|v| &v.id));
+
+    if loaders.is_empty() && game_versions.is_empty() && tags.is_empty() {
+        Ok(())
+    } else {
+        Err(AppError::UnknownMetadata {
+            loaders,
+            game_versions,
+            tags,
+        })
+    }
+}
+
+/// Find the values that aren't in the list of known values.
+/// If there are no known values, nothing is unknown.
+pub fn find_unknown<'a, 'b>(
+    values: impl IntoIterator<Item = &'a String>,
+    known: impl IntoIterator<Item = &'b String>,
+) -> Vec<String> {
+    let known = known.into_iter().collect::<Vec<_>>();
+
+    if known.is_empty() {
+        return Vec::new();
+    }
+
+    let mut unknown = values
+        .into_iter()
+        .filter(|v| !known.iter().any(|k| k == v))
+        .cloned()
+        .collect::<Vec<_>>();
+
+    unknown.sort();
+    unknown.dedup();
+    unknown
+}
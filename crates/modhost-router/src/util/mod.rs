@@ -1,3 +1,16 @@
 //! Utilities.
 
+pub mod access;
+pub mod archive;
+pub mod diff;
+pub mod download;
+pub mod duplicates;
+pub mod gc;
+pub mod metadata;
+pub mod page;
+pub mod reader;
+pub mod resolve;
+pub mod scan;
+pub mod session;
 pub mod stats;
+pub mod upload;
@@ -0,0 +1,45 @@
+//! Simple HTML pages for browser-facing routes.
+
+use axum::{
+    body::Body,
+    http::{StatusCode, header::CONTENT_TYPE},
+    response::{IntoResponse, Response},
+};
+use modhost_core::{AppError, HasCode};
+
+/// The template for error pages.
+const ERROR_PAGE: &str = r#"<!DOCTYPE html>
+<html>
+    <head>
+        <meta charset="utf-8">
+        <title>Login failed</title>
+    </head>
+    <body>
+        <h1>Login failed</h1>
+        <p>{message}</p>
+        <p><a href="/">Go back home</a></p>
+    </body>
+</html>
+"#;
+
+/// Render an error as an HTML page, for routes that browsers are sent to directly.
+pub fn error_page(err: AppError) -> Response {
+    let status = StatusCode::from_u16(err.code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+
+    let body = ERROR_PAGE.replace("{message}", &escape_html(&err.to_string()));
+
+    Response::builder()
+        .status(status)
+        .header(CONTENT_TYPE, "text/html; charset=utf-8")
+        .body(Body::new(body))
+        .unwrap_or_else(|_| err.into_response())
+}
+
+/// Escape text to be put into HTML.
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
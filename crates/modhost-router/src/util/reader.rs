@@ -0,0 +1,107 @@
+//! Reading stored objects without holding them in memory.
+
+use axum::body::Bytes;
+use modhost_config::Bucket;
+use object_store::path::Path;
+use std::io::{self, Read, Seek, SeekFrom};
+use tokio::runtime::Handle;
+
+/// How many bytes are fetched at once when reading an object.
+const READ_CHUNK_SIZE: usize = 256 * 1024;
+
+/// A blocking reader for an object in a bucket. The object is fetched in ranges
+/// as it's read, so seeking around in it (like to the central directory of a zip
+/// file) only downloads the parts that are actually read.
+/// This blocks on the runtime, so it can only be used on a blocking thread,
+/// like in [`tokio::task::spawn_blocking`].
+pub struct ObjectReader {
+    /// The bucket the object is in.
+    bucket: Bucket,
+
+    /// The object's path.
+    path: Path,
+
+    /// The object's size in bytes.
+    size: u64,
+
+    /// The current position in the object.
+    pos: u64,
+
+    /// The last range that was fetched.
+    chunk: Bytes,
+
+    /// Where the last range that was fetched starts.
+    chunk_start: u64,
+
+    /// The runtime to fetch ranges on.
+    runtime: Handle,
+}
+
+impl ObjectReader {
+    /// Create a reader for an object that's `size` bytes long.
+    /// This has to be called from inside of the runtime.
+    pub fn new(bucket: Bucket, path: Path, size: u64) -> Self {
+        Self {
+            bucket,
+            path,
+            size,
+            pos: 0,
+            chunk: Bytes::new(),
+            chunk_start: 0,
+            runtime: Handle::current(),
+        }
+    }
+}
+
+impl Read for ObjectReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pos >= self.size || buf.is_empty() {
+            return Ok(0);
+        }
+
+        let chunk_end = self.chunk_start + self.chunk.len() as u64;
+
+        if self.pos < self.chunk_start || self.pos >= chunk_end {
+            let end = self
+                .size
+                .min(self.pos + buf.len().max(READ_CHUNK_SIZE) as u64);
+
+            self.chunk = self
+                .runtime
+                .block_on(
+                    self.bucket
+                        .get_range(&self.path, self.pos as usize..end as usize),
+                )
+                .map_err(io::Error::other)?;
+
+            self.chunk_start = self.pos;
+        }
+
+        let offset = (self.pos - self.chunk_start) as usize;
+        let len = buf.len().min(self.chunk.len().saturating_sub(offset));
+
+        buf[..len].copy_from_slice(&self.chunk[offset..offset + len]);
+        self.pos += len as u64;
+
+        Ok(len)
+    }
+}
+
+impl Seek for ObjectReader {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let pos = match pos {
+            SeekFrom::Start(pos) => Some(pos),
+            SeekFrom::End(offset) => self.size.checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.pos.checked_add_signed(offset),
+        };
+
+        self.pos = pos.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Tried to seek before the start of the object!",
+            )
+        })?;
+
+        Ok(self.pos)
+    }
+}
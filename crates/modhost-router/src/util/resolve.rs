@@ -0,0 +1,227 @@
+//! A backtracking dependency resolver for project versions.
+
+use modhost_db::{ProjectVersionData, RelationKind};
+use std::collections::HashMap;
+
+/// The maximum number of steps the resolver takes before giving up.
+const MAX_STEPS: usize = 10_000;
+
+/// A project that has to be part of the solution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Requirement {
+    /// The project ID.
+    pub project: i32,
+
+    /// The version ID, if a specific version is required.
+    pub version: Option<i32>,
+
+    /// The ID of the version that required this, if any.
+    pub required_by: Option<i32>,
+}
+
+/// A partial solution that the resolver still has to explore.
+#[derive(Debug, Clone)]
+struct State<'a> {
+    /// The versions chosen so far.
+    assigned: Vec<&'a ProjectVersionData>,
+
+    /// The requirements that haven't been resolved yet.
+    pending: Vec<Requirement>,
+
+    /// The version to try next, if this state is a choice between candidates.
+    choice: Option<&'a ProjectVersionData>,
+}
+
+/// The outcome of a single resolver step.
+#[derive(Debug)]
+enum Step<'a> {
+    /// Every requirement is satisfied.
+    Solved(Vec<&'a ProjectVersionData>),
+
+    /// These states should be tried next, in order.
+    Next(Vec<State<'a>>),
+
+    /// This state led to a conflict.
+    Failed(String),
+}
+
+/// A dependency resolution problem.
+#[derive(Debug, Clone, Default)]
+pub struct ResolveProblem {
+    /// The candidate versions for each project, in order of preference.
+    pub candidates: HashMap<i32, Vec<ProjectVersionData>>,
+
+    /// The display names of projects, used in error messages.
+    pub names: HashMap<i32, String>,
+}
+
+impl ResolveProblem {
+    /// Find a set of versions (one per project) that includes all of the `roots`,
+    /// satisfies every dependency, and violates no incompatibility.
+    /// Newer versions are preferred. If there is no solution, this returns an
+    /// explanation of the first conflict found.
+    pub fn solve(&self, roots: &[Requirement]) -> Result<Vec<&ProjectVersionData>, String> {
+        // This is a depth-first search with an explicit stack, so deep dependency
+        // chains can't overflow the real one.
+        let mut stack = vec![State {
+            assigned: Vec::new(),
+            pending: roots.to_vec(),
+            choice: None,
+        }];
+
+        let mut first_err = None;
+        let mut steps = 0;
+
+        while let Some(state) = stack.pop() {
+            steps += 1;
+
+            if steps > MAX_STEPS {
+                return Err("The dependency graph is too complex to resolve!".into());
+            }
+
+            match self.step(state) {
+                Step::Solved(solution) => return Ok(solution),
+                Step::Next(states) => stack.extend(states.into_iter().rev()),
+
+                Step::Failed(err) => {
+                    first_err.get_or_insert(err);
+                }
+            }
+        }
+
+        Err(first_err.unwrap_or_default())
+    }
+
+    /// Get a project's display name.
+    fn name(&self, project: i32) -> String {
+        self.names
+            .get(&project)
+            .cloned()
+            .unwrap_or_else(|| format!("project {}", project))
+    }
+
+    /// Get a version's display name.
+    fn label(&self, ver: &ProjectVersionData) -> String {
+        format!("{} {}", self.name(ver.project), ver.version_number)
+    }
+
+    /// Describe what required a project.
+    fn cause(&self, req: &Requirement, assigned: &[&ProjectVersionData]) -> String {
+        match req
+            .required_by
+            .and_then(|id| assigned.iter().find(|v| v.id == id))
+        {
+            Some(ver) => format!("required by {}", self.label(ver)),
+            None => "requested".into(),
+        }
+    }
+
+    /// Apply a state's choice, then resolve its first pending requirement.
+    /// The states that follow are returned in the order they should be tried.
+    fn step<'a>(&'a self, state: State<'a>) -> Step<'a> {
+        let State {
+            mut assigned,
+            mut pending,
+            choice,
+        } = state;
+
+        if let Some(ver) = choice {
+            if let Err(err) = self.check(&assigned, ver) {
+                return Step::Failed(err);
+            }
+
+            assigned.push(ver);
+
+            for rel in &ver.relations {
+                if rel.kind == RelationKind::Dependency {
+                    pending.push(Requirement {
+                        project: rel.target_project,
+                        version: rel.target_version,
+                        required_by: Some(ver.id),
+                    });
+                }
+            }
+        }
+
+        let Some((req, rest)) = pending.split_first() else {
+            return Step::Solved(assigned);
+        };
+
+        if let Some(ver) = assigned.iter().find(|v| v.project == req.project) {
+            if req.version.is_some_and(|id| id != ver.id) {
+                let wanted = self
+                    .candidates
+                    .get(&req.project)
+                    .and_then(|v| v.iter().find(|v| Some(v.id) == req.version))
+                    .map(|v| self.label(v))
+                    .unwrap_or_else(|| format!("another version of {}", self.name(req.project)));
+
+                return Step::Failed(format!(
+                    "{} is {}, but {} was already chosen.",
+                    wanted,
+                    self.cause(req, &assigned),
+                    self.label(ver)
+                ));
+            }
+
+            let rest = rest.to_vec();
+
+            return Step::Next(vec![State {
+                assigned,
+                pending: rest,
+                choice: None,
+            }]);
+        }
+
+        let candidates = self
+            .candidates
+            .get(&req.project)
+            .map(|v| v.as_slice())
+            .unwrap_or_default()
+            .iter()
+            .filter(|v| req.version.is_none_or(|id| id == v.id))
+            .map(|ver| State {
+                assigned: assigned.clone(),
+                pending: rest.to_vec(),
+                choice: Some(ver),
+            })
+            .collect::<Vec<_>>();
+
+        if candidates.is_empty() {
+            return Step::Failed(format!(
+                "{} is {}, but it has no matching versions for the given loader and game version.",
+                self.name(req.project),
+                self.cause(req, &assigned)
+            ));
+        }
+
+        Step::Next(candidates)
+    }
+
+    /// Check that a version isn't incompatible with any of the chosen versions.
+    fn check(
+        &self,
+        assigned: &[&ProjectVersionData],
+        ver: &ProjectVersionData,
+    ) -> Result<(), String> {
+        for other in assigned {
+            let conflicts = |a: &ProjectVersionData, b: &ProjectVersionData| {
+                a.relations.iter().any(|rel| {
+                    rel.kind == RelationKind::Incompatibility
+                        && rel.target_project == b.project
+                        && rel.target_version.is_none_or(|id| id == b.id)
+                })
+            };
+
+            if conflicts(ver, other) || conflicts(other, ver) {
+                return Err(format!(
+                    "{} is incompatible with {}.",
+                    self.label(ver),
+                    self.label(other)
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,945 @@
+//! Safety scanning for uploaded archives.
+
+use crate::util::{archive::ArchiveKind, upload::StagedUpload};
+use diesel::{ExpressionMethods, insert_into, update};
+use diesel_async::RunQueryDsl;
+use flate2::read::GzDecoder;
+use itertools::Itertools;
+use modhost_config::{Bucket, ScanConfig};
+use modhost_core::{AppError, Result};
+use modhost_db::{
+    DbConn, ModerationQueueStatus, NewScanFinding, Project, ProjectFile, ScanFindingKind, ScanRule,
+    ScanRuleKind, project_versions, scan_findings,
+};
+use modhost_db_util::{
+    files::FileHasher,
+    moderation::{
+        create_system_comment, get_enabled_scan_rules, get_or_create_moderation_queue_item,
+        set_moderation_status,
+    },
+};
+use regex::{Regex, RegexBuilder, bytes};
+use std::{
+    fs::File,
+    io::{self, BufReader, Read, Seek, Write},
+};
+use tar::Archive;
+use zip::ZipArchive;
+
+/// Entries smaller than this aren't checked for their compression ratio,
+/// since tiny files can be compressed extremely well without being malicious.
+const MIN_RATIO_CHECK_SIZE: u64 = 1024 * 1024;
+
+/// How many bytes of a file are held in memory at once while it's scanned.
+/// Byte and regex rules are matched against windows of this size.
+const SCAN_WINDOW_SIZE: usize = 1024 * 1024;
+
+/// How many bytes at the end of a window are kept at the start of the next one,
+/// so byte and regex rules can still match across the boundary.
+const SCAN_WINDOW_OVERLAP: usize = 64 * 1024;
+
+/// The separator between the path of a nested archive and the path of an entry inside it.
+pub const NESTED_SEPARATOR: &str = "!/";
+
+/// A problem found while scanning an archive.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ArchiveFinding {
+    /// The kind of problem.
+    pub kind: ScanFindingKind,
+
+    /// The path of the offending entry.
+    pub path: String,
+
+    /// A description of the problem.
+    pub message: String,
+
+    /// The ID of the scan rule that matched, if any.
+    pub rule_id: Option<i32>,
+}
+
+impl ArchiveFinding {
+    /// Turn this into a finding that can be stored for a file.
+    pub fn into_new(self, file_id: i32) -> NewScanFinding {
+        NewScanFinding {
+            file_id,
+            kind: self.kind,
+            path: self.path,
+            message: self.message,
+            rule_id: self.rule_id,
+        }
+    }
+}
+
+/// A pattern from a scan rule, compiled so files can be checked against it.
+#[derive(Debug, Clone)]
+enum RuleMatcher {
+    /// Matches files containing a byte sequence.
+    Bytes(bytes::Regex),
+
+    /// Matches text files containing a match for a regular expression.
+    Regex(Regex),
+
+    /// Matches files with a hash (as lowercase hex).
+    Hash(String),
+}
+
+impl RuleMatcher {
+    /// Compile a rule's pattern.
+    fn compile(kind: ScanRuleKind, pattern: &str) -> Result<Self> {
+        let invalid = |msg: String| AppError::InvalidScanRule(msg);
+
+        match kind {
+            ScanRuleKind::Bytes => {
+                let hex = pattern
+                    .trim()
+                    .trim_start_matches("0x")
+                    .replace(|c: char| c.is_whitespace(), "");
+
+                if hex.is_empty()
+                    || hex.len() % 2 != 0
+                    || !hex.chars().all(|c| c.is_ascii_hexdigit())
+                {
+                    return Err(invalid(
+                        "Byte patterns must be an even number of hex digits!".into(),
+                    ));
+                }
+
+                let escaped = (0..hex.len())
+                    .step_by(2)
+                    .map(|i| format!("\\x{}", &hex[i..i + 2]))
+                    .collect::<String>();
+
+                bytes::Regex::new(&format!("(?-u){}", escaped))
+                    .map(Self::Bytes)
+                    .map_err(|err| invalid(err.to_string()))
+            }
+
+            ScanRuleKind::Regex => RegexBuilder::new(pattern)
+                .size_limit(1024 * 1024)
+                .build()
+                .map(Self::Regex)
+                .map_err(|err| invalid(err.to_string())),
+
+            ScanRuleKind::Hash => {
+                let hash = pattern.trim().to_lowercase();
+
+                if ![40, 64, 128].contains(&hash.len())
+                    || !hash.chars().all(|c| c.is_ascii_hexdigit())
+                {
+                    return Err(invalid(
+                        "Hashes must be SHA-1, SHA-256, or SHA-512 hashes, written as hex!".into(),
+                    ));
+                }
+
+                Ok(Self::Hash(hash))
+            }
+        }
+    }
+}
+
+/// A scan rule, compiled so files can be checked against it.
+#[derive(Debug, Clone)]
+pub struct CompiledRule {
+    /// The rule ID.
+    pub id: i32,
+
+    /// The rule's name.
+    pub name: String,
+
+    /// The compiled pattern.
+    matcher: RuleMatcher,
+}
+
+impl CompiledRule {
+    /// Compile a scan rule.
+    pub fn new(rule: &ScanRule) -> Result<Self> {
+        Ok(Self {
+            id: rule.id,
+            name: rule.name.clone(),
+            matcher: RuleMatcher::compile(rule.kind, &rule.pattern)?,
+        })
+    }
+}
+
+/// Check that a scan rule's pattern is valid.
+pub fn validate_rule(kind: ScanRuleKind, pattern: &str) -> Result<()> {
+    RuleMatcher::compile(kind, pattern).map(|_| ())
+}
+
+/// Load and compile the enabled scan rules.
+/// Rules that can't be compiled are skipped.
+pub async fn load_rules(conn: &mut DbConn) -> Result<Vec<CompiledRule>> {
+    Ok(get_enabled_scan_rules(conn)
+        .await?
+        .iter()
+        .filter_map(|rule| match CompiledRule::new(rule) {
+            Ok(it) => Some(it),
+
+            Err(err) => {
+                warn!("Skipping scan rule #{} ({}): {}", rule.id, rule.name, err);
+                None
+            }
+        })
+        .collect())
+}
+
+/// Checks a file against the scan rules as it's read, a window at a time.
+struct RuleChecker<'a> {
+    /// The rules to check.
+    rules: &'a [CompiledRule],
+
+    /// Whether each byte or regex rule has matched so far.
+    matched: Vec<bool>,
+
+    /// Whether the file has been valid UTF-8 so far.
+    /// Regex rules only match text files.
+    text: bool,
+
+    /// The hasher for hash rules, if there are any.
+    hasher: Option<FileHasher>,
+}
+
+impl<'a> RuleChecker<'a> {
+    /// Create a checker for a new file.
+    fn new(rules: &'a [CompiledRule]) -> Self {
+        Self {
+            rules,
+            matched: vec![false; rules.len()],
+            text: true,
+            hasher: rules
+                .iter()
+                .any(|v| matches!(v.matcher, RuleMatcher::Hash(_)))
+                .then(FileHasher::new),
+        }
+    }
+
+    /// Check the next window of the file. The first `carried` bytes of the window
+    /// were already part of the previous one.
+    fn update(&mut self, window: &[u8], carried: usize) {
+        if let Some(hasher) = &mut self.hasher {
+            hasher.update(&window[carried..]);
+        }
+
+        let text = if self.text {
+            window_text(window, carried)
+        } else {
+            None
+        };
+
+        self.text = text.is_some();
+
+        for (rule, matched) in self.rules.iter().zip(&mut self.matched) {
+            *matched = *matched
+                || match &rule.matcher {
+                    RuleMatcher::Bytes(re) => re.is_match(window),
+                    RuleMatcher::Regex(re) => text.is_some_and(|text| re.is_match(text)),
+                    RuleMatcher::Hash(_) => false,
+                };
+        }
+    }
+
+    /// Get the rules that matched the whole file.
+    fn finish(self) -> Vec<&'a CompiledRule> {
+        let hashes = self.hasher.map(FileHasher::finish);
+
+        self.rules
+            .iter()
+            .zip(self.matched)
+            .filter(|(rule, matched)| match &rule.matcher {
+                RuleMatcher::Bytes(_) => *matched,
+                RuleMatcher::Regex(_) => self.text && *matched,
+
+                RuleMatcher::Hash(hash) => hashes
+                    .as_ref()
+                    .is_some_and(|v| [&v.sha1, &v.sha256, &v.sha512].contains(&hash)),
+            })
+            .map(|(rule, _)| rule)
+            .collect()
+    }
+}
+
+/// Get a window's text, if it's valid UTF-8. Characters that are cut off at
+/// either end of the window are skipped, since the neighbouring windows have them.
+fn window_text(window: &[u8], carried: usize) -> Option<&str> {
+    // Only a window that continues the previous one can start in the middle of a character.
+    let start = if carried > 0 {
+        window
+            .iter()
+            .take(3)
+            .take_while(|b| **b & 0xc0 == 0x80)
+            .count()
+    } else {
+        0
+    };
+
+    match std::str::from_utf8(&window[start..]) {
+        Ok(text) => Some(text),
+
+        Err(err) if err.error_len().is_none() => {
+            std::str::from_utf8(&window[start..start + err.valid_up_to()]).ok()
+        }
+
+        Err(_) => None,
+    }
+}
+
+/// Read up to `limit` bytes from `reader`, passing them to `visit` one window at a time,
+/// along with the number of bytes carried over from the previous window.
+/// Returns the number of bytes that were read.
+fn read_windows(
+    reader: impl Read,
+    limit: u64,
+    mut visit: impl FnMut(&[u8], usize) -> io::Result<()>,
+) -> io::Result<u64> {
+    let mut reader = reader.take(limit);
+    let mut window = Vec::with_capacity(SCAN_WINDOW_OVERLAP + SCAN_WINDOW_SIZE);
+    let mut total = 0;
+
+    loop {
+        let carried = window.len();
+        let read = (&mut reader)
+            .take(SCAN_WINDOW_SIZE as u64)
+            .read_to_end(&mut window)?;
+
+        total += read as u64;
+
+        if read > 0 || total == 0 {
+            visit(&window, carried)?;
+        }
+
+        if read < SCAN_WINDOW_SIZE {
+            return Ok(total);
+        }
+
+        window.drain(..window.len() - SCAN_WINDOW_OVERLAP.min(window.len()));
+    }
+}
+
+/// A scanner for a single uploaded archive.
+struct ArchiveScanner<'a> {
+    /// The name of the uploaded file.
+    root: &'a str,
+
+    /// The scan configuration.
+    config: &'a ScanConfig,
+
+    /// The moderators' scan rules.
+    rules: &'a [CompiledRule],
+
+    /// The problems found so far.
+    findings: Vec<ArchiveFinding>,
+
+    /// The number of bytes unpacked so far.
+    unpacked: u64,
+
+    /// The number of entries seen so far.
+    entries: usize,
+
+    /// Whether scanning stopped early, because the archive looks like a bomb.
+    aborted: bool,
+}
+
+impl ArchiveScanner<'_> {
+    /// Get the path of an archive from the prefix of its entries.
+    fn archive_path(&self, prefix: &str) -> String {
+        match prefix.strip_suffix(NESTED_SEPARATOR) {
+            Some(path) => path.to_string(),
+            None => self.root.to_string(),
+        }
+    }
+
+    /// Record a problem.
+    fn report(
+        &mut self,
+        kind: ScanFindingKind,
+        path: impl Into<String>,
+        message: impl Into<String>,
+    ) {
+        self.findings.push(ArchiveFinding {
+            kind,
+            path: path.into(),
+            message: message.into(),
+            rule_id: None,
+        });
+    }
+
+    /// Record an archive bomb, and stop scanning.
+    fn abort(&mut self, path: impl Into<String>, message: impl Into<String>) {
+        self.report(ScanFindingKind::ArchiveBomb, path, message);
+        self.aborted = true;
+    }
+
+    /// Count an entry, stopping if there are too many.
+    fn count_entry(&mut self, archive: &str) -> bool {
+        self.entries += 1;
+
+        if self.entries > self.config.max_entries {
+            self.abort(
+                archive,
+                format!(
+                    "The archive has more than {} entries.",
+                    self.config.max_entries
+                ),
+            );
+        }
+
+        !self.aborted
+    }
+
+    /// Scan an archive. `prefix` is prepended to the paths of its entries,
+    /// and `size` is the archive's (compressed) size.
+    fn scan(&mut self, kind: ArchiveKind, prefix: &str, file: File, size: u64, depth: usize) {
+        match kind {
+            ArchiveKind::Zip => self.scan_zip(prefix, file, depth),
+            ArchiveKind::TarGz => self.scan_tar(prefix, file, size, depth),
+        }
+    }
+
+    /// Scan a zip archive.
+    fn scan_zip(&mut self, prefix: &str, file: File, depth: usize) {
+        let archive_path = self.archive_path(prefix);
+
+        let mut archive = match ZipArchive::new(BufReader::new(file)) {
+            Ok(it) => it,
+
+            Err(err) => {
+                self.report(
+                    ScanFindingKind::Unreadable,
+                    archive_path,
+                    format!("The archive couldn't be opened: {}", err),
+                );
+
+                return;
+            }
+        };
+
+        for idx in 0..archive.len() {
+            if !self.count_entry(&archive_path) {
+                return;
+            }
+
+            let entry = match archive.by_index(idx) {
+                Ok(it) => it,
+
+                Err(err) => {
+                    self.report(
+                        ScanFindingKind::Unreadable,
+                        &archive_path,
+                        format!("Entry #{} couldn't be read: {}", idx, err),
+                    );
+
+                    continue;
+                }
+            };
+
+            let name = entry.name().to_string();
+            let path = format!("{}{}", prefix, name);
+
+            self.check_path(&path, &name);
+
+            // The upper 4 bits of the mode are the file type, and 0o12 is a symlink.
+            if entry
+                .unix_mode()
+                .is_some_and(|mode| mode & 0o170000 == 0o120000)
+            {
+                self.report(
+                    ScanFindingKind::Symlink,
+                    path,
+                    "The entry is a symbolic link.",
+                );
+
+                continue;
+            }
+
+            if entry.is_dir() {
+                continue;
+            }
+
+            let (size, packed) = (entry.size(), entry.compressed_size());
+
+            if size >= MIN_RATIO_CHECK_SIZE
+                && size > packed.saturating_mul(self.config.max_compression_ratio)
+            {
+                self.abort(
+                    path,
+                    format!(
+                        "The entry is compressed more than {}:1 ({} bytes to {} bytes).",
+                        self.config.max_compression_ratio, size, packed
+                    ),
+                );
+
+                return;
+            }
+
+            self.scan_entry(&path, entry, depth);
+
+            if self.aborted {
+                return;
+            }
+        }
+    }
+
+    /// Scan a gzipped tarball.
+    fn scan_tar(&mut self, prefix: &str, file: File, packed: u64, depth: usize) {
+        let archive_path = self.archive_path(prefix);
+        let start = self.unpacked;
+        let mut archive = Archive::new(GzDecoder::new(BufReader::new(file)));
+
+        let entries = match archive.entries() {
+            Ok(it) => it,
+
+            Err(err) => {
+                self.report(
+                    ScanFindingKind::Unreadable,
+                    archive_path,
+                    format!("The archive couldn't be opened: {}", err),
+                );
+
+                return;
+            }
+        };
+
+        for entry in entries {
+            if !self.count_entry(&archive_path) {
+                return;
+            }
+
+            let entry = match entry {
+                Ok(it) => it,
+
+                Err(err) => {
+                    self.report(
+                        ScanFindingKind::Unreadable,
+                        archive_path,
+                        format!("The archive couldn't be read: {}", err),
+                    );
+
+                    return;
+                }
+            };
+
+            let name = String::from_utf8_lossy(&entry.path_bytes()).to_string();
+            let path = format!("{}{}", prefix, name);
+            let kind = entry.header().entry_type();
+
+            self.check_path(&path, &name);
+
+            if kind.is_symlink() || kind.is_hard_link() {
+                self.report(
+                    ScanFindingKind::Symlink,
+                    path,
+                    "The entry is a symbolic or hard link.",
+                );
+
+                continue;
+            }
+
+            if !kind.is_file() {
+                continue;
+            }
+
+            self.scan_entry(&path, entry, depth);
+
+            if self.aborted {
+                return;
+            }
+
+            // Tarballs are compressed as a whole, so the ratio is checked for
+            // everything unpacked from this archive so far.
+            let size = self.unpacked - start;
+
+            if size >= MIN_RATIO_CHECK_SIZE
+                && size > packed.saturating_mul(self.config.max_compression_ratio)
+            {
+                self.abort(
+                    archive_path,
+                    format!(
+                        "The archive is compressed more than {}:1 ({} bytes to {} bytes).",
+                        self.config.max_compression_ratio, size, packed
+                    ),
+                );
+
+                return;
+            }
+        }
+    }
+
+    /// Read an entry and check its contents, stopping if the total unpacked size
+    /// gets too large. Only a window of the entry is held in memory at a time, and
+    /// nested archives are copied to a temporary file so they can be scanned afterwards.
+    fn scan_entry(&mut self, path: &str, entry: impl Read, depth: usize) {
+        let budget = self.config.max_unpacked_size.saturating_sub(self.unpacked);
+        let mut checker = RuleChecker::new(self.rules);
+        let mut nested = None;
+        let mut first = true;
+
+        let read = read_windows(entry, budget + 1, |window, carried| {
+            if first {
+                first = false;
+
+                if let Some(kind) = self.check_file(path, window, depth) {
+                    nested = Some((kind, tempfile::tempfile()?));
+                }
+            }
+
+            checker.update(window, carried);
+
+            if let Some((_, file)) = &mut nested {
+                file.write_all(&window[carried..])?;
+            }
+
+            Ok(())
+        });
+
+        let size = match read {
+            Ok(size) => size,
+
+            Err(err) => {
+                self.report(
+                    ScanFindingKind::Unreadable,
+                    path,
+                    format!("The entry couldn't be read: {}", err),
+                );
+
+                return;
+            }
+        };
+
+        self.unpacked += size;
+
+        if size > budget {
+            self.abort(
+                path,
+                format!(
+                    "The archive unpacks to more than {} bytes.",
+                    self.config.max_unpacked_size
+                ),
+            );
+
+            return;
+        }
+
+        self.check_rules(path, checker);
+
+        let Some((kind, mut file)) = nested else {
+            return;
+        };
+
+        if let Err(err) = file.rewind() {
+            self.report(
+                ScanFindingKind::Unreadable,
+                path,
+                format!("The entry couldn't be read: {}", err),
+            );
+
+            return;
+        }
+
+        self.scan(
+            kind,
+            &format!("{}{}", path, NESTED_SEPARATOR),
+            file,
+            size,
+            depth + 1,
+        );
+    }
+
+    /// Check that an entry's path stays inside of the archive.
+    fn check_path(&mut self, path: &str, name: &str) {
+        let name = name.replace('\\', "/");
+        let bytes = name.as_bytes();
+
+        let absolute = name.starts_with('/')
+            || (bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':');
+
+        if absolute {
+            self.report(
+                ScanFindingKind::PathTraversal,
+                path,
+                "The entry has an absolute path.",
+            );
+        } else if name.split('/').any(|part| part == "..") {
+            self.report(
+                ScanFindingKind::PathTraversal,
+                path,
+                "The entry's path leaves the archive.",
+            );
+        }
+    }
+
+    /// Record the scan rules that a file matched.
+    fn check_rules(&mut self, path: &str, checker: RuleChecker) {
+        for rule in checker.finish() {
+            self.findings.push(ArchiveFinding {
+                kind: ScanFindingKind::RuleMatch,
+                path: path.into(),
+                message: format!("The file matched the `{}` scan rule.", rule.name),
+                rule_id: Some(rule.id),
+            });
+        }
+    }
+
+    /// Check a file inside of an archive from its name and the first window of its
+    /// contents. Returns the kind of archive it is, if it should be scanned too.
+    fn check_file(&mut self, path: &str, head: &[u8], depth: usize) -> Option<ArchiveKind> {
+        let name = path.rsplit(['/', '\\']).next().unwrap_or(path);
+
+        let extension = name
+            .rsplit_once('.')
+            .map(|(_, ext)| ext.to_lowercase())
+            .unwrap_or_default();
+
+        if self
+            .config
+            .denied_extensions
+            .iter()
+            .any(|v| v.trim_start_matches('.').eq_ignore_ascii_case(&extension))
+        {
+            self.report(
+                ScanFindingKind::ForbiddenFile,
+                path,
+                format!("Files with the `.{}` extension aren't allowed.", extension),
+            );
+        } else if self.config.deny_native_executables && is_native_executable(head) {
+            self.report(
+                ScanFindingKind::ForbiddenFile,
+                path,
+                "The entry is a native executable.",
+            );
+        }
+
+        let kind = ArchiveKind::detect(name, head)?;
+
+        if depth >= self.config.max_nesting_depth {
+            self.report(
+                ScanFindingKind::NestedArchive,
+                path,
+                format!(
+                    "Archives can only be nested {} level(s) deep.",
+                    self.config.max_nesting_depth
+                ),
+            );
+
+            return None;
+        }
+
+        Some(kind)
+    }
+}
+
+/// Check whether a file is an ELF, PE, or Mach-O executable.
+/// Universal Mach-O binaries are skipped, since they share a magic number with Java classes.
+fn is_native_executable(data: &[u8]) -> bool {
+    if data.starts_with(b"\x7fELF") {
+        return true;
+    }
+
+    const MACH_O: [[u8; 4]; 4] = [
+        [0xfe, 0xed, 0xfa, 0xce],
+        [0xfe, 0xed, 0xfa, 0xcf],
+        [0xce, 0xfa, 0xed, 0xfe],
+        [0xcf, 0xfa, 0xed, 0xfe],
+    ];
+
+    if MACH_O.iter().any(|magic| data.starts_with(magic)) {
+        return true;
+    }
+
+    // PE files start with a DOS header, which points at the "PE\0\0" signature.
+    if data.starts_with(b"MZ") && data.len() >= 0x40 {
+        let offset = u32::from_le_bytes([data[0x3c], data[0x3d], data[0x3e], data[0x3f]]) as usize;
+
+        return data.get(offset..offset + 4) == Some(&b"PE\0\0"[..]);
+    }
+
+    false
+}
+
+/// Scan a file for problems.
+/// The file itself, and every file inside of it (if it's a `.zip`, `.jar`,
+/// or `.tar.gz` archive), is checked against the scan rules.
+/// The file is read a window at a time, so it's never held in memory all at once.
+pub fn scan_file(
+    config: &ScanConfig,
+    rules: &[CompiledRule],
+    file_name: &str,
+    mut file: File,
+) -> Vec<ArchiveFinding> {
+    let mut scanner = ArchiveScanner {
+        root: file_name,
+        config,
+        rules,
+        findings: Vec::new(),
+        unpacked: 0,
+        entries: 0,
+        aborted: false,
+    };
+
+    let mut checker = RuleChecker::new(rules);
+    let mut kind = None;
+    let mut first = true;
+
+    let read = read_windows(&mut file, u64::MAX, |window, carried| {
+        if first {
+            first = false;
+            kind = ArchiveKind::detect(file_name, window);
+        }
+
+        checker.update(window, carried);
+
+        Ok(())
+    });
+
+    let size = match read.and_then(|size| file.rewind().map(|_| size)) {
+        Ok(size) => size,
+
+        Err(err) => {
+            scanner.report(
+                ScanFindingKind::Unreadable,
+                file_name,
+                format!("The file couldn't be read: {}", err),
+            );
+
+            return scanner.findings;
+        }
+    };
+
+    scanner.check_rules(file_name, checker);
+
+    if let Some(kind) = kind {
+        scanner.scan(kind, "", file, size, 0);
+    }
+
+    scanner.findings
+}
+
+/// Scan a staged upload, before it's committed to storage.
+/// The upload is copied to a temporary file first, so it's never held in memory.
+/// This returns nothing if scanning is disabled.
+pub async fn scan_upload(
+    config: &ScanConfig,
+    rules: &[CompiledRule],
+    file_name: &str,
+    upload: &StagedUpload,
+    bucket: &Bucket,
+) -> Result<Vec<ArchiveFinding>> {
+    if !config.enabled
+        || (rules.is_empty() && ArchiveKind::detect(file_name, &upload.prefix).is_none())
+    {
+        return Ok(Vec::new());
+    }
+
+    let file = upload.spool(bucket).await?;
+    let config = config.clone();
+    let rules = rules.to_vec();
+    let file_name = file_name.to_string();
+
+    Ok(tokio::task::spawn_blocking(move || scan_file(&config, &rules, &file_name, file)).await?)
+}
+
+/// The maximum number of findings stored for a single file.
+/// Rule matches are kept first, and anything past this is dropped.
+const MAX_STORED_FINDINGS: usize = 1000;
+
+/// The number of findings inserted at once, to stay well under
+/// PostgreSQL's limit on query parameters.
+const FINDING_CHUNK_SIZE: usize = 1000;
+
+/// The maximum number of rule matches listed in a system comment.
+const MAX_LISTED_MATCHES: usize = 50;
+
+/// Store the findings for a file, replacing any old ones.
+/// Duplicate findings are only stored once, and at most [`MAX_STORED_FINDINGS`] are kept.
+/// If there are any, the project is put under review in the moderation queue.
+/// If any scan rules matched, the file's version is blocked from being downloaded,
+/// and a system comment is left for the moderators.
+/// This should be called in the same transaction that creates the file.
+pub async fn save_findings(
+    project: &Project,
+    file: &ProjectFile,
+    findings: Vec<ArchiveFinding>,
+    conn: &mut DbConn,
+) -> Result<()> {
+    diesel::delete(scan_findings::table)
+        .filter(scan_findings::file_id.eq(file.id))
+        .execute(conn)
+        .await?;
+
+    if findings.is_empty() {
+        return Ok(());
+    }
+
+    let (mut findings, others): (Vec<_>, Vec<_>) = findings
+        .into_iter()
+        .unique()
+        .partition(|v| v.kind == ScanFindingKind::RuleMatch);
+
+    let matched = findings
+        .iter()
+        .map(|v| format!("- `{}`: {}", v.path, v.message))
+        .collect::<Vec<_>>();
+
+    findings.extend(others);
+
+    if findings.len() > MAX_STORED_FINDINGS {
+        warn!(
+            "Only storing {} of the {} scan findings for file {} (id: {})",
+            MAX_STORED_FINDINGS,
+            findings.len(),
+            file.file_name,
+            file.id
+        );
+
+        findings.truncate(MAX_STORED_FINDINGS);
+    }
+
+    let findings = findings
+        .into_iter()
+        .map(|v| v.into_new(file.id))
+        .collect::<Vec<_>>();
+
+    for chunk in findings.chunks(FINDING_CHUNK_SIZE) {
+        insert_into(scan_findings::table)
+            .values(chunk)
+            .execute(conn)
+            .await?;
+    }
+
+    get_or_create_moderation_queue_item(project, conn).await?;
+    set_moderation_status(project, ModerationQueueStatus::UnderReview, conn).await?;
+
+    if !matched.is_empty() {
+        update(project_versions::table)
+            .filter(project_versions::id.eq(file.version_id))
+            .set(project_versions::blocked.eq(true))
+            .execute(conn)
+            .await?;
+
+        let mut listed = matched
+            .iter()
+            .take(MAX_LISTED_MATCHES)
+            .cloned()
+            .collect::<Vec<_>>();
+
+        if matched.len() > MAX_LISTED_MATCHES {
+            listed.push(format!(
+                "- ...and {} more",
+                matched.len() - MAX_LISTED_MATCHES
+            ));
+        }
+
+        create_system_comment(
+            project,
+            format!(
+                "`{}` matched {} scan rule(s), so its version can't be downloaded until a moderator clears it.\n\n{}",
+                file.file_name,
+                matched.len(),
+                listed.join("\n")
+            ),
+            conn,
+        )
+        .await?;
+    }
+
+    Ok(())
+}
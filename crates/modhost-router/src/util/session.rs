@@ -0,0 +1,139 @@
+//! Utilities for login sessions.
+
+use axum::http::{HeaderMap, HeaderValue, header::USER_AGENT};
+use ipnet::IpNet;
+use modhost_auth::login::{LOGIN_COOKIE, LOGIN_STATE_EXPIRE_TIME};
+use modhost_core::{AppError, Result};
+use std::net::{IpAddr, SocketAddr};
+use url::Url;
+
+/// The longest user agent that's stored for a session.
+pub const MAX_USER_AGENT_LENGTH: usize = 512;
+
+/// Get the user agent a request was made with, if it sent one.
+pub fn user_agent(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(USER_AGENT)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.chars().take(MAX_USER_AGENT_LENGTH).collect())
+}
+
+/// Get the IP address a request came from.
+/// If the request came through one of the `trusted` proxies, the client's address is
+/// taken from the `X-Forwarded-For` (or `X-Real-IP`) header instead of the proxy's.
+/// Those headers are ignored otherwise, since anyone can send them.
+pub fn client_ip(headers: &HeaderMap, addr: SocketAddr, trusted: &[IpNet]) -> String {
+    let is_trusted = |ip: &IpAddr| trusted.iter().any(|net| net.contains(ip));
+    let peer = addr.ip();
+
+    if !is_trusted(&peer) {
+        return peer.to_string();
+    }
+
+    let forwarded = headers
+        .get_all("X-Forwarded-For")
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .collect::<Vec<_>>();
+
+    if forwarded.is_empty() {
+        return headers
+            .get("X-Real-IP")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse::<IpAddr>().ok())
+            .unwrap_or(peer)
+            .to_string();
+    }
+
+    // Each proxy adds the address it got the request from to the end, so the client
+    // is the last address that wasn't added by a trusted proxy.
+    let mut client = peer;
+
+    for ip in forwarded.into_iter().rev() {
+        let Ok(ip) = ip.trim().parse::<IpAddr>() else {
+            break;
+        };
+
+        client = ip;
+
+        if !is_trusted(&ip) {
+            break;
+        }
+    }
+
+    client.to_string()
+}
+
+/// Create the `Set-Cookie` header value for a session token.
+pub fn session_cookie(token: impl AsRef<str>, host: impl AsRef<str>) -> Result<HeaderValue> {
+    Ok(HeaderValue::from_str(&format!(
+        "auth-token={}; HttpOnly; Path=/; Domain={}",
+        token.as_ref(),
+        sanitize_port(host.as_ref())
+    ))?)
+}
+
+/// Create the `Set-Cookie` header value that removes the session cookie.
+pub fn clear_session_cookie(host: impl AsRef<str>) -> Result<HeaderValue> {
+    Ok(HeaderValue::from_str(&format!(
+        "auth-token=; HttpOnly; Path=/; Domain={}; Max-Age=0",
+        sanitize_port(host.as_ref())
+    ))?)
+}
+
+/// Create the `Set-Cookie` header value that ties a login request to the browser.
+/// It holds the request's nonce and its PKCE verifier, which must never be sent to the provider.
+pub fn login_cookie(
+    nonce: impl AsRef<str>,
+    verifier: impl AsRef<str>,
+    secure: bool,
+) -> Result<HeaderValue> {
+    Ok(HeaderValue::from_str(&format!(
+        "{}={}.{}; HttpOnly; Path=/api/v1/auth; Max-Age={}; SameSite=Lax{}",
+        LOGIN_COOKIE,
+        nonce.as_ref(),
+        verifier.as_ref(),
+        LOGIN_STATE_EXPIRE_TIME,
+        if secure { "; Secure" } else { "" }
+    ))?)
+}
+
+/// Create the `Set-Cookie` header value that removes the login cookie.
+pub fn clear_login_cookie() -> Result<HeaderValue> {
+    Ok(HeaderValue::from_str(&format!(
+        "{}=; HttpOnly; Path=/api/v1/auth; Max-Age=0",
+        LOGIN_COOKIE
+    ))?)
+}
+
+/// Resolve where a login is allowed to redirect to, relative to the server's own `origin`.
+/// Only the server itself and the origins in the `allowlist` are allowed,
+/// since the new token is sent along in the URL.
+pub fn resolve_redirect(to: impl AsRef<str>, origin: &str, allowlist: &[String]) -> Result<Url> {
+    let to = to.as_ref();
+    let base = Url::parse(origin)?;
+
+    let url = base
+        .join(to)
+        .map_err(|_| AppError::RedirectNotAllowed(to.into()))?;
+
+    let allowed = url.origin() == base.origin()
+        || allowlist
+            .iter()
+            .filter_map(|v| Url::parse(v).ok())
+            .any(|v| v.origin() == url.origin());
+
+    if allowed && matches!(url.scheme(), "http" | "https") {
+        Ok(url)
+    } else {
+        Err(AppError::RedirectNotAllowed(to.into()))
+    }
+}
+
+fn sanitize_port(host: &str) -> String {
+    match host.split_once(":") {
+        Some((domain, _port)) => domain.to_string(),
+        None => host.to_string(),
+    }
+}
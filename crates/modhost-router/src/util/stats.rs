@@ -11,7 +11,7 @@ use modhost_core::{
 use modhost_db::{DbConn, gallery_images, project_versions, projects, users, version_files};
 use modhost_search::{Index, MeiliProject};
 use modhost_server_core::state::AppState;
-use object_store::{ObjectStore, aws::AmazonS3};
+use object_store::ObjectStore;
 use once_cell::sync::Lazy;
 use std::sync::atomic::{AtomicBool, Ordering};
 use tokio::{
@@ -78,8 +78,8 @@ pub fn start_stats_thread(state: &AppState) -> JoinHandle<Result<()>> {
             let mut conn = pool.get().await?;
 
             let stats = fetch_stats(
-                &projects_bucket,
-                &gallery_bucket,
+                projects_bucket.as_ref(),
+                gallery_bucket.as_ref(),
                 &search_projects,
                 &mut conn,
             )
@@ -98,8 +98,8 @@ pub fn start_stats_thread(state: &AppState) -> JoinHandle<Result<()>> {
 
 /// Fetch admin statistics.
 pub async fn fetch_stats(
-    projects_bucket: &AmazonS3,
-    gallery_bucket: &AmazonS3,
+    projects_bucket: &dyn ObjectStore,
+    gallery_bucket: &dyn ObjectStore,
     search_projects: &Index,
     conn: &mut DbConn,
 ) -> Result<AdminStats> {
@@ -0,0 +1,131 @@
+//! Utilities for streaming file uploads into storage.
+
+use axum::{body::Bytes, extract::multipart::Field};
+use futures::TryStreamExt;
+use modhost_config::Bucket;
+use modhost_core::{AppError, Result};
+use modhost_db_util::files::{FileHasher, FileHashes};
+use object_store::{WriteMultipart, path::Path};
+use std::{fs::File, io::Seek};
+use tokio::io::AsyncWriteExt;
+use uuid::Uuid;
+
+/// The response header that lists the warnings for an accepted upload, as a JSON array.
+pub const UPLOAD_WARNINGS_HEADER: &str = "X-Upload-Warnings";
+
+/// The maximum number of parts uploaded concurrently for a single file.
+const MAX_CONCURRENT_PARTS: usize = 4;
+
+/// A file that has been streamed into a temporary location in a bucket,
+/// but hasn't been moved to its final (content-addressed) location yet.
+pub struct StagedUpload {
+    /// The temporary path of the file.
+    pub path: Path,
+
+    /// The hashes of the file.
+    pub hashes: FileHashes,
+
+    /// The size of the file, in bytes.
+    pub size: i64,
+
+    /// The first bytes of the file, for the upload validators.
+    pub prefix: Bytes,
+}
+
+impl StagedUpload {
+    /// Stream a multipart field into a temporary location in the bucket.
+    /// The field's hashes are computed as it's uploaded, and at most
+    /// `prefix_size` bytes from the start of the file are kept in memory.
+    /// If the field is larger than `max_size` bytes, the upload is aborted.
+    pub async fn stream(
+        mut field: Field<'_>,
+        bucket: &Bucket,
+        max_size: u64,
+        prefix_size: usize,
+    ) -> Result<Self> {
+        let path: Path = format!("/uploads/{}", Uuid::new_v4()).into();
+        let mut upload = WriteMultipart::new(bucket.put_multipart(&path).await?);
+        let mut hasher = FileHasher::new();
+        let mut prefix = Vec::new();
+        let mut size = 0u64;
+
+        loop {
+            let chunk = match field.chunk().await {
+                Ok(Some(chunk)) => chunk,
+                Ok(None) => break,
+
+                Err(err) => {
+                    upload.abort().await?;
+                    return Err(err.into());
+                }
+            };
+
+            size += chunk.len() as u64;
+
+            if size > max_size {
+                upload.abort().await?;
+                return Err(AppError::UploadTooLarge(max_size));
+            }
+
+            if prefix.len() < prefix_size {
+                let end = chunk.len().min(prefix_size - prefix.len());
+
+                prefix.extend_from_slice(&chunk[..end]);
+            }
+
+            hasher.update(&chunk);
+
+            if let Err(err) = upload.wait_for_capacity(MAX_CONCURRENT_PARTS).await {
+                upload.abort().await?;
+                return Err(err.into());
+            }
+
+            upload.write(&chunk);
+        }
+
+        upload.finish().await?;
+
+        Ok(Self {
+            path,
+            hashes: hasher.finish(),
+            size: size as i64,
+            prefix: prefix.into(),
+        })
+    }
+
+    /// Download the file into an anonymous temporary file, so it can be read
+    /// (and seeked through) without holding all of it in memory.
+    /// The temporary file is deleted when it's dropped.
+    pub async fn spool(&self, bucket: &Bucket) -> Result<File> {
+        let mut stream = bucket.get(&self.path).await?.into_stream();
+        let mut file = tokio::fs::File::from_std(tempfile::tempfile()?);
+
+        while let Some(chunk) = stream.try_next().await? {
+            file.write_all(&chunk).await?;
+        }
+
+        file.flush().await?;
+
+        let mut file = file.into_std().await;
+
+        file.rewind()?;
+
+        Ok(file)
+    }
+
+    /// Move the file to its final location (`/{sha1}`) in the bucket.
+    pub async fn commit(self, bucket: &Bucket) -> Result<()> {
+        bucket
+            .rename(&self.path, &format!("/{}", self.hashes.sha1).into())
+            .await?;
+
+        Ok(())
+    }
+
+    /// Delete the temporary file from the bucket.
+    pub async fn discard(self, bucket: &Bucket) -> Result<()> {
+        bucket.delete(&self.path).await?;
+
+        Ok(())
+    }
+}
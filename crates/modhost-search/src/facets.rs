@@ -2,12 +2,13 @@
 
 use chrono::NaiveDateTime;
 use modhost_core::{AppError, Result};
-use modhost_db::ProjectVisibility;
+use modhost_db::{ProjectVisibility, VersionChannel};
 
 /// A facet/filter.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema, ToResponse)]
 pub enum Facet {
     /// Filters game versions. It will match any provided.
+    /// The router expands ranges and wildcards (like `1.20.x`) before this is created.
     GameVersions(Vec<String>),
 
     /// Filters mod loaders. It will match any provided.
@@ -16,6 +17,10 @@ pub enum Facet {
     /// Filter by tags. It will match any provided.
     Tags(Vec<String>),
 
+    /// Filter by release channels. It will match projects with a version
+    /// in any of the channels provided.
+    Channels(Vec<VersionChannel>),
+
     /// Filter by a range of dates when the project was published.
     /// The first element is the minimum date, the second is the maximum.
     /// The comparison is `(item.published >= a && item.published <= b)`
@@ -124,6 +129,10 @@ impl Facet {
                 Self::GameVersions(v) => format!("game_versions IN [{}]", v.join(", ")),
                 Self::Loaders(v) => format!("loaders IN [{}]", v.join(", ")),
                 Self::Tags(v) => format!("tags IN [{}]", v.join(", ")),
+                Self::Channels(v) => format!(
+                    "channels IN [{}]",
+                    v.iter().map(|v| v.as_str()).collect::<Vec<_>>().join(", ")
+                ),
                 Self::Published(start, end) => format!(
                     "(created_at >= {}) AND (created_at <= {})",
                     start.and_utc().timestamp(),
@@ -152,6 +161,10 @@ impl Facet {
             "loaders" => Ok(Facet::Loaders(it.1)),
             "tags" => Ok(Facet::Tags(it.1)),
 
+            "channels" => Ok(Facet::Channels(
+                it.1.iter().map(|v| v.parse()).collect::<Result<Vec<_>>>()?,
+            )),
+
             "published" => {
                 if it.1.len() == 2 {
                     Ok(Self::Published(it.1[0].parse()?, it.1[1].parse()?))
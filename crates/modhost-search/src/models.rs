@@ -2,7 +2,7 @@
 
 use chrono::NaiveDateTime;
 use itertools::Itertools;
-use modhost_db::{Project, ProjectData, ProjectVersion, ProjectVisibility, User};
+use modhost_db::{Project, ProjectData, ProjectVersion, ProjectVisibility, User, VersionChannel};
 
 /// A project for search indexing.
 #[derive(
@@ -66,6 +66,10 @@ pub struct MeiliProject {
     /// A list of game versions this project supports (all versions).
     pub game_versions: Vec<String>,
 
+    /// A list of release channels this project has versions in.
+    #[serde(default)]
+    pub channels: Vec<VersionChannel>,
+
     /// A list of tags for this project.
     pub tags: Vec<String>,
 }
@@ -125,6 +129,12 @@ impl MeiliProject {
                 .sorted()
                 .dedup()
                 .collect_vec(),
+            channels: versions
+                .iter()
+                .map(|v| v.channel)
+                .sorted()
+                .dedup()
+                .collect_vec(),
             tags: pkg.tags.into_iter().flatten().collect_vec(),
             authors,
             versions,
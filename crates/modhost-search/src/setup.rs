@@ -39,6 +39,7 @@ impl MeilisearchService {
                 "created_at",
                 "updated_at",
                 "tags",
+                "channels",
             ])
             .await?;
 
@@ -63,6 +64,7 @@ impl MeilisearchService {
                 "created_at",
                 "updated_at",
                 "tags",
+                "channels",
             ])
             .await?;
 
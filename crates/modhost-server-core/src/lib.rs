@@ -15,6 +15,12 @@ pub mod glue;
 pub mod macros;
 pub mod models;
 pub mod state;
+pub mod validation;
 pub mod worker;
 
-modhost_core::utoipa_types![models::GameVersion, models::ModLoader, models::Tag,];
+modhost_core::utoipa_types![
+    models::GameVersion,
+    models::ModLoader,
+    models::Tag,
+    validation::ValidationVerdict,
+];
@@ -1,5 +1,9 @@
 //! Models relating to game versions.
 
+use chrono::{DateTime, Utc};
+use modhost_core::{AppError, Result};
+use std::cmp::Ordering;
+
 /// A game version.
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema, ToResponse)]
 pub struct GameVersion {
@@ -8,4 +12,234 @@ pub struct GameVersion {
 
     /// Whether this version is a beta version.
     pub beta: bool,
+
+    /// When this version was released, if known.
+    /// This is used to order game versions.
+    #[serde(default)]
+    pub release_date: Option<DateTime<Utc>>,
+}
+
+/// Sort game versions from newest to oldest by their release dates.
+/// Versions without a release date keep their relative order,
+/// and are placed after the versions that have one.
+pub fn sort_game_versions(versions: &mut [GameVersion]) {
+    versions.sort_by(|a, b| match (a.release_date, b.release_date) {
+        (Some(a), Some(b)) => b.cmp(&a),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    });
+}
+
+/// Expand game version specifiers into a list of game version IDs.
+/// `versions` must be ordered from newest to oldest (see [`sort_game_versions`]).
+///
+/// The supported syntax is:
+/// - A plain version ID, like `1.20.1`, which is kept as-is.
+/// - A wildcard, like `1.20.x` or `1.20.*`, which matches `1.20` and every `1.20.*` version.
+/// - An inclusive range, like `1.19.2..1.20.1`.
+/// - Space-separated comparisons, like `>=1.20 <1.21`, using `>`, `>=`, `<`, `<=`, and `=`.
+///
+/// Wildcards, ranges, and comparisons skip beta versions, and must match at least one
+/// game version. The bounds of ranges and comparisons must be known game versions.
+pub fn expand_game_versions(
+    specs: impl IntoIterator<Item = impl AsRef<str>>,
+    versions: &[GameVersion],
+) -> Result<Vec<String>> {
+    let mut out = Vec::new();
+
+    for spec in specs {
+        for id in expand_game_version(spec.as_ref().trim(), versions)? {
+            if !out.contains(&id) {
+                out.push(id);
+            }
+        }
+    }
+
+    Ok(out)
+}
+
+/// Expand a single game version specifier.
+fn expand_game_version(spec: &str, versions: &[GameVersion]) -> Result<Vec<String>> {
+    match expand_pattern(spec, versions)? {
+        Some(ids) if ids.is_empty() => Err(AppError::InvalidGameVersionRange(spec.into())),
+        Some(ids) => Ok(ids),
+        None if spec.is_empty() => Ok(Vec::new()),
+        None => Ok(vec![spec.into()]),
+    }
+}
+
+/// Expand a wildcard, range, or comparison specifier.
+/// Returns [`None`] if the specifier is a plain version ID.
+fn expand_pattern(spec: &str, versions: &[GameVersion]) -> Result<Option<Vec<String>>> {
+    if let Some((start, end)) = spec.split_once("..") {
+        let start = position(start.trim(), spec, versions)?;
+        let end = position(end.trim(), spec, versions)?;
+        let (newest, oldest) = (start.min(end), start.max(end));
+
+        return Ok(Some(matching(versions, |i| i >= newest && i <= oldest)));
+    }
+
+    if spec.starts_with(['<', '>', '=']) {
+        let mut bounds = Vec::new();
+
+        for part in spec.split_whitespace() {
+            let (op, ver) = if part.starts_with(">=") || part.starts_with("<=") {
+                part.split_at(2)
+            } else if part.starts_with(['<', '>', '=']) {
+                part.split_at(1)
+            } else {
+                return Err(AppError::InvalidGameVersionRange(spec.into()));
+            };
+
+            bounds.push((op, position(ver, spec, versions)?));
+        }
+
+        // Positions count from the newest version, so "greater" means a lower position.
+        return Ok(Some(matching(versions, |i| {
+            bounds.iter().all(|(op, pos)| match *op {
+                ">" => i < *pos,
+                ">=" => i <= *pos,
+                "<" => i > *pos,
+                "<=" => i >= *pos,
+                _ => i == *pos,
+            })
+        })));
+    }
+
+    let mut parts = spec.split('.').collect::<Vec<_>>();
+
+    if parts.len() > 1 && matches!(parts.last(), Some(&"x" | &"X" | &"*")) {
+        parts.pop();
+
+        let prefix = parts.join(".");
+
+        if parts.iter().any(|v| matches!(*v, "x" | "X" | "*")) {
+            return Err(AppError::InvalidGameVersionRange(spec.into()));
+        }
+
+        return Ok(Some(matching(versions, |i| {
+            let id = &versions[i].id;
+
+            *id == prefix || id.starts_with(&format!("{}.", prefix))
+        })));
+    }
+
+    Ok(None)
+}
+
+/// Find the position of a game version used as a bound in a specifier.
+fn position(id: &str, spec: &str, versions: &[GameVersion]) -> Result<usize> {
+    versions
+        .iter()
+        .position(|v| v.id == id)
+        .ok_or_else(|| AppError::InvalidGameVersionRange(spec.into()))
+}
+
+/// Get the IDs of non-beta versions matching a predicate on their position.
+fn matching(versions: &[GameVersion], pred: impl Fn(usize) -> bool) -> Vec<String> {
+    versions
+        .iter()
+        .enumerate()
+        .filter(|(i, v)| !v.beta && pred(*i))
+        .map(|(_, v)| v.id.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Some game versions, from newest to oldest.
+    fn versions() -> Vec<GameVersion> {
+        [
+            ("1.21", false),
+            ("1.21-pre1", true),
+            ("1.20.2", false),
+            ("1.20.2-rc1", true),
+            ("1.20.1", false),
+            ("1.20", false),
+            ("1.19.4", false),
+        ]
+        .into_iter()
+        .map(|(id, beta)| GameVersion {
+            id: id.into(),
+            beta,
+            release_date: None,
+        })
+        .collect()
+    }
+
+    fn expand(spec: &str) -> Result<Vec<String>> {
+        expand_game_versions([spec], &versions())
+    }
+
+    #[test]
+    fn plain_versions_are_kept() {
+        assert_eq!(expand("1.20.1").unwrap(), ["1.20.1"]);
+        assert_eq!(expand("unknown").unwrap(), ["unknown"]);
+        assert!(expand("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn wildcards_skip_betas() {
+        assert_eq!(expand("1.20.x").unwrap(), ["1.20.2", "1.20.1", "1.20"]);
+        assert_eq!(expand("1.20.*").unwrap(), expand("1.20.x").unwrap());
+        assert_eq!(expand("1.x").unwrap().len(), 5);
+    }
+
+    #[test]
+    fn wildcards_must_match() {
+        assert!(matches!(
+            expand("1.99.x"),
+            Err(AppError::InvalidGameVersionRange(_))
+        ));
+
+        assert!(matches!(
+            expand("1.x.x"),
+            Err(AppError::InvalidGameVersionRange(_))
+        ));
+    }
+
+    #[test]
+    fn ranges_skip_betas() {
+        assert_eq!(
+            expand("1.20..1.21").unwrap(),
+            ["1.21", "1.20.2", "1.20.1", "1.20"]
+        );
+        assert_eq!(expand("1.21..1.20").unwrap(), expand("1.20..1.21").unwrap());
+    }
+
+    #[test]
+    fn ranges_need_known_bounds() {
+        assert!(matches!(
+            expand("1.20..1.99"),
+            Err(AppError::InvalidGameVersionRange(_))
+        ));
+    }
+
+    #[test]
+    fn comparisons() {
+        assert_eq!(expand(">=1.20 <1.20.2").unwrap(), ["1.20.1", "1.20"]);
+        assert_eq!(expand(">1.20.2").unwrap(), ["1.21"]);
+        assert_eq!(expand("=1.19.4").unwrap(), ["1.19.4"]);
+
+        assert!(matches!(
+            expand(">1.21"),
+            Err(AppError::InvalidGameVersionRange(_))
+        ));
+
+        assert!(matches!(
+            expand(">=1.20 1.21"),
+            Err(AppError::InvalidGameVersionRange(_))
+        ));
+    }
+
+    #[test]
+    fn duplicates_are_removed() {
+        assert_eq!(
+            expand_game_versions(["1.20.x", "1.20.1", "1.21"], &versions()).unwrap(),
+            ["1.20.2", "1.20.1", "1.20", "1.21"]
+        );
+    }
 }
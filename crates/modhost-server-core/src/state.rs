@@ -2,26 +2,41 @@
 
 use axum::body::Bytes;
 use base64::{Engine, prelude::BASE64_STANDARD};
-use modhost_config::AppConfig;
+use modhost_auth::providers::AuthProviders;
+use modhost_config::{AppConfig, Bucket, BucketSigner};
 use modhost_core::Result;
-use modhost_db::DbPool;
+use modhost_db::{DbPool, ProjectManifest};
 use modhost_search::MeilisearchService;
 use modhost_ui::DEFAULT_FAVICON_PNG;
-use oauth2::{EndpointNotSet, EndpointSet, basic::BasicClient};
-use object_store::aws::AmazonS3;
 use std::{fs, sync::Arc};
+use tokio::sync::Semaphore;
 use utoipa::openapi::OpenApi;
 
-use crate::models::{GameVersion, ModLoader, Tag};
+use crate::{
+    models::{GameVersion, ModLoader, Tag},
+    validation::ValidatorChain,
+};
 
-/// S3 bucket state. This contains references to the buckets used by the server.
+/// A function that reads a [`ProjectManifest`] from the start of an uploaded file.
+pub type ManifestExtractor = Box<dyn Fn(Bytes) -> Option<ProjectManifest> + Send + Sync>;
+
+/// Bucket state. This contains references to the buckets used by the server.
+/// These can be backed by any storage backend (see [`modhost_config::StorageBackend`]).
 #[derive(Clone)]
 pub struct BucketState {
     /// A reference to the bucket for projects.
-    pub projects: AmazonS3,
+    pub projects: Bucket,
 
     /// A reference to the bucket for gallery images.
-    pub gallery: AmazonS3,
+    pub gallery: Bucket,
+
+    /// A signer for presigned project download URLs.
+    /// This is only present when presigned downloads are enabled.
+    pub projects_signer: Option<BucketSigner>,
+
+    /// A signer for presigned gallery image URLs.
+    /// This is only present when presigned downloads are enabled.
+    pub gallery_signer: Option<BucketSigner>,
 }
 
 /// The server's shared state.
@@ -30,8 +45,10 @@ pub struct AppState {
     /// The database pool.
     pub pool: DbPool,
 
-    /// The [`BasicClient`] for GitHub OAuth2 calls.
-    pub auth: BasicClient<EndpointSet, EndpointNotSet, EndpointNotSet, EndpointNotSet, EndpointSet>,
+    /// The providers users can log in with.
+    /// Providers are enabled in the config, and more can be added with
+    /// [`modhost::ModHost::auth_provider`].
+    pub auth: AuthProviders,
 
     /// References to buckets used by the server.
     pub buckets: BucketState,
@@ -39,11 +56,15 @@ pub struct AppState {
     /// The app's configuration.
     pub config: AppConfig,
 
+    /// Permits for reading stored archives (for previews and diffs), so only
+    /// `storage.max_archive_reads` of them are worked on at once.
+    pub archive_reads: Arc<Semaphore>,
+
     /// A list of available mod loaders.
     /// This is set with [`modhost::ModHost::loaders`].
     pub loaders: Vec<ModLoader>,
 
-    /// A list of available game versions.
+    /// A list of available game versions, ordered from newest to oldest.
     /// This is set with [`modhost::ModHost::versions`].
     pub game_versions: Vec<GameVersion>,
 
@@ -54,10 +75,15 @@ pub struct AppState {
     /// The Meilisearch service, used for the search endpoint.
     pub search: MeilisearchService,
 
-    /// A verifier method the server uses to verify files when uploading.
-    /// This should be able to verify based on bytes alone (check the file headers).
-    /// This function returns a [`bool`] indicating whether the file is valid or not.
-    pub verifier: Arc<Box<dyn Fn(Bytes) -> bool + Send + Sync>>,
+    /// The validators the server uses to check primary files when uploading.
+    /// This is set with [`modhost::ModHost::validator`].
+    pub validator: ValidatorChain,
+
+    /// An extractor method the server uses to read a [`ProjectManifest`] from uploaded files.
+    /// Like the upload validators, this only receives the first `storage.verify_buffer_size` bytes
+    /// of the primary file. The manifest fills in version metadata that an upload leaves out.
+    /// This is set with [`modhost::ModHost::extractor`].
+    pub extractor: Option<Arc<ManifestExtractor>>,
 
     /// The data URL of the icon PNG file (`data:image/png;base64,...`).
     pub icon_png_data_url: String,
@@ -68,12 +94,7 @@ pub struct AppState {
 
 impl AppState {
     /// Instantiate a new [`AppState`] instance.
-    pub async fn new(
-        pool: DbPool,
-        config: &AppConfig,
-        verifier: Box<dyn Fn(Bytes) -> bool + Send + Sync>,
-        api_spec: OpenApi,
-    ) -> Result<Self> {
+    pub async fn new(pool: DbPool, config: &AppConfig, api_spec: OpenApi) -> Result<Self> {
         let icon_data = if config.ui.favicon_png == "default" {
             DEFAULT_FAVICON_PNG.to_vec()
         } else if !config.ui.favicon_png.starts_with("http") {
@@ -90,16 +111,20 @@ impl AppState {
 
         Ok(Self {
             pool,
-            auth: config.auth.github()?,
+            auth: AuthProviders::new(&config.auth),
             buckets: BucketState {
                 projects: config.storage.projects()?,
                 gallery: config.storage.gallery()?,
+                projects_signer: config.storage.projects_signer()?,
+                gallery_signer: config.storage.gallery_signer()?,
             },
             config: config.clone(),
+            archive_reads: Arc::new(Semaphore::new(config.storage.max_archive_reads)),
             loaders: vec![],
             game_versions: vec![],
             tags: vec![],
-            verifier: Arc::new(verifier),
+            validator: ValidatorChain::default(),
+            extractor: None,
             search: MeilisearchService::new(config)?,
             icon_png_data_url: format!("data:image/png;base64,{}", icon_b64),
             api_spec,
@@ -0,0 +1,133 @@
+//! Validation for uploaded project files.
+
+use axum::body::Bytes;
+use modhost_core::{AppError, Result};
+use std::sync::Arc;
+
+/// Re-exported so validators can be implemented without depending on `async-trait`.
+pub use async_trait::async_trait;
+
+/// The result of validating an upload.
+#[derive(
+    Debug, Clone, PartialEq, Eq, Hash, Default, Serialize, Deserialize, ToSchema, ToResponse,
+)]
+#[serde(tag = "status", content = "reasons", rename_all = "snake_case")]
+pub enum ValidationVerdict {
+    /// The upload is valid.
+    #[default]
+    Accepted,
+
+    /// The upload is valid, but has some problems the author should know about.
+    Warned(Vec<String>),
+
+    /// The upload is invalid, for the given reasons.
+    Rejected(Vec<String>),
+}
+
+impl ValidationVerdict {
+    /// Reject an upload for a single reason.
+    pub fn reject(reason: impl Into<String>) -> Self {
+        Self::Rejected(vec![reason.into()])
+    }
+
+    /// Accept an upload with a single warning.
+    pub fn warn(warning: impl Into<String>) -> Self {
+        Self::Warned(vec![warning.into()])
+    }
+
+    /// Whether the upload was rejected.
+    pub fn is_rejected(&self) -> bool {
+        matches!(self, Self::Rejected(_))
+    }
+
+    /// Combine two verdicts. A rejection wins over warnings, which win over an
+    /// acceptance. Reasons from verdicts of the same kind are kept together.
+    pub fn merge(self, other: Self) -> Self {
+        match (self, other) {
+            (Self::Rejected(mut a), Self::Rejected(b)) => {
+                a.extend(b);
+                Self::Rejected(a)
+            }
+
+            (Self::Warned(mut a), Self::Warned(b)) => {
+                a.extend(b);
+                Self::Warned(a)
+            }
+
+            (Self::Rejected(a), _) | (_, Self::Rejected(a)) => Self::Rejected(a),
+            (Self::Warned(a), _) | (_, Self::Warned(a)) => Self::Warned(a),
+            (Self::Accepted, Self::Accepted) => Self::Accepted,
+        }
+    }
+
+    /// Turn a rejection into an [`AppError::UploadRejected`] error.
+    /// If the upload was accepted, this returns its warnings.
+    pub fn check(self) -> Result<Vec<String>> {
+        match self {
+            Self::Accepted => Ok(Vec::new()),
+            Self::Warned(warnings) => Ok(warnings),
+            Self::Rejected(reasons) => Err(AppError::UploadRejected(reasons)),
+        }
+    }
+}
+
+/// A validator for uploaded project files.
+///
+/// Uploads are streamed into storage, so validators only receive the first
+/// `storage.verify_buffer_size` bytes of the file, which may be truncated.
+///
+/// Any `Fn(Bytes) -> bool` is a validator, which rejects files it returns `false` for.
+#[async_trait]
+pub trait UploadValidator: Send + Sync {
+    /// Validate an uploaded file.
+    async fn validate(&self, file_name: &str, bytes: Bytes) -> Result<ValidationVerdict>;
+}
+
+#[async_trait]
+impl<F: Fn(Bytes) -> bool + Send + Sync> UploadValidator for F {
+    async fn validate(&self, file_name: &str, bytes: Bytes) -> Result<ValidationVerdict> {
+        if self(bytes) {
+            Ok(ValidationVerdict::Accepted)
+        } else {
+            Ok(ValidationVerdict::reject(format!(
+                "{} isn't a valid project file!",
+                file_name
+            )))
+        }
+    }
+}
+
+/// A chain of validators. Every validator is run on each upload,
+/// and their verdicts are merged (see [`ValidationVerdict::merge`]).
+/// An empty chain accepts everything.
+#[derive(Clone, Default)]
+pub struct ValidatorChain {
+    /// The validators, in the order they run.
+    validators: Vec<Arc<dyn UploadValidator>>,
+}
+
+impl ValidatorChain {
+    /// Add a validator to the end of the chain.
+    pub fn push(&mut self, validator: impl UploadValidator + 'static) {
+        self.validators.push(Arc::new(validator));
+    }
+
+    /// Add a validator to the end of the chain.
+    pub fn with(mut self, validator: impl UploadValidator + 'static) -> Self {
+        self.push(validator);
+        self
+    }
+}
+
+#[async_trait]
+impl UploadValidator for ValidatorChain {
+    async fn validate(&self, file_name: &str, bytes: Bytes) -> Result<ValidationVerdict> {
+        let mut verdict = ValidationVerdict::Accepted;
+
+        for validator in &self.validators {
+            verdict = verdict.merge(validator.validate(file_name, bytes.clone()).await?);
+        }
+
+        Ok(verdict)
+    }
+}
@@ -4,17 +4,32 @@
 #[macro_use]
 extern crate tracing;
 
+pub use modhost_auth::providers::{AuthProvider, OAuthClient, ProviderIdentity};
 pub use modhost_core::{Result, logger::*};
-pub use modhost_server_core::{loader, loaders, models::*, tag, tags};
-
-use axum::{Router, body::Bytes, extract::connect_info::IntoMakeServiceWithConnectInfo, serve};
+pub use modhost_server_core::{
+    loader, loaders,
+    models::*,
+    tag, tags,
+    validation::{UploadValidator, ValidationVerdict, ValidatorChain, async_trait},
+};
+
+use axum::{Router, extract::connect_info::IntoMakeServiceWithConnectInfo, serve};
 use jsglue::{glue::Glue, util::is_debug};
-use modhost_config::{AppConfig, get_config};
-use modhost_db::{DbPool, create_connection, run_migrations};
+use modhost_auth::local::{check_password, create_local_admin, local_auth_config};
+use modhost_config::{AppConfig, LocalAuthConfig, get_config};
+use modhost_db::{DbPool, User, create_connection, run_migrations};
+use modhost_db_util::files::backfill_file_hashes;
 use modhost_router::{create_api_spec, create_router};
 use modhost_search::MeiliProject;
-use modhost_server_core::{glue::make_glue, state::AppState, worker::run_worker};
-use std::net::{IpAddr, SocketAddr};
+use modhost_server_core::{
+    glue::make_glue,
+    state::{AppState, ManifestExtractor},
+    worker::run_worker,
+};
+use std::{
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+};
 use tokio::{join, net::TcpListener, task::JoinHandle};
 use utoipa::openapi::OpenApi;
 
@@ -47,11 +62,15 @@ pub struct ModHost {
     /// The join handle for the stats thread.
     #[allow(dead_code)]
     stats_thread: JoinHandle<Result<()>>,
+
+    /// The join handle for the storage garbage collection thread.
+    #[allow(dead_code)]
+    gc_thread: JoinHandle<Result<()>>,
 }
 
 impl ModHost {
     /// Create a new server instance.
-    pub async fn new(verifier: Box<dyn Fn(Bytes) -> bool + Send + Sync>) -> Result<Self> {
+    pub async fn new() -> Result<Self> {
         modhost_core::core_init();
 
         info!("Starting app...");
@@ -66,11 +85,31 @@ impl ModHost {
         info!("Creating state...");
 
         let api_spec = create_api_spec(&config);
-        let state = AppState::new(pool.clone(), &config, verifier, api_spec.clone()).await?;
+        let state = AppState::new(pool.clone(), &config, api_spec.clone()).await?;
 
         info!("Running migrations...");
 
         run_migrations(&pool).await?;
+
+        info!("Backfilling file hashes...");
+
+        let backfill_pool = pool.clone();
+        let backfill_bucket = state.buckets.projects.clone();
+
+        tokio::spawn(async move {
+            let result = async {
+                let mut conn = backfill_pool.get().await?;
+
+                backfill_file_hashes(backfill_bucket.as_ref(), &mut conn).await
+            }
+            .await;
+
+            match result {
+                Ok(0) => {}
+                Ok(count) => info!("Backfilled hashes for {} file(s).", count),
+                Err(err) => error!("Failed to backfill file hashes: {}", err),
+            }
+        });
         state.search.ensure_setup().await?;
 
         let index = state.search.projects();
@@ -87,6 +126,10 @@ impl ModHost {
 
         let stats_thread = modhost_router::util::stats::start_stats_thread(&state);
 
+        info!("Starting storage garbage collection thread...");
+
+        let gc_thread = modhost_router::util::gc::start_gc_thread(&state);
+
         info!("Getting listen address...");
 
         let ip: IpAddr = config.server.host.parse()?;
@@ -101,11 +144,15 @@ impl ModHost {
             api_spec,
             router: None,
             stats_thread,
+            gc_thread,
         })
     }
 
     /// Set the game versions for the API.
-    pub fn versions(mut self, vers: Vec<GameVersion>) -> Self {
+    /// These should be ordered from newest to oldest. Versions with release dates
+    /// are sorted by them, so version ranges can be expanded correctly.
+    pub fn versions(mut self, mut vers: Vec<GameVersion>) -> Self {
+        sort_game_versions(&mut vers);
         self.state.game_versions = vers;
         self
     }
@@ -122,10 +169,80 @@ impl ModHost {
         self
     }
 
+    /// Add a validator for uploaded files.
+    /// Validators run in the order they are added, and every one of them
+    /// has to accept a file for it to be uploaded.
+    /// Any `Fn(Bytes) -> bool` can be used as a validator.
+    pub fn validator(mut self, validator: impl UploadValidator + 'static) -> Self {
+        self.state.validator.push(validator);
+        self
+    }
+
+    /// Add a login provider, on top of the ones enabled in the config.
+    /// This replaces any provider with the same ID.
+    pub fn auth_provider(mut self, provider: impl AuthProvider + 'static) -> Self {
+        self.state.auth.push(provider);
+        self
+    }
+
+    /// Create a local admin account, or make an existing local account an admin
+    /// and reset its password. This is how the first admin is set up on servers
+    /// that don't use an external login provider.
+    /// Only the config and the database are set up for this, so it doesn't start
+    /// any of the server's background tasks.
+    pub async fn create_admin(
+        username: impl AsRef<str>,
+        password: impl AsRef<str>,
+    ) -> Result<User> {
+        modhost_core::core_init();
+
+        info!("Getting config...");
+
+        let config = get_config()?;
+
+        info!("Connecting to the database (async pool)...");
+
+        let pool = create_connection(Some(config.postgres.uri())).await?;
+
+        info!("Running migrations...");
+
+        run_migrations(&pool).await?;
+
+        let local = match local_auth_config(&config.auth) {
+            Some(local) => local,
+
+            None => {
+                warn!(
+                    "Local accounts aren't enabled! Configure `auth.local` to log in with this one."
+                );
+                LocalAuthConfig::default()
+            }
+        };
+
+        check_password(password.as_ref(), &local)?;
+
+        let user = create_local_admin(username.as_ref(), password.as_ref(), &mut pool.get().await?)
+            .await?;
+
+        info!("Created admin {} (id: {}).", user.username, user.id);
+
+        Ok(user)
+    }
+
+    /// Set the manifest extractor for the API.
+    /// When a version is uploaded, this reads a [`modhost_db::ProjectManifest`] from its primary file,
+    /// which is used to fill in the version number, loaders, game versions, and relations.
+    pub fn extractor(mut self, extractor: ManifestExtractor) -> Self {
+        self.state.extractor = Some(Arc::new(extractor));
+        self
+    }
+
     /// Register the router.
     /// - If you are registering versions, run this AFTER you run [`Self::versions`].
     /// - If you are registering loaders, run this AFTER you run [`Self::loaders`].
     /// - If you are registering tags, run this AFTER you run [`Self::tags`].
+    /// - If you are registering validators, run this AFTER you run [`Self::validator`].
+    /// - If you are registering an extractor, run this AFTER you run [`Self::extractor`].
     pub fn router(mut self) -> Self {
         info!("Registering routes...");
 
@@ -6,6 +6,7 @@ pub extern crate clap_complete;
 pub extern crate clap_verbosity_flag;
 pub extern crate dotenvy;
 pub extern crate modhost;
+pub extern crate rpassword;
 pub extern crate tokio;
 
 /// The QuickHost macro.
@@ -14,7 +15,8 @@ pub extern crate tokio;
 ///
 /// ```rs
 /// use axum::body::Bytes;
-/// use modhost::{GameVersion, Result};
+/// use modhost::{GameVersion, Result, UploadValidator, ValidationVerdict, async_trait};
+/// use modhost_db::ProjectManifest;
 ///
 /// async fn some_function_that_returns_versions() -> Result<Vec<GameVersion>> {
 ///     Ok(vec![])
@@ -26,11 +28,29 @@ pub extern crate tokio;
 ///     true
 /// }
 ///
+/// struct ReadmeValidator;
+///
+/// #[async_trait]
+/// impl UploadValidator for ReadmeValidator {
+///     async fn validate(&self, file_name: &str, _bytes: Bytes) -> Result<ValidationVerdict> {
+///         // Validators can reject files with reasons, or accept them with warnings.
+///         Ok(ValidationVerdict::warn(format!("{} doesn't have a readme.", file_name)))
+///     }
+/// }
+///
+/// fn extract_project_manifest(_bytes: Bytes) -> Option<ProjectManifest> {
+///     // Read the project's manifest from the bytes, if it has one.
+///     // This will let ModHost fill in version metadata automatically.
+///     None
+/// }
+///
 /// quickhost::quickhost! {
 ///     versions = [crate::some_function_that_returns_versions().await?];
 ///     loaders = [modhost::loaders!["This", "Can", "Also", "Be", "A", "Function", "Like", "Above"]];
 ///     tags = [modhost::tags![]]; // This can also be omitted if you don't have tags.
 ///     verifier = [crate::verify_project_file]; // This line can be omitted to always return true.
+///     validator = [crate::ReadmeValidator]; // This line can be repeated, or omitted.
+///     extractor = [crate::extract_project_manifest]; // This line can be omitted if you don't have manifests.
 /// }
 /// ```
 #[macro_export]
@@ -39,10 +59,12 @@ macro_rules! quickhost {
         versions = [$($versions: tt)*];
         loaders = [$($loaders: tt)*];
         $(tags = [$($tags: tt)*];)?
-        verifier = [$($verifier: tt)*];
+        $(verifier = [$($verifier: tt)*];)?
+        $(validator = [$($validator: tt)*];)*
+        $(extractor = [$($extractor: tt)*];)?
     } => {
         mod __quickhost_impl {
-            use $crate::clap::{self, Parser, CommandFactory, Command};
+            use $crate::clap::{self, Parser, Subcommand, CommandFactory, Command};
             use $crate::clap_verbosity_flag::{Verbosity, InfoLevel};
             use $crate::clap_complete::{Shell, Generator, generate};
             use $crate::modhost::{ModHost, Result, init_logger, from_log_level};
@@ -57,6 +79,23 @@ macro_rules! quickhost {
 
                 #[arg(short = 'C', long)]
                 complete: Option<Shell>,
+
+                #[command(subcommand)]
+                command: Option<QuickHostCommand>,
+            }
+
+            #[derive(Debug, Clone, Subcommand)]
+            enum QuickHostCommand {
+                /// Create a local admin account, or make an existing local account an admin.
+                CreateAdmin {
+                    /// The admin's username.
+                    username: String,
+
+                    /// The admin's password. If this isn't given, it's read from
+                    /// `MODHOST_ADMIN_PASSWORD`, or prompted for.
+                    #[arg(short, long)]
+                    password: Option<String>,
+                },
             }
 
             impl QuickHostCli {
@@ -74,11 +113,26 @@ macro_rules! quickhost {
 
                     let _guard = init_logger("modhost-server", from_log_level(self.verbose.log_level_filter()))?;
 
-                    ModHost::new(Box::new($($verifier)*))
+                    if let Some(QuickHostCommand::CreateAdmin { username, password }) = self.command {
+                        let password = match password.or_else(|| std::env::var("MODHOST_ADMIN_PASSWORD").ok()) {
+                            Some(password) => password,
+
+                            None => $crate::rpassword::prompt_password(format!("Password for {}: ", username))?,
+                        };
+
+                        ModHost::create_admin(username, password).await?;
+
+                        return Ok(());
+                    }
+
+                    ModHost::new()
                         .await?
                         .versions($($versions)*)
                         .loaders($($loaders)*)
                         $(.tags($($tags)*))?
+                        $(.validator($($verifier)*))?
+                        $(.validator($($validator)*))*
+                        $(.extractor(Box::new($($extractor)*)))?
                         .router()
                         .run()
                         .await?;
@@ -100,19 +154,4 @@ macro_rules! quickhost {
                 .block_on(__quickhost_impl::run())
         }
     };
-
-    {
-        versions = [$($versions: tt)*];
-        loaders = [$($loaders: tt)*];
-        $(tags = [$($tags: tt)*];)?
-    } => {
-        fn __gen_verify(_: $crate::axum::body::Bytes) -> bool { true }
-
-        $crate::quickhost! {
-            versions = [$($versions)*];
-            loaders = [$($loaders)*];
-            $(tags = [$($tags)*];)?
-            verifier = [super::__gen_verify];
-        }
-    };
 }